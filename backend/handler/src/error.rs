@@ -1,4 +1,8 @@
-use axum::{Json, http::StatusCode, response::IntoResponse};
+use axum::{
+    Json,
+    http::{HeaderValue, StatusCode, header::RETRY_AFTER},
+    response::IntoResponse,
+};
 use serde::Serialize;
 use usecase::errors::app_error::{AppError, ErrorStatus};
 
@@ -23,10 +27,21 @@ impl UsecaseError {
             error: AppError::unauthorized(Some(message)),
         }
     }
+    pub fn forbidden(message: &str) -> Self {
+        UsecaseError {
+            error: AppError::forbidden(Some(message)),
+        }
+    }
+    pub fn too_many_requests(retry_after: u64) -> Self {
+        UsecaseError {
+            error: AppError::too_many_requests(None, retry_after),
+        }
+    }
 }
 
 impl IntoResponse for UsecaseError {
     fn into_response(self) -> axum::response::Response {
+        let mut retry_after = None;
         let (status, code, message) = match self.error.status {
             ErrorStatus::NotFound => (StatusCode::NOT_FOUND, "NOT_FOUND", self.error.message),
             ErrorStatus::AlreadyExist => {
@@ -40,10 +55,25 @@ impl IntoResponse for UsecaseError {
             ErrorStatus::Unauthorized => {
                 (StatusCode::UNAUTHORIZED, "UNAUTHORIZED", self.error.message)
             }
+            ErrorStatus::Forbidden => (StatusCode::FORBIDDEN, "FORBIDDEN", self.error.message),
             ErrorStatus::Invalid => (StatusCode::BAD_REQUEST, "INVALID", self.error.message),
+            ErrorStatus::TooManyRequests { retry_after: secs } => {
+                retry_after = Some(secs);
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "TOO_MANY_REQUESTS",
+                    self.error.message,
+                )
+            }
         };
 
-        (status, Json(ErrorBody { code, message })).into_response()
+        let mut response = (status, Json(ErrorBody { code, message })).into_response();
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
     }
 }
 
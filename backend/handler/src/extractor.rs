@@ -1,20 +1,32 @@
 use axum::RequestPartsExt;
 use axum::extract::FromRequestParts;
+use axum::http::header::{AUTHORIZATION, USER_AGENT};
 use axum::http::request::Parts;
 use axum_extra::TypedHeader;
 use axum_extra::extract::CookieJar;
 use axum_extra::headers::Authorization;
 use axum_extra::headers::authorization::Bearer;
+use std::convert::Infallible;
+use std::marker::PhantomData;
 use std::sync::Arc;
+use uuid::Uuid;
 
-use usecase::model::user::User;
+use axum::http::HeaderMap;
+use usecase::model::user::{ClientInfo, Permission, User, csrf_tokens_match};
 use usecase::service::service::Service;
 use usecase::service::user::user_service::UserService;
 
 use crate::error::UsecaseError;
+use crate::rate_limit::client_ip;
 
+const MAX_USER_AGENT_LENGTH: usize = 256;
+// ログイン時にcsrf_tokenのCookieで渡した値を、状態を変えるリクエストではこのヘッダーで送り返させる
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+// session_idのCookieでログインしている利用者。APIトークンは受け付けない
 pub struct AuthorizedUser {
     pub access_token: String,
+    pub session_id: Uuid,
     pub user: User,
 }
 
@@ -34,16 +46,174 @@ impl FromRequestParts<Arc<Service>> for AuthorizedUser {
             .map(|val| val.value().to_string())
             .unwrap_or_default();
 
-        let user_id = service
-            .fetch_user_id_by_token(access_token.clone())
+        let session = service
+            .fetch_session_by_token(access_token.clone())
             .await
             .map_err(|_| UsecaseError::unauthorized("unauthorized error"))?;
 
+        // 他のサイトからのリクエストにもCookieは付くので、ページのスクリプトしか読めない値で確かめる
+        if !parts.method.is_safe() {
+            let csrf_token = parts
+                .headers
+                .get(CSRF_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            if !session.verify_csrf_token(csrf_token) {
+                return Err(UsecaseError::forbidden("CSRF token is missing or invalid"));
+            }
+        }
+
         let user = service
-            .get_user(user_id)
+            .get_user(session.user_id)
             .await
             .map_err(|_| UsecaseError::unauthorized("unauthorized error"))?;
 
-        Ok(Self { access_token, user })
+        Ok(Self {
+            access_token,
+            session_id: session.session_id,
+            user,
+        })
+    }
+}
+
+// CSRFヘッダーがcsrf_tokenのCookieと一致するリクエスト
+//
+// セッションを確かめられないルート(リフレッシュ)で使う。他のサイトからはCookieを読めないので、
+// 同じ値をヘッダーに入れられるのはページのスクリプトだけになる
+pub struct CsrfChecked;
+
+impl<S: Send + Sync> FromRequestParts<S> for CsrfChecked {
+    type Rejection = UsecaseError;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        verify_csrf_cookie(&parts.headers)?;
+        Ok(Self)
+    }
+}
+
+fn verify_csrf_cookie(headers: &HeaderMap) -> Result<(), UsecaseError> {
+    let jar = CookieJar::from_headers(headers);
+    let cookie = jar
+        .get("csrf_token")
+        .map(|cookie| cookie.value())
+        .unwrap_or_default();
+    let header = headers
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !csrf_tokens_match(cookie, header) {
+        return Err(UsecaseError::forbidden("CSRF token is missing or invalid"));
+    }
+    Ok(())
+}
+
+// ルートに必要な権限を型で表す
+pub trait RequiredPermission {
+    const PERMISSION: Permission;
+}
+
+// 記事を書き、画像をアップロードする
+pub struct WriteBlogs;
+
+impl RequiredPermission for WriteBlogs {
+    const PERMISSION: Permission = Permission::WriteBlogs;
+}
+
+// 役割がPの権限を持つ利用者。認証できなければ401、権限がなければ403を返す
+//
+// Authorizationヘッダーがあれば個人用のAPIトークンで、なければsession_idのCookieで認証する。
+// APIトークンではPをスコープに含むトークンだけを受け付ける
+pub struct Permitted<P>(pub User, pub PhantomData<P>);
+
+impl<P: RequiredPermission> FromRequestParts<Arc<Service>> for Permitted<P> {
+    type Rejection = UsecaseError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        service: &Arc<Service>,
+    ) -> Result<Self, Self::Rejection> {
+        let user = if parts.headers.contains_key(AUTHORIZATION) {
+            let TypedHeader(Authorization(bearer)) = parts
+                .extract::<TypedHeader<Authorization<Bearer>>>()
+                .await
+                .map_err(|_| UsecaseError::unauthorized("authorization must be a bearer token"))?;
+            let (user, token) = service
+                .authenticate_api_token(bearer.token())
+                .await
+                .map_err(|_| UsecaseError::unauthorized("unauthorized error"))?;
+            if !token.allows(P::PERMISSION) {
+                return Err(UsecaseError::forbidden(
+                    "The api token does not have the required scope",
+                ));
+            }
+            user
+        } else {
+            AuthorizedUser::from_request_parts(parts, service)
+                .await?
+                .user
+        };
+        // トークンを作った後に役割が変わっていることもあるので、役割も確かめる
+        if !user.role.permits(P::PERMISSION) {
+            return Err(UsecaseError::forbidden(
+                "You do not have permission to do this",
+            ));
+        }
+        Ok(Self(user, PhantomData))
+    }
+}
+
+// リクエストしてきた端末の情報。セッション一覧に表示するためのもので、認証には使わない
+pub struct RequestClient(pub ClientInfo);
+
+impl FromRequestParts<Arc<Service>> for RequestClient {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        service: &Arc<Service>,
+    ) -> Result<Self, Self::Rejection> {
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+        let user_agent =
+            header(USER_AGENT.as_str()).map(|ua| ua.chars().take(MAX_USER_AGENT_LENGTH).collect());
+        // 頻度の制限と同じく、信頼できるプロキシからの接続でだけX-Forwarded-Forを見る
+        let ip = client_ip(
+            &parts.headers,
+            &parts.extensions,
+            &service.config.trusted_proxies,
+        )
+        .map(|ip| ip.to_string());
+        Ok(Self(ClientInfo { user_agent, ip }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header::COOKIE;
+
+    fn headers(cookie: &str, csrf: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(COOKIE, cookie.parse().unwrap());
+        if let Some(csrf) = csrf {
+            headers.insert(CSRF_HEADER, csrf.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn refresh_without_csrf_header_is_rejected() {
+        let cookie = "refresh_token=r; csrf_token=abc123";
+
+        assert!(verify_csrf_cookie(&headers(cookie, None)).is_err());
+        assert!(verify_csrf_cookie(&headers(cookie, Some("abc124"))).is_err());
+        assert!(verify_csrf_cookie(&headers("refresh_token=r", Some(""))).is_err());
+        assert!(verify_csrf_cookie(&headers(cookie, Some("abc123"))).is_ok());
     }
 }
@@ -1,18 +1,23 @@
 use axum::{
     Json,
-    extract::{Multipart, Query, State},
+    extract::{Multipart, Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
 };
 use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::error;
 
+use crate::extractor::{Permitted, WriteBlogs};
+use crate::model::blog::{BlogDetailResponse, BlogListResponse, BlogResponse};
 use crate::model::image::ImageResponse;
-use crate::{extractor::AuthorizedUser, model::blog::BlogResponse};
 
 use super::error::UsecaseError;
 use super::handler::Handler;
-use super::model::blog::CreateBlogRequest;
-use usecase::model::blog::BlogRequest;
+use super::model::blog::{CreateBlogRequest, DeleteBlogQuery, DraftRequest, PatchBlogRequest};
+use usecase::model::blog::{BlogPatch, BlogRequest, SlugLookup};
+use usecase::model::slug;
+use usecase::model::user::User;
 use usecase::service::blog::blog_service::BlogService;
 use usecase::service::service::Service;
 
@@ -20,35 +25,70 @@ impl Handler {
     pub async fn get_blogs(
         Query(params): Query<HashMap<String, String>>,
         state: State<Arc<Service>>,
-    ) -> Json<serde_json::Value> {
-        let year = params.get("year");
-        let month = params.get("month");
+    ) -> Result<Json<BlogListResponse>, UsecaseError> {
+        let service = state.0.clone();
+
+        let result = service
+            .get_blogs(
+                params.get("year"),
+                params.get("month"),
+                params.get("cursor"),
+                params.get("limit"),
+            )
+            .await;
+        if let Err(ref e) = result {
+            error!("Failed to get blogs: {}", e.message);
+        }
+        Ok(Json(result?.into()))
+    }
 
+    pub async fn get_blog(
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+    ) -> Result<Json<BlogDetailResponse>, UsecaseError> {
         let service = state.0.clone();
 
-        service.get_blogs(year, month);
+        let result = service.get_blog(&id).await;
+        if let Err(ref e) = result {
+            error!("Failed to get blog: {}, error: {}", id, e.message);
+        }
+        Ok(Json(result?.into()))
+    }
+
+    // 旧スラッグでアクセスされた場合は、現在のスラッグのURLへ恒久的に転送する
+    pub async fn get_blog_by_slug(
+        state: State<Arc<Service>>,
+        Path(slug): Path<String>,
+    ) -> Result<Response, UsecaseError> {
+        let service = state.0.clone();
 
-        Json(serde_json::json!({
-            "status": "success",
-            "data": {
-                "id": params.get("id").unwrap_or(&"unknown".to_string())
+        let result = service.get_blog_by_slug(&slug).await;
+        if let Err(ref e) = result {
+            error!("Failed to get blog by slug: {}, error: {}", slug, e.message);
+        }
+        match result? {
+            SlugLookup::Found(detail) => Ok(Json(BlogDetailResponse::from(detail)).into_response()),
+            SlugLookup::Redirect(current) => {
+                let location = format!("/api/blogs/slug/{}", slug::encode(&current));
+                Ok(Redirect::permanent(&location).into_response())
             }
-        }))
+        }
     }
 
     pub async fn create_blog(
-        _: AuthorizedUser,
+        Permitted(user, _): Permitted<WriteBlogs>,
         state: State<Arc<Service>>,
         Json(req): Json<CreateBlogRequest>,
     ) -> Result<Json<BlogResponse>, UsecaseError> {
         let blog_req = BlogRequest {
             title: req.title,
+            slug: req.slug,
             content: req.content,
         };
 
         let service = state.0.clone();
 
-        let result = service.create_blog(blog_req).await;
+        let result = service.create_blog(&user, blog_req).await;
         if let Err(ref e) = result {
             error!("Failed to create blog: {}", e.message);
         }
@@ -56,8 +96,136 @@ impl Handler {
         Ok(Json(blog.into()))
     }
 
+    pub async fn create_draft(
+        Permitted(user, _): Permitted<WriteBlogs>,
+        state: State<Arc<Service>>,
+        Json(req): Json<DraftRequest>,
+    ) -> Result<(StatusCode, Json<BlogResponse>), UsecaseError> {
+        let draft = BlogRequest {
+            title: req.title,
+            slug: req.slug,
+            content: req.content,
+        };
+
+        let service = state.0.clone();
+
+        let result = service.create_draft(&user, draft).await;
+        if let Err(ref e) = result {
+            error!("Failed to create draft: {}", e.message);
+        }
+        Ok((StatusCode::CREATED, Json(result?.into())))
+    }
+
+    pub async fn update_draft(
+        Permitted(user, _): Permitted<WriteBlogs>,
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+        Json(req): Json<DraftRequest>,
+    ) -> Result<Json<BlogResponse>, UsecaseError> {
+        let draft = BlogRequest {
+            title: req.title,
+            slug: req.slug,
+            content: req.content,
+        };
+
+        let service = state.0.clone();
+
+        let result = service.update_draft(&user, &id, draft).await;
+        if let Err(ref e) = result {
+            error!("Failed to update draft: {}, error: {}", id, e.message);
+        }
+        Ok(Json(result?.into()))
+    }
+
+    pub async fn publish_blog(
+        Permitted(user, _): Permitted<WriteBlogs>,
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+    ) -> Result<Json<BlogResponse>, UsecaseError> {
+        let service = state.0.clone();
+
+        let result = service.publish_blog(&user, &id).await;
+        if let Err(ref e) = result {
+            error!("Failed to publish blog: {}, error: {}", id, e.message);
+        }
+        Ok(Json(result?.into()))
+    }
+
+    pub async fn unpublish_blog(
+        Permitted(user, _): Permitted<WriteBlogs>,
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+    ) -> Result<Json<BlogResponse>, UsecaseError> {
+        let service = state.0.clone();
+
+        let result = service.unpublish_blog(&user, &id).await;
+        if let Err(ref e) = result {
+            error!("Failed to unpublish blog: {}, error: {}", id, e.message);
+        }
+        Ok(Json(result?.into()))
+    }
+
+    pub async fn put_blog(
+        Permitted(user, _): Permitted<WriteBlogs>,
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+        Json(req): Json<CreateBlogRequest>,
+    ) -> Result<Json<BlogResponse>, UsecaseError> {
+        let patch = BlogPatch {
+            title: Some(req.title),
+            slug: req.slug,
+            content: Some(req.content),
+        };
+        Self::update_blog(user, state, id, patch).await
+    }
+
+    pub async fn patch_blog(
+        Permitted(user, _): Permitted<WriteBlogs>,
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+        Json(req): Json<PatchBlogRequest>,
+    ) -> Result<Json<BlogResponse>, UsecaseError> {
+        let patch = BlogPatch {
+            title: req.title,
+            slug: req.slug,
+            content: req.content,
+        };
+        Self::update_blog(user, state, id, patch).await
+    }
+
+    async fn update_blog(
+        user: User,
+        state: State<Arc<Service>>,
+        id: String,
+        patch: BlogPatch,
+    ) -> Result<Json<BlogResponse>, UsecaseError> {
+        let service = state.0.clone();
+
+        let result = service.update_blog(&user, &id, patch).await;
+        if let Err(ref e) = result {
+            error!("Failed to update blog: {}, error: {}", id, e.message);
+        }
+        Ok(Json(result?.into()))
+    }
+
+    pub async fn delete_blog(
+        Permitted(user, _): Permitted<WriteBlogs>,
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+        Query(query): Query<DeleteBlogQuery>,
+    ) -> Result<StatusCode, UsecaseError> {
+        let service = state.0.clone();
+
+        let result = service.delete_blog(&user, &id, query.purge).await;
+        if let Err(ref e) = result {
+            error!("Failed to delete blog: {}, error: {}", id, e.message);
+        }
+        result?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+
     pub async fn upload_blog_image(
-        _: AuthorizedUser,
+        Permitted(user, _): Permitted<WriteBlogs>,
         state: State<Arc<Service>>,
         mut multipart: Multipart,
     ) -> Result<Json<ImageResponse>, UsecaseError> {
@@ -76,11 +244,28 @@ impl Handler {
             }
             let service = state.0.clone();
             return service
-                .upload_blog_image(data)
+                .upload_blog_image(&user, data)
                 .await
                 .map(|image| Json(image.into()))
                 .map_err(UsecaseError::from);
         }
         Err(UsecaseError::bad_request("No image field in multipart"))
     }
+
+    pub async fn get_uploaded_image(
+        Path(name): Path<String>,
+        state: State<Arc<Service>>,
+    ) -> Result<Response, UsecaseError> {
+        let service = state.0.clone();
+        let (data, format) = service.get_uploaded_image(&name).await?;
+        // 同じ名前の画像は書き換えないので、長くキャッシュさせる
+        Ok((
+            [
+                (header::CONTENT_TYPE, format.mime_type()),
+                (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+            ],
+            data,
+        )
+            .into_response())
+    }
 }
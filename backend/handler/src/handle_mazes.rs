@@ -0,0 +1,137 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State, rejection::QueryRejection},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use super::error::UsecaseError;
+use super::extractor::AuthorizedUser;
+use super::handler::Handler;
+use super::model::maze::{MazeQuery, MazeResponse, SaveMazeBody, SavedMazeResponse, parse_format};
+use usecase::model::maze::{MazeBody, MazeFormat, MazeRequest, SaveMazeRequest};
+use usecase::service::maze::maze_service::MazeService;
+use usecase::service::service::Service;
+
+const SEED_HEADER: &str = "x-maze-seed";
+
+impl Handler {
+    pub async fn get_maze(
+        headers: HeaderMap,
+        state: State<Arc<Service>>,
+        query: Result<Query<MazeQuery>, QueryRejection>,
+    ) -> Result<Response, UsecaseError> {
+        let Query(query) = query.map_err(|e| UsecaseError::bad_request(&e.body_text()))?;
+        let format = match query.format.as_deref() {
+            Some(format) => parse_format(format)
+                .ok_or_else(|| UsecaseError::bad_request("format must be json, svg or png"))?,
+            None => format_from_accept(&headers),
+        };
+
+        let req = MazeRequest {
+            rows: query.rows,
+            cols: query.cols,
+            maze_type: query.maze_type,
+            seed: query.seed,
+            format,
+        };
+
+        let service = state.0.clone();
+        let result = service.generate_maze(req).await;
+        if let Err(ref e) = result {
+            error!("Failed to generate maze: {}", e.message);
+        }
+        let generated = result?;
+
+        let seed = HeaderValue::from(generated.seed);
+        let mut response = match generated.body {
+            MazeBody::Json(maze) => Json(MazeResponse {
+                rows: maze.rows(),
+                cols: maze.cols(),
+                maze_type: generated.maze_type.to_string(),
+                seed: generated.seed.to_string(),
+                walls: maze.walls().to_vec(),
+            })
+            .into_response(),
+            MazeBody::Svg(svg) => ([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response(),
+            MazeBody::Png(png) => ([(header::CONTENT_TYPE, "image/png")], png).into_response(),
+        };
+        response.headers_mut().insert(SEED_HEADER, seed);
+        Ok(response)
+    }
+
+    pub async fn save_maze(
+        user: AuthorizedUser,
+        state: State<Arc<Service>>,
+        Json(body): Json<SaveMazeBody>,
+    ) -> Result<(StatusCode, Json<SavedMazeResponse>), UsecaseError> {
+        let seed = body
+            .seed
+            .map(|seed| seed.parse::<u64>())
+            .transpose()
+            .map_err(|_| UsecaseError::bad_request("seed must be an unsigned 64-bit integer"))?;
+        let req = SaveMazeRequest {
+            title: body.title,
+            rows: body.rows,
+            cols: body.cols,
+            maze_type: body.maze_type,
+            seed,
+        };
+
+        let service = state.0.clone();
+        let maze = service.save_maze(user.user.id, req).await?;
+        Ok((StatusCode::CREATED, Json(maze.into())))
+    }
+
+    pub async fn get_saved_mazes(
+        user: AuthorizedUser,
+        state: State<Arc<Service>>,
+    ) -> Result<Json<Vec<SavedMazeResponse>>, UsecaseError> {
+        let service = state.0.clone();
+        let mazes = service.get_saved_mazes(user.user.id).await?;
+        Ok(Json(mazes.into_iter().map(Into::into).collect()))
+    }
+
+    pub async fn get_saved_maze(
+        user: AuthorizedUser,
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+    ) -> Result<Json<SavedMazeResponse>, UsecaseError> {
+        let maze_id = parse_maze_id(&id)?;
+        let service = state.0.clone();
+        let maze = service.get_saved_maze(user.user.id, maze_id).await?;
+        Ok(Json(maze.into()))
+    }
+
+    pub async fn delete_saved_maze(
+        user: AuthorizedUser,
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+    ) -> Result<StatusCode, UsecaseError> {
+        let maze_id = parse_maze_id(&id)?;
+        let service = state.0.clone();
+        service.delete_saved_maze(user.user.id, maze_id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
+
+fn parse_maze_id(id: &str) -> Result<Uuid, UsecaseError> {
+    Uuid::parse_str(id).map_err(|_| UsecaseError::bad_request("maze id must be a uuid"))
+}
+
+// formatが指定されていない場合は、Acceptヘッダーの先頭から対応している形式を選ぶ
+fn format_from_accept(headers: &HeaderMap) -> MazeFormat {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .and_then(|accept| {
+            accept
+                .split(',')
+                .filter_map(|media| media.split(';').next())
+                .find_map(|media| parse_format(media.trim()))
+        })
+        .unwrap_or(MazeFormat::Json)
+}
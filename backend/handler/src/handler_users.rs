@@ -1,21 +1,26 @@
 use crate::error::UsecaseError;
-use crate::extractor::AuthorizedUser;
-use crate::model::user::{LoginRequest, LoginResponse};
+use crate::extractor::{AuthorizedUser, CsrfChecked, RequestClient};
+use crate::model::user::{
+    ApiTokenResponse, CreateApiTokenRequest, CreatedApiTokenResponse, LoginRequest, LoginResponse,
+    SessionResponse,
+};
 
 use axum::http::StatusCode;
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
-use usecase::model::user::Token;
+use usecase::model::user::{ApiTokenRequest, IssuedTokens};
 use usecase::service::service::Service;
 use usecase::service::user::user_service::UserService;
 
 use super::handler::Handler;
-use axum::extract::{Json, State};
+use axum::extract::{Json, Path, State};
 use std::sync::Arc;
 use tracing::error;
+use uuid::Uuid;
 
 impl Handler {
     pub async fn login_admin(
         jar: CookieJar,
+        RequestClient(client): RequestClient,
         state: State<Arc<Service>>,
         Json(req): Json<LoginRequest>,
     ) -> Result<(CookieJar, StatusCode), UsecaseError> {
@@ -23,28 +28,30 @@ impl Handler {
 
         let (username, password) = (req.username, req.password);
 
-        let result = service.login(&username, &password).await;
+        let result = service.login(&username, &password, client).await;
         if let Err(ref e) = result {
             error!("Failed to login: {}", e.message);
         }
-        let (token, refresh_token) = result?;
-        let is_prod = service.config.env == "prod".to_string();
-
-        let session_cookie = Cookie::build(("session_id", token.access_token.clone()))
-            .path("/")
-            .http_only(true)
-            .secure(is_prod)
-            .same_site(SameSite::Strict)
-            .build();
-
-        let refresh_cookie = Cookie::build(("refresh_token", refresh_token))
-            .path("/users")
-            .http_only(true)
-            .secure(is_prod)
-            .same_site(SameSite::Strict)
-            .build();
-
-        let jar = jar.add(session_cookie).add(refresh_cookie);
+        let jar = set_session_cookies(jar, &service, result?);
+        Ok((jar, StatusCode::OK))
+    }
+
+    pub async fn refresh(
+        jar: CookieJar,
+        _: CsrfChecked,
+        state: State<Arc<Service>>,
+    ) -> Result<(CookieJar, StatusCode), UsecaseError> {
+        let service = state.0.clone();
+        let refresh_token = jar
+            .get("refresh_token")
+            .map(|cookie| cookie.value().to_string())
+            .ok_or_else(|| UsecaseError::unauthorized("refresh token is missing"))?;
+
+        let result = service.refresh(&refresh_token).await;
+        if let Err(ref e) = result {
+            error!("Failed to refresh token: {}", e.message);
+        }
+        let jar = set_session_cookies(jar, &service, result?);
         Ok((jar, StatusCode::OK))
     }
 
@@ -54,14 +61,136 @@ impl Handler {
         state: State<Arc<Service>>,
     ) -> Result<(CookieJar, StatusCode), UsecaseError> {
         let service = state.0.clone();
-        let token = Token {
-            id: user.user.id,
-            access_token: user.access_token,
+        service.logout(user.user.id, user.session_id).await?;
+
+        let jar = clear_session_cookies(jar);
+
+        Ok((jar, StatusCode::NO_CONTENT))
+    }
+
+    pub async fn get_sessions(
+        user: AuthorizedUser,
+        state: State<Arc<Service>>,
+    ) -> Result<Json<Vec<SessionResponse>>, UsecaseError> {
+        let service = state.0.clone();
+        let sessions = service.list_sessions(user.user.id).await?;
+        Ok(Json(
+            sessions
+                .into_iter()
+                .map(|session| SessionResponse::new(session, user.session_id))
+                .collect(),
+        ))
+    }
+
+    pub async fn delete_session(
+        jar: CookieJar,
+        user: AuthorizedUser,
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+    ) -> Result<(CookieJar, StatusCode), UsecaseError> {
+        let session_id = Uuid::parse_str(&id)
+            .map_err(|_| UsecaseError::bad_request("session id must be a uuid"))?;
+        let service = state.0.clone();
+        service.revoke_session(user.user.id, session_id).await?;
+
+        // 今のセッションを消したなら、ログアウトと同じくCookieも消す
+        let jar = if session_id == user.session_id {
+            clear_session_cookies(jar)
+        } else {
+            jar
         };
-        service.logout(token).await?;
+        Ok((jar, StatusCode::NO_CONTENT))
+    }
 
-        let jar = jar.remove("session_id").remove("refresh_token");
+    pub async fn delete_sessions(
+        jar: CookieJar,
+        user: AuthorizedUser,
+        state: State<Arc<Service>>,
+    ) -> Result<(CookieJar, StatusCode), UsecaseError> {
+        let service = state.0.clone();
+        let result = service.revoke_all_sessions(user.user.id).await;
+        if let Err(ref e) = result {
+            error!("Failed to revoke sessions: {}", e.message);
+        }
+        result?;
 
+        let jar = clear_session_cookies(jar);
         Ok((jar, StatusCode::NO_CONTENT))
     }
+
+    // APIトークンの管理はブラウザのセッションからだけ行い、漏れたトークンで新しいトークンを作らせない
+    pub async fn create_api_token(
+        user: AuthorizedUser,
+        state: State<Arc<Service>>,
+        Json(req): Json<CreateApiTokenRequest>,
+    ) -> Result<(StatusCode, Json<CreatedApiTokenResponse>), UsecaseError> {
+        let req = ApiTokenRequest {
+            name: req.name,
+            scopes: req.scopes,
+            expires_in_days: req.expires_in_days,
+        };
+        let service = state.0.clone();
+        let result = service.create_api_token(&user.user, req).await;
+        if let Err(ref e) = result {
+            error!("Failed to create api token: {}", e.message);
+        }
+        Ok((StatusCode::CREATED, Json(result?.into())))
+    }
+
+    pub async fn get_api_tokens(
+        user: AuthorizedUser,
+        state: State<Arc<Service>>,
+    ) -> Result<Json<Vec<ApiTokenResponse>>, UsecaseError> {
+        let service = state.0.clone();
+        let tokens = service.list_api_tokens(user.user.id).await?;
+        Ok(Json(tokens.into_iter().map(Into::into).collect()))
+    }
+
+    pub async fn delete_api_token(
+        user: AuthorizedUser,
+        state: State<Arc<Service>>,
+        Path(id): Path<String>,
+    ) -> Result<StatusCode, UsecaseError> {
+        let token_id = Uuid::parse_str(&id)
+            .map_err(|_| UsecaseError::bad_request("api token id must be a uuid"))?;
+        let service = state.0.clone();
+        service.revoke_api_token(user.user.id, token_id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
+
+fn set_session_cookies(jar: CookieJar, service: &Service, tokens: IssuedTokens) -> CookieJar {
+    // 平文のHTTPで開発するときだけ、COOKIE_SECURE=falseで外す
+    let secure = service.config.cookie_secure;
+
+    let session_cookie = Cookie::build(("session_id", tokens.token.access_token))
+        .path("/")
+        .http_only(true)
+        .secure(secure)
+        .same_site(SameSite::Strict)
+        .build();
+
+    // リフレッシュトークンは/users以下でしか送らせない
+    let refresh_cookie = Cookie::build(("refresh_token", tokens.refresh_token))
+        .path("/users")
+        .http_only(true)
+        .secure(secure)
+        .same_site(SameSite::Strict)
+        .build();
+
+    // フロントエンドのスクリプトが読んでX-CSRF-Tokenヘッダーに入れるので、HttpOnlyにしない
+    let csrf_cookie = Cookie::build(("csrf_token", tokens.csrf_token))
+        .path("/")
+        .http_only(false)
+        .secure(secure)
+        .same_site(SameSite::Strict)
+        .build();
+
+    jar.add(session_cookie).add(refresh_cookie).add(csrf_cookie)
+}
+
+fn clear_session_cookies(jar: CookieJar) -> CookieJar {
+    jar.remove("session_id")
+        .remove("refresh_token")
+        .remove("csrf_token")
 }
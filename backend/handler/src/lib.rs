@@ -1,6 +1,8 @@
 pub mod error;
 pub mod extractor;
 pub mod handle_blogs;
+pub mod handle_mazes;
 pub mod handler;
 pub mod handler_users;
 pub mod model;
+pub mod rate_limit;
@@ -1,8 +1,36 @@
-use usecase::model::blog::Blog;
+use usecase::model::blog::{Blog, BlogDetail, BlogPage, TocEntry};
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct CreateBlogRequest {
     pub title: String,
+    // 省略した場合はタイトルから作る
+    #[serde(default)]
+    pub slug: Option<String>,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct PatchBlogRequest {
+    pub title: Option<String>,
+    pub slug: Option<String>,
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct DeleteBlogQuery {
+    // trueの場合はR2の本文と画像も含めて完全に削除する
+    #[serde(default)]
+    pub purge: bool,
+}
+
+// 下書きは題名や本文が空のまま保存できる
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct DraftRequest {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default)]
     pub content: String,
 }
 
@@ -10,8 +38,12 @@ pub struct CreateBlogRequest {
 pub struct BlogResponse {
     pub id: String,
     pub title: String,
+    pub slug: String,
     pub content_key: String,
     pub status: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub published_at: Option<String>,
 }
 
 impl From<Blog> for BlogResponse {
@@ -19,8 +51,71 @@ impl From<Blog> for BlogResponse {
         Self {
             id: blog.id.to_string(),
             title: blog.title,
+            slug: blog.slug,
             content_key: blog.content_key,
             status: blog.status.to_string(),
+            created_at: blog.created_at.and_utc().to_rfc3339(),
+            updated_at: blog.updated_at.and_utc().to_rfc3339(),
+            published_at: blog.published_at.map(|at| at.and_utc().to_rfc3339()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct BlogListResponse {
+    pub items: Vec<BlogResponse>,
+    pub next_cursor: Option<String>,
+}
+
+impl From<BlogPage> for BlogListResponse {
+    fn from(page: BlogPage) -> Self {
+        Self {
+            items: page.blogs.into_iter().map(BlogResponse::from).collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct BlogDetailResponse {
+    #[serde(flatten)]
+    pub blog: BlogResponse,
+    // 編集用のMarkdown
+    pub content: String,
+    // 表示用のサニタイズ済みHTML
+    pub html: String,
+    pub toc: Vec<TocEntryResponse>,
+}
+
+impl From<BlogDetail> for BlogDetailResponse {
+    fn from(detail: BlogDetail) -> Self {
+        Self {
+            blog: detail.blog.into(),
+            content: detail.content,
+            html: detail.rendered.html,
+            toc: detail
+                .rendered
+                .toc
+                .into_iter()
+                .map(TocEntryResponse::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct TocEntryResponse {
+    pub level: u8,
+    pub id: String,
+    pub title: String,
+}
+
+impl From<TocEntry> for TocEntryResponse {
+    fn from(entry: TocEntry) -> Self {
+        Self {
+            level: entry.level,
+            id: entry.id,
+            title: entry.title,
         }
     }
 }
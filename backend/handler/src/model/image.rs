@@ -1,9 +1,13 @@
-use usecase::model::image::Image;
+use usecase::model::image::{Image, ImageVariant};
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct ImageResponse {
     pub id: String,
     pub url: String,
+    pub width: u32,
+    pub height: u32,
+    pub mime_type: String,
+    pub variants: Vec<ImageVariantResponse>,
 }
 
 impl From<Image> for ImageResponse {
@@ -11,6 +15,35 @@ impl From<Image> for ImageResponse {
         ImageResponse {
             id: image.id,
             url: image.url,
+            width: image.width,
+            height: image.height,
+            mime_type: image.mime_type,
+            variants: image
+                .variants
+                .into_iter()
+                .map(ImageVariantResponse::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ImageVariantResponse {
+    pub name: String,
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+    pub mime_type: String,
+}
+
+impl From<ImageVariant> for ImageVariantResponse {
+    fn from(variant: ImageVariant) -> Self {
+        ImageVariantResponse {
+            name: variant.name,
+            url: variant.url,
+            width: variant.width,
+            height: variant.height,
+            mime_type: variant.mime_type,
         }
     }
 }
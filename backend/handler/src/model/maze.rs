@@ -0,0 +1,67 @@
+use usecase::model::maze::{MazeFormat, SavedMaze};
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct MazeQuery {
+    pub rows: usize,
+    pub cols: usize,
+    #[serde(rename = "type")]
+    pub maze_type: Option<String>,
+    pub seed: Option<u64>,
+    pub format: Option<String>,
+}
+
+// JSONで返す迷路。seedはJSの数値で精度が落ちないよう文字列にする
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct MazeResponse {
+    pub rows: usize,
+    pub cols: usize,
+    #[serde(rename = "type")]
+    pub maze_type: String,
+    pub seed: String,
+    pub walls: Vec<u8>,
+}
+
+pub fn parse_format(format: &str) -> Option<MazeFormat> {
+    match format {
+        "json" | "application/json" => Some(MazeFormat::Json),
+        "svg" | "image/svg+xml" => Some(MazeFormat::Svg),
+        "png" | "image/png" => Some(MazeFormat::Png),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct SaveMazeBody {
+    pub title: Option<String>,
+    pub rows: usize,
+    pub cols: usize,
+    #[serde(rename = "type")]
+    pub maze_type: Option<String>,
+    pub seed: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct SavedMazeResponse {
+    pub id: String,
+    pub title: Option<String>,
+    #[serde(rename = "type")]
+    pub maze_type: String,
+    pub seed: String,
+    pub rows: usize,
+    pub cols: usize,
+    pub created_at: String,
+}
+
+impl From<SavedMaze> for SavedMazeResponse {
+    fn from(maze: SavedMaze) -> Self {
+        Self {
+            id: maze.id.to_string(),
+            title: maze.title,
+            maze_type: maze.maze_type.to_string(),
+            seed: maze.seed.to_string(),
+            rows: maze.rows,
+            cols: maze.cols,
+            created_at: maze.created_at.and_utc().to_rfc3339(),
+        }
+    }
+}
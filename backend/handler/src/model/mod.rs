@@ -1,3 +1,4 @@
 pub mod blog;
 pub mod image;
+pub mod maze;
 pub mod user;
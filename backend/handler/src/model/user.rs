@@ -1,4 +1,5 @@
-use usecase::model::user::{Token, User};
+use usecase::model::user::{ApiToken, NewApiToken, Session, Token, User};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct LoginRequest {
@@ -35,3 +36,76 @@ impl From<Token> for LoginResponse {
         }
     }
 }
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionResponse {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: String,
+    // このリクエストを送ってきたセッションならtrue
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn new(session: Session, current_session_id: Uuid) -> Self {
+        Self {
+            id: session.id.to_string(),
+            user_agent: session.user_agent,
+            ip: session.ip,
+            created_at: session.created_at.and_utc().to_rfc3339(),
+            current: session.id == current_session_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    // "blogs:write"や"blogs:manage"
+    pub scopes: Vec<String>,
+    // 省略すると期限なし
+    pub expires_in_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ApiTokenResponse {
+    pub id: String,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+}
+
+impl From<ApiToken> for ApiTokenResponse {
+    fn from(token: ApiToken) -> Self {
+        Self {
+            id: token.id.to_string(),
+            name: token.name,
+            prefix: token.prefix,
+            scopes: token.scopes.iter().map(ToString::to_string).collect(),
+            created_at: token.created_at.and_utc().to_rfc3339(),
+            expires_at: token.expires_at.map(|at| at.and_utc().to_rfc3339()),
+            last_used_at: token.last_used_at.map(|at| at.and_utc().to_rfc3339()),
+        }
+    }
+}
+
+// 発行したときだけ、平文のトークンを返す
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CreatedApiTokenResponse {
+    #[serde(flatten)]
+    pub api_token: ApiTokenResponse,
+    pub token: String,
+}
+
+impl From<NewApiToken> for CreatedApiTokenResponse {
+    fn from(created: NewApiToken) -> Self {
+        Self {
+            api_token: created.token.into(),
+            token: created.secret,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// 接続元のIPごとにリクエストの頻度を制限する
+//
+// IPは接続元のアドレスを使う。TRUSTED_PROXIESに含まれるプロキシからの接続に限り、
+// X-Forwarded-Forを右からたどって接続元を探す。セッション一覧やログインの制限に使うIPも同じ方法で決める
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{Extensions, HeaderMap, Request};
+use axum::response::{IntoResponse, Response};
+use governor::middleware::NoOpMiddleware;
+use shared::config::{RateLimitConfig, TrustedProxies};
+use tower_governor::governor::{GovernorConfig, GovernorConfigBuilder};
+use tower_governor::key_extractor::KeyExtractor;
+use tower_governor::{GovernorError, GovernorLayer};
+use tracing::error;
+use usecase::errors::app_error::AppError;
+
+use super::error::UsecaseError;
+
+type Limiter = GovernorConfig<ClientIpKeyExtractor, NoOpMiddleware>;
+
+// 接続元のIP。axum::serveにConnectInfo<SocketAddr>を渡していないと取れない
+pub fn client_ip(
+    headers: &HeaderMap,
+    extensions: &Extensions,
+    trusted_proxies: &TrustedProxies,
+) -> Option<IpAddr> {
+    let ConnectInfo(peer) = extensions.get::<ConnectInfo<SocketAddr>>()?;
+    // ヘッダーが複数行に分かれていても、付けられた順に1つの並びとして読む
+    let forwarded_for: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .collect();
+    Some(trusted_proxies.client_ip(peer.ip(), forwarded_for.into_iter()))
+}
+
+#[derive(Clone)]
+pub struct ClientIpKeyExtractor {
+    trusted_proxies: Arc<TrustedProxies>,
+}
+
+impl KeyExtractor for ClientIpKeyExtractor {
+    type Key = IpAddr;
+
+    fn extract<T>(&self, req: &Request<T>) -> Result<Self::Key, GovernorError> {
+        client_ip(req.headers(), req.extensions(), &self.trusted_proxies)
+            .ok_or(GovernorError::UnableToExtractKey)
+    }
+}
+
+// ルーターごとに作ると、それぞれ別に数える
+#[derive(Clone)]
+pub struct RateLimit {
+    config: Arc<Limiter>,
+}
+
+impl RateLimit {
+    pub fn new(config: &RateLimitConfig, trusted_proxies: &TrustedProxies) -> Self {
+        let config = GovernorConfigBuilder::default()
+            .period(Duration::from_secs(1) / config.per_second)
+            .burst_size(config.burst)
+            .key_extractor(ClientIpKeyExtractor {
+                trusted_proxies: Arc::new(trusted_proxies.clone()),
+            })
+            .finish()
+            .expect("rate limit must be greater than 0");
+        Self {
+            config: Arc::new(config),
+        }
+    }
+
+    pub fn layer(&self) -> GovernorLayer<ClientIpKeyExtractor, NoOpMiddleware, Body> {
+        GovernorLayer::new(self.config.clone()).error_handler(into_response)
+    }
+
+    // 上限に達していないIPの記録を捨てる。定期的に呼ばないと、来たIPの数だけメモリが増え続ける
+    pub fn retain_recent(&self) {
+        self.config.limiter().retain_recent();
+    }
+}
+
+fn into_response(e: GovernorError) -> Response {
+    match e {
+        GovernorError::TooManyRequests { wait_time, .. } => {
+            UsecaseError::too_many_requests(wait_time).into_response()
+        }
+        _ => {
+            error!("Failed to apply rate limit: {}", e);
+            UsecaseError::from(AppError::internal(None)).into_response()
+        }
+    }
+}
@@ -1,11 +1,46 @@
+// 起動時に読み込む設定
+//
+// 環境変数と、CONFIG_FILEで指定したTOMLファイルの両方から読み込む。同じ項目があれば環境変数を優先する。
+// 足りない値や不正な値は1つ目で止めず、全て集めてから起動を中止する
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use ipnet::IpNet;
+use thiserror::Error;
+
 pub struct DatabaseConfig {
     pub url: String,
     pub max_connection: u32,
 }
 
+#[derive(Clone)]
 pub struct StorageConfig {
+    // 記事の本文と変換済みのHTMLを置くバケット
     pub blog_bucket: String,
+    // 記事に貼る画像を置くバケット
     pub blog_image_bucket: String,
+    pub backend: StorageBackend,
+}
+
+// 本文や画像の保存先
+#[derive(Clone)]
+pub enum StorageBackend {
+    R2 {
+        account_id: String,
+        access_key_id: String,
+        secret_access_key: String,
+    },
+    // Cloudflareなしで開発するときに使う。root以下にバケットごとのディレクトリを作る
+    Local {
+        root: PathBuf,
+    },
+    // テスト用。プロセスが終わると消える
+    Memory,
 }
 
 #[derive(Clone)]
@@ -14,6 +49,93 @@ pub struct Config {
     pub env: String,
     pub token_ttl: u64,
     pub refresh_ttl: u64,
+    pub password_peppers: Peppers,
+    // 認証のCookieにSecureを付ける。平文のHTTPで開発するときだけfalseにする
+    pub cookie_secure: bool,
+    // X-Forwarded-Forを信じてよい前段のプロキシ
+    pub trusted_proxies: TrustedProxies,
+}
+
+// 前段のプロキシのアドレス。ここからの接続に限り、X-Forwarded-Forから接続元を探す
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrustedProxies(Vec<IpNet>);
+
+impl TrustedProxies {
+    pub fn new(networks: Vec<IpNet>) -> Self {
+        Self(networks)
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.0.iter().any(|network| network.contains(ip))
+    }
+
+    // 接続元のIPを決める。X-Forwarded-Forの値は、付けられた順に並べて渡す
+    //
+    // 左側は利用者が自由に書けるので、右(自分に近い側)から信頼できるプロキシを飛ばしていき、
+    // 最初に出てきた信頼できないアドレスを接続元とする
+    pub fn client_ip<'a>(
+        &self,
+        peer: IpAddr,
+        forwarded_for: impl DoubleEndedIterator<Item = &'a str>,
+    ) -> IpAddr {
+        let mut client = peer;
+        if !self.contains(&client) {
+            return client;
+        }
+        for hop in forwarded_for.rev() {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) => client = ip,
+                // 読めない値より左は信用しない
+                Err(_) => break,
+            }
+            if !self.contains(&client) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+// パスワードに混ぜる秘密の値。idはハッシュに書き込み、入れ替えた後も古いハッシュを確かめられるようにする
+#[derive(Clone)]
+pub struct Pepper {
+    pub id: String,
+    pub secret: String,
+}
+
+impl Pepper {
+    pub fn new(id: &str, secret: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            secret: secret.to_string(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Peppers {
+    // 新しく作るハッシュに使う
+    pub current: Pepper,
+    // 入れ替える前のもの。古いハッシュを確かめるためだけに使う
+    pub old: Vec<Pepper>,
+}
+
+impl Peppers {
+    pub fn new(current: Pepper) -> Self {
+        Self {
+            current,
+            old: Vec::new(),
+        }
+    }
+
+    // 今のものを先にして全て返す
+    pub fn iter(&self) -> impl Iterator<Item = &Pepper> {
+        std::iter::once(&self.current).chain(&self.old)
+    }
+
+    pub fn find(&self, id: &[u8]) -> Option<&Pepper> {
+        self.iter().find(|pepper| pepper.id.as_bytes() == id)
+    }
 }
 
 pub struct RedisConfig {
@@ -21,6 +143,24 @@ pub struct RedisConfig {
     pub port: String,
 }
 
+// 接続元のIPごとのリクエスト数の上限
+#[derive(Clone)]
+pub struct RateLimitConfig {
+    // 1秒あたりに補充するリクエスト数
+    pub per_second: u32,
+    // 間を空けずに送れるリクエスト数
+    pub burst: u32,
+}
+
+// SIGTERMなどで止めるときの待ち時間(秒)
+#[derive(Clone)]
+pub struct ShutdownConfig {
+    // readinessを503にしてから新しい接続を断るまで。ロードバランサーが振り分けをやめるのを待つ
+    pub delay: u64,
+    // 新しい接続を断ってから、処理中のリクエストを待つ上限
+    pub timeout: u64,
+}
+
 impl DatabaseConfig {
     pub fn new(url: String, max_connection: u32) -> Self {
         Self {
@@ -29,3 +169,574 @@ impl DatabaseConfig {
         }
     }
 }
+
+// アプリケーション全体の設定
+pub struct AppConfig {
+    pub config: Config,
+    pub database: DatabaseConfig,
+    pub storage: StorageConfig,
+    pub redis: RedisConfig,
+    pub rate_limit: RateLimitConfig,
+    pub shutdown: ShutdownConfig,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid configuration:\n  {}", .0.join("\n  "))]
+pub struct ConfigError(pub Vec<String>);
+
+// 設定項目ごとの、環境変数の名前とTOMLファイル中の位置([section]のkey)
+struct Key {
+    env: &'static str,
+    section: &'static str,
+    name: &'static str,
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ([{}] {})", self.env, self.section, self.name)
+    }
+}
+
+const fn key(env: &'static str, section: &'static str, name: &'static str) -> Key {
+    Key { env, section, name }
+}
+
+const PAGE_HOST: Key = key("PAGE_HOST", "server", "host");
+const ENV: Key = key("ENV", "server", "env");
+const TOKEN_TTL: Key = key("TOKEN_TTL", "auth", "token_ttl");
+const REFRESH_TTL: Key = key("REFRESH_TTL", "auth", "refresh_ttl");
+const PASSWORD_PEPPER: Key = key("PASSWORD_PEPPER", "auth", "password_pepper");
+const PASSWORD_PEPPER_ID: Key = key("PASSWORD_PEPPER_ID", "auth", "password_pepper_id");
+const PASSWORD_OLD_PEPPERS: Key = key("PASSWORD_OLD_PEPPERS", "auth", "old_password_peppers");
+const COOKIE_SECURE: Key = key("COOKIE_SECURE", "auth", "cookie_secure");
+const TRUSTED_PROXIES: Key = key("TRUSTED_PROXIES", "server", "trusted_proxies");
+const DATABASE_URL: Key = key("DATABASE_URL", "database", "url");
+const DATABASE_MAX_CONNECTIONS: Key =
+    key("DATABASE_MAX_CONNECTIONS", "database", "max_connections");
+const REDIS_HOST: Key = key("REDIS_HOST", "redis", "host");
+const REDIS_PORT: Key = key("REDIS_PORT", "redis", "port");
+const STORAGE_BACKEND: Key = key("STORAGE_BACKEND", "storage", "backend");
+const BLOG_BUCKET: Key = key("BLOG_BUCKET", "storage", "blog_bucket");
+const BLOG_IMAGE_BUCKET: Key = key("BLOG_IMAGE_BUCKET", "storage", "blog_image_bucket");
+const STORAGE_LOCAL_ROOT: Key = key("STORAGE_LOCAL_ROOT", "storage", "local_root");
+const CLOUDFLARE_ACCOUNT_ID: Key = key("CLOUDFLARE_ACCOUNT_ID", "storage", "account_id");
+const CLOUDFLARE_ACCESS_KEY_ID: Key = key("CLOUDFLARE_ACCESS_KEY_ID", "storage", "access_key_id");
+const CLOUDFLARE_SECRET_ACCESS_KEY: Key = key(
+    "CLOUDFLARE_SECRET_ACCESS_KEY",
+    "storage",
+    "secret_access_key",
+);
+const RATE_LIMIT_PER_SECOND: Key = key("RATE_LIMIT_PER_SECOND", "rate_limit", "per_second");
+const RATE_LIMIT_BURST: Key = key("RATE_LIMIT_BURST", "rate_limit", "burst");
+const SHUTDOWN_DELAY_SECONDS: Key = key("SHUTDOWN_DELAY_SECONDS", "shutdown", "delay_seconds");
+const SHUTDOWN_TIMEOUT_SECONDS: Key =
+    key("SHUTDOWN_TIMEOUT_SECONDS", "shutdown", "timeout_seconds");
+
+const DEFAULT_MAX_CONNECTIONS: u32 = 5;
+const DEFAULT_RATE_LIMIT_PER_SECOND: u32 = 10;
+const DEFAULT_RATE_LIMIT_BURST: u32 = 50;
+const DEFAULT_SHUTDOWN_DELAY_SECONDS: u64 = 5;
+const DEFAULT_SHUTDOWN_TIMEOUT_SECONDS: u64 = 30;
+const DEFAULT_BUCKET: &str = "blog-assets";
+const DEFAULT_PEPPER_ID: &str = "1";
+// Argon2のPHC文字列に書けるkeyidの長さの上限
+const MAX_PEPPER_ID_BYTES: usize = 8;
+
+impl AppConfig {
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut loader = Loader {
+            env: env::vars().collect(),
+            file: toml::Table::new(),
+            errors: Vec::new(),
+        };
+        if let Ok(path) = env::var("CONFIG_FILE") {
+            match std::fs::read_to_string(&path) {
+                Ok(text) => loader.read_file(&path, &text),
+                Err(e) => loader.errors.push(format!("CONFIG_FILE {path}: {e}")),
+            }
+        }
+        loader.load()
+    }
+}
+
+struct Loader {
+    env: HashMap<String, String>,
+    file: toml::Table,
+    errors: Vec<String>,
+}
+
+impl Loader {
+    fn read_file(&mut self, path: &str, text: &str) {
+        match text.parse::<toml::Table>() {
+            Ok(file) => self.file = file,
+            Err(e) => self.errors.push(format!("CONFIG_FILE {path}: {e}")),
+        }
+    }
+
+    fn load(mut self) -> Result<AppConfig, ConfigError> {
+        let config = Config {
+            host: self.required(&PAGE_HOST),
+            env: self.required(&ENV),
+            token_ttl: self.positive(&TOKEN_TTL),
+            refresh_ttl: self.positive(&REFRESH_TTL),
+            password_peppers: self.peppers(),
+            cookie_secure: self.parsed_or(&COOKIE_SECURE, true),
+            trusted_proxies: self.trusted_proxies(),
+        };
+        if config.token_ttl > 0 && config.refresh_ttl > 0 && config.refresh_ttl < config.token_ttl {
+            self.errors.push(format!(
+                "{} must not be shorter than {}",
+                REFRESH_TTL, TOKEN_TTL
+            ));
+        }
+
+        let database = DatabaseConfig::new(
+            self.required(&DATABASE_URL),
+            self.positive_or(&DATABASE_MAX_CONNECTIONS, DEFAULT_MAX_CONNECTIONS),
+        );
+
+        let redis = RedisConfig {
+            host: self.required(&REDIS_HOST),
+            port: self.required(&REDIS_PORT),
+        };
+        if !redis.port.is_empty() && redis.port.parse::<u16>().is_err() {
+            self.errors
+                .push(format!("{} must be a port number", REDIS_PORT));
+        }
+
+        let storage = self.storage();
+
+        let rate_limit = RateLimitConfig {
+            per_second: self.positive_or(&RATE_LIMIT_PER_SECOND, DEFAULT_RATE_LIMIT_PER_SECOND),
+            burst: self.positive_or(&RATE_LIMIT_BURST, DEFAULT_RATE_LIMIT_BURST),
+        };
+
+        let shutdown = ShutdownConfig {
+            delay: self.parsed_or(&SHUTDOWN_DELAY_SECONDS, DEFAULT_SHUTDOWN_DELAY_SECONDS),
+            timeout: self.positive_or(&SHUTDOWN_TIMEOUT_SECONDS, DEFAULT_SHUTDOWN_TIMEOUT_SECONDS),
+        };
+
+        if self.errors.is_empty() {
+            Ok(AppConfig {
+                config,
+                database,
+                storage,
+                redis,
+                rate_limit,
+                shutdown,
+            })
+        } else {
+            Err(ConfigError(self.errors))
+        }
+    }
+
+    // PASSWORD_OLD_PEPPERSは"id:secret"をカンマで区切って並べる
+    fn peppers(&mut self) -> Peppers {
+        let current = Pepper {
+            id: self
+                .value(&PASSWORD_PEPPER_ID)
+                .unwrap_or_else(|| DEFAULT_PEPPER_ID.to_string()),
+            secret: self.required(&PASSWORD_PEPPER),
+        };
+        let mut peppers = Peppers::new(current);
+        for entry in self
+            .value(&PASSWORD_OLD_PEPPERS)
+            .unwrap_or_default()
+            .split(',')
+        {
+            match entry.trim().split_once(':') {
+                Some((id, secret)) if !id.is_empty() && !secret.is_empty() => {
+                    peppers.old.push(Pepper::new(id, secret))
+                }
+                _ if entry.trim().is_empty() => {}
+                _ => self.errors.push(format!(
+                    "{} must be a comma-separated list of id:secret",
+                    PASSWORD_OLD_PEPPERS
+                )),
+            }
+        }
+
+        let mut ids = Vec::new();
+        for pepper in peppers.iter() {
+            if pepper.id.len() > MAX_PEPPER_ID_BYTES {
+                self.errors.push(format!(
+                    "pepper id {:?} must be at most {MAX_PEPPER_ID_BYTES} bytes",
+                    pepper.id
+                ));
+            }
+            if ids.contains(&&pepper.id) {
+                self.errors
+                    .push(format!("pepper id {:?} is used more than once", pepper.id));
+            }
+            ids.push(&pepper.id);
+        }
+        peppers
+    }
+
+    // TRUSTED_PROXIESはIPアドレスかCIDRをカンマで区切って並べる。未設定なら接続元のアドレスだけを使う
+    fn trusted_proxies(&mut self) -> TrustedProxies {
+        let mut networks = Vec::new();
+        for entry in self.value(&TRUSTED_PROXIES).unwrap_or_default().split(',') {
+            let entry = entry.trim();
+            if entry.is_empty() {
+                continue;
+            }
+            match entry
+                .parse::<IpNet>()
+                .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+            {
+                Ok(network) => networks.push(network),
+                Err(_) => self.errors.push(format!(
+                    "{} must be a comma-separated list of IP addresses or CIDRs, got {entry:?}",
+                    TRUSTED_PROXIES
+                )),
+            }
+        }
+        TrustedProxies::new(networks)
+    }
+
+    // STORAGE_BACKENDで保存先を選ぶ。未指定ならR2を使う
+    fn storage(&mut self) -> StorageConfig {
+        let backend = match self.value(&STORAGE_BACKEND).as_deref() {
+            Some("r2") | None => StorageBackend::R2 {
+                account_id: self.required(&CLOUDFLARE_ACCOUNT_ID),
+                access_key_id: self.required(&CLOUDFLARE_ACCESS_KEY_ID),
+                secret_access_key: self.required(&CLOUDFLARE_SECRET_ACCESS_KEY),
+            },
+            Some("local") => StorageBackend::Local {
+                root: PathBuf::from(self.required(&STORAGE_LOCAL_ROOT)),
+            },
+            Some("memory") => StorageBackend::Memory,
+            Some(other) => {
+                self.errors.push(format!(
+                    "{} must be one of r2, local or memory, got {other:?}",
+                    STORAGE_BACKEND
+                ));
+                StorageBackend::Memory
+            }
+        };
+
+        StorageConfig {
+            blog_bucket: self
+                .value(&BLOG_BUCKET)
+                .unwrap_or_else(|| DEFAULT_BUCKET.to_string()),
+            blog_image_bucket: self
+                .value(&BLOG_IMAGE_BUCKET)
+                .unwrap_or_else(|| DEFAULT_BUCKET.to_string()),
+            backend,
+        }
+    }
+
+    // 空の値は未設定として扱う
+    fn value(&mut self, key: &Key) -> Option<String> {
+        if let Some(value) = self.env.get(key.env) {
+            return Some(value.clone()).filter(|v| !v.is_empty());
+        }
+        let value = self.file.get(key.section)?.get(key.name)?;
+        match value {
+            toml::Value::String(s) => Some(s.clone()).filter(|v| !v.is_empty()),
+            toml::Value::Integer(i) => Some(i.to_string()),
+            toml::Value::Boolean(b) => Some(b.to_string()),
+            _ => {
+                self.errors
+                    .push(format!("{} must be a string or a number", key));
+                None
+            }
+        }
+    }
+
+    fn required(&mut self, key: &Key) -> String {
+        self.value(key).unwrap_or_else(|| {
+            self.errors.push(format!("{} must be set", key));
+            String::new()
+        })
+    }
+
+    fn parsed<T>(&mut self, key: &Key) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self.required(key);
+        if value.is_empty() {
+            return None;
+        }
+        match value.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                self.errors
+                    .push(format!("{} is invalid ({value:?}): {e}", key));
+                None
+            }
+        }
+    }
+
+    // 1以上の整数。不正な値は0として返し、エラーに積む
+    fn positive<T>(&mut self, key: &Key) -> T
+    where
+        T: FromStr + Default + PartialEq,
+        T::Err: Display,
+    {
+        match self.parsed::<T>(key) {
+            Some(value) if value == T::default() => {
+                self.errors.push(format!("{} must be greater than 0", key));
+                value
+            }
+            value => value.unwrap_or_default(),
+        }
+    }
+
+    // 未設定ならdefaultを使う
+    fn positive_or<T>(&mut self, key: &Key, default: T) -> T
+    where
+        T: FromStr + Default + PartialEq,
+        T::Err: Display,
+    {
+        match self.value(key) {
+            Some(_) => self.positive(key),
+            None => default,
+        }
+    }
+
+    // 未設定ならdefaultを使う。0も受け付ける
+    fn parsed_or<T>(&mut self, key: &Key, default: T) -> T
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.value(key) {
+            Some(_) => self.parsed(key).unwrap_or(default),
+            None => default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loader(env: &[(&str, &str)], file: &str) -> Loader {
+        let mut loader = Loader {
+            env: env
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            file: toml::Table::new(),
+            errors: Vec::new(),
+        };
+        loader.read_file("config.toml", file);
+        loader
+    }
+
+    const FILE: &str = r#"
+        [server]
+        host = "https://example.com"
+        env = "dev"
+
+        [auth]
+        token_ttl = 300
+        refresh_ttl = 86400
+        password_pepper = "pepper"
+
+        [database]
+        url = "postgres://user@localhost:5432/user"
+
+        [redis]
+        host = "localhost"
+        port = 6379
+
+        [storage]
+        backend = "memory"
+    "#;
+
+    #[test]
+    fn load_from_file_with_defaults() {
+        let config = loader(&[], FILE).load().unwrap();
+
+        assert_eq!(300, config.config.token_ttl);
+        assert_eq!(DEFAULT_MAX_CONNECTIONS, config.database.max_connection);
+        assert_eq!("6379", config.redis.port);
+        assert_eq!(DEFAULT_BUCKET, config.storage.blog_bucket);
+        assert!(matches!(config.storage.backend, StorageBackend::Memory));
+        assert_eq!(DEFAULT_RATE_LIMIT_BURST, config.rate_limit.burst);
+        assert_eq!(DEFAULT_SHUTDOWN_DELAY_SECONDS, config.shutdown.delay);
+        assert_eq!(DEFAULT_PEPPER_ID, config.config.password_peppers.current.id);
+        assert!(config.config.password_peppers.old.is_empty());
+        assert!(config.config.cookie_secure);
+    }
+
+    #[test]
+    fn env_overrides_file() {
+        let config = loader(
+            &[
+                ("TOKEN_TTL", "60"),
+                ("RATE_LIMIT_PER_SECOND", "2"),
+                ("SHUTDOWN_DELAY_SECONDS", "0"),
+                ("COOKIE_SECURE", "false"),
+                ("STORAGE_BACKEND", "local"),
+                ("STORAGE_LOCAL_ROOT", "/tmp/blog"),
+            ],
+            FILE,
+        )
+        .load()
+        .unwrap();
+
+        assert_eq!(60, config.config.token_ttl);
+        assert_eq!(2, config.rate_limit.per_second);
+        assert_eq!(0, config.shutdown.delay);
+        assert!(!config.config.cookie_secure);
+        assert!(matches!(
+            config.storage.backend,
+            StorageBackend::Local { root } if root.to_str() == Some("/tmp/blog")
+        ));
+    }
+
+    #[test]
+    fn report_every_problem() {
+        let errors = loader(
+            &[
+                ("TOKEN_TTL", "five minutes"),
+                ("REFRESH_TTL", "0"),
+                ("REDIS_PORT", "redis"),
+                ("STORAGE_BACKEND", "r2"),
+            ],
+            FILE,
+        )
+        .load()
+        .err()
+        .unwrap()
+        .0;
+
+        assert!(errors[0].starts_with("TOKEN_TTL ([auth] token_ttl) is invalid"));
+        assert_eq!(
+            vec![
+                "REFRESH_TTL ([auth] refresh_ttl) must be greater than 0",
+                "REDIS_PORT ([redis] port) must be a port number",
+                "CLOUDFLARE_ACCOUNT_ID ([storage] account_id) must be set",
+                "CLOUDFLARE_ACCESS_KEY_ID ([storage] access_key_id) must be set",
+                "CLOUDFLARE_SECRET_ACCESS_KEY ([storage] secret_access_key) must be set",
+            ],
+            errors[1..]
+        );
+    }
+
+    #[test]
+    fn refresh_ttl_must_outlive_token_ttl() {
+        let errors = loader(&[("REFRESH_TTL", "60")], FILE)
+            .load()
+            .err()
+            .unwrap()
+            .0;
+
+        assert_eq!(
+            vec![
+                "REFRESH_TTL ([auth] refresh_ttl) must not be shorter than TOKEN_TTL ([auth] token_ttl)"
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn old_peppers_are_parsed_and_checked() {
+        let config = loader(
+            &[
+                ("PASSWORD_PEPPER_ID", "2024"),
+                ("PASSWORD_OLD_PEPPERS", "2023:old, 2022:older"),
+            ],
+            FILE,
+        )
+        .load()
+        .unwrap();
+        let peppers = config.config.password_peppers;
+
+        assert_eq!(
+            vec!["2024", "2023", "2022"],
+            peppers.iter().map(|p| p.id.as_str()).collect::<Vec<_>>()
+        );
+        assert_eq!("older", peppers.find(b"2022").unwrap().secret);
+
+        let errors = loader(
+            &[
+                ("PASSWORD_PEPPER_ID", "2023"),
+                ("PASSWORD_OLD_PEPPERS", "2023:old,broken,toolongid:x"),
+            ],
+            FILE,
+        )
+        .load()
+        .err()
+        .unwrap()
+        .0;
+
+        assert_eq!(
+            vec![
+                "PASSWORD_OLD_PEPPERS ([auth] old_password_peppers) must be a comma-separated list of id:secret",
+                "pepper id \"2023\" is used more than once",
+                "pepper id \"toolongid\" must be at most 8 bytes",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn trusted_proxies_are_parsed_and_checked() {
+        let config = loader(&[("TRUSTED_PROXIES", "10.0.0.0/8, 192.0.2.1")], FILE)
+            .load()
+            .unwrap();
+        let proxies = config.config.trusted_proxies;
+
+        assert!(proxies.contains(&"10.1.2.3".parse().unwrap()));
+        assert!(proxies.contains(&"192.0.2.1".parse().unwrap()));
+        assert!(!proxies.contains(&"192.0.2.2".parse().unwrap()));
+
+        let errors = loader(&[("TRUSTED_PROXIES", "10.0.0.0/8,proxy")], FILE)
+            .load()
+            .err()
+            .unwrap()
+            .0;
+        assert_eq!(
+            vec![
+                "TRUSTED_PROXIES ([server] trusted_proxies) must be a comma-separated list of IP addresses or CIDRs, got \"proxy\""
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn client_ip_skips_only_trusted_hops() {
+        let proxies = TrustedProxies::new(vec!["10.0.0.0/8".parse().unwrap()]);
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        let hops = |s: &'static str| s.split(',');
+
+        // 信頼していない接続元のヘッダーは見ない
+        assert_eq!(
+            ip("203.0.113.9"),
+            TrustedProxies::default().client_ip(ip("203.0.113.9"), hops("198.51.100.1"))
+        );
+        assert_eq!(
+            ip("203.0.113.9"),
+            proxies.client_ip(ip("203.0.113.9"), hops("198.51.100.1"))
+        );
+        // 利用者が左端に偽のアドレスを書いても、プロキシが付けた右側を使う
+        assert_eq!(
+            ip("198.51.100.7"),
+            proxies.client_ip(ip("10.0.0.1"), hops("1.2.3.4, 198.51.100.7, 10.0.0.2"))
+        );
+        // 読めない値より左は使わない
+        assert_eq!(
+            ip("10.0.0.2"),
+            proxies.client_ip(ip("10.0.0.1"), hops("1.2.3.4, unknown, 10.0.0.2"))
+        );
+        assert_eq!(
+            ip("10.0.0.1"),
+            proxies.client_ip(ip("10.0.0.1"), std::iter::empty())
+        );
+    }
+
+    #[test]
+    fn broken_file_is_reported() {
+        let errors = loader(&[], "[server").load().err().unwrap().0;
+
+        assert!(errors[0].starts_with("CONFIG_FILE config.toml:"));
+        assert!(errors.contains(&"DATABASE_URL ([database] url) must be set".to_string()));
+    }
+}
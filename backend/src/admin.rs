@@ -0,0 +1,86 @@
+// 管理用のサブコマンド
+//
+// `backend admin set-password <username>` で利用者を作るか、パスワードを置き換える。
+// パスワードはシェルの履歴やpsに残らないよう、引数ではなく標準入力の1行目から読む。
+// `backend admin set-role <username> <admin|editor|viewer>` で役割を変える
+
+use std::io::{BufRead, IsTerminal, Write};
+
+use usecase::model::user::Role;
+use usecase::service::service::Service;
+use usecase::service::user::user_service::UserService;
+
+const USAGE: &str = "usage: backend admin set-password <username>  (password is read from stdin)
+       backend admin set-role <username> <admin|editor|viewer>";
+
+pub enum Command {
+    SetPassword { username: String },
+    SetRole { username: String, role: Role },
+}
+
+impl Command {
+    // 引数がなければサーバーとして起動するのでNone
+    pub fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        match args {
+            [] => Ok(None),
+            [admin, command, username] if admin == "admin" && command == "set-password" => {
+                Ok(Some(Command::SetPassword {
+                    username: username.clone(),
+                }))
+            }
+            [admin, command, username, role] if admin == "admin" && command == "set-role" => {
+                let role = role
+                    .parse::<Role>()
+                    .map_err(|e| format!("{}\n{}", e, USAGE))?;
+                Ok(Some(Command::SetRole {
+                    username: username.clone(),
+                    role,
+                }))
+            }
+            _ => Err(USAGE.to_string()),
+        }
+    }
+
+    // 終了コードを返す
+    pub async fn run(self, service: &Service) -> i32 {
+        match self {
+            Command::SetPassword { username } => {
+                let password = match read_password() {
+                    Ok(password) => password,
+                    Err(e) => {
+                        eprintln!("failed to read password: {}", e);
+                        return 1;
+                    }
+                };
+                match service.set_password(&username, &password).await {
+                    Ok(true) => println!("created user: {}", username),
+                    Ok(false) => println!("reset password of user: {}", username),
+                    Err(e) => {
+                        eprintln!("failed to set password: {}", e.message);
+                        return 1;
+                    }
+                }
+                0
+            }
+            Command::SetRole { username, role } => {
+                if let Err(e) = service.set_role(&username, role).await {
+                    eprintln!("failed to set role: {}", e.message);
+                    return 1;
+                }
+                println!("set role of user: {} to {}", username, role);
+                0
+            }
+        }
+    }
+}
+
+fn read_password() -> std::io::Result<String> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        eprint!("password: ");
+        std::io::stderr().flush()?;
+    }
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
@@ -1,22 +1,33 @@
+mod admin;
+
 use async_shutdown::ShutdownManager;
-use aws_config::{BehaviorVersion, Region};
-use aws_sdk_s3::Client;
-use aws_sdk_s3::config::Credentials;
-use axum::{Json, Router, extract::State, http::StatusCode, routing::get, routing::post};
+use axum::{
+    Json, Router,
+    extract::{DefaultBodyLimit, State},
+    http::StatusCode,
+    routing::delete,
+    routing::get,
+    routing::post,
+    routing::put,
+};
 use dotenv::dotenv;
 use serde_json;
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
-use std::env;
 use storage::redis::RedisClient;
-use tracing::info;
+use tokio::signal::unix::{SignalKind, signal};
+use tracing::{error, info};
 use tracing_subscriber;
 
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use handler::handler::*;
-use shared::config::{Config, RedisConfig};
+use handler::rate_limit::RateLimit;
+use shared::config::{AppConfig, DatabaseConfig, RedisConfig, ShutdownConfig, StorageBackend};
 use storage::repository::*;
+use usecase::model::image::MAX_IMAGE_BYTES;
 use usecase::service::service::*;
 
 #[tokio::main]
@@ -25,121 +36,263 @@ async fn main() {
 
     dotenv().ok();
 
-    let pool = initialize_db().await;
-    let r2_client = initialize_cloud_storage().await;
-    let redis_client = initialize_redis();
-    let config = Config {
-        host: env::var("PAGE_HOST").expect("PAGE_HOST must be set"),
-        env: env::var("ENV").expect("ENV must be set"),
-        token_ttl: env::var("TOKEN_TTL")
-            .expect("TOKEN_TTL must be set")
-            .parse::<u64>()
-            .unwrap_or(300),
-        refresh_ttl: env::var("REFRESH_TTL")
-            .expect("REFRESH_TTL must be set")
-            .parse::<u64>()
-            .unwrap_or(300),
-    };
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = admin::Command::parse(&args).unwrap_or_else(|usage| {
+        eprintln!("{}", usage);
+        std::process::exit(2);
+    });
+
+    let AppConfig {
+        config,
+        database,
+        storage: storage_config,
+        redis,
+        rate_limit,
+        shutdown: shutdown_config,
+    } = AppConfig::load().unwrap_or_else(|e| {
+        error!("{}", e);
+        std::process::exit(1);
+    });
+
+    // R2なら画像はR2から配信される。それ以外では画像のURLをこのサーバーで受ける
+    let serves_uploads = !matches!(storage_config.backend, StorageBackend::R2 { .. });
+    let pool = initialize_db(&database).await;
+    let object_storage = storage::object_storage::connect(&storage_config).await;
+    let redis_client = initialize_redis(redis);
 
     let repository = Box::new(Repository::new(
         pool.clone(),
-        r2_client,
+        object_storage,
+        storage_config,
         redis_client,
         config.clone(),
     ));
     let service = Arc::new(Service::new(config, repository));
 
-    let app = Router::new()
+    if let Some(command) = command {
+        std::process::exit(command.run(&service).await);
+    }
+
+    // /apiと/usersは別々に数える
+    let api_limit = RateLimit::new(&rate_limit, &service.config.trusted_proxies);
+    let users_limit = RateLimit::new(&rate_limit, &service.config.trusted_proxies);
+    spawn_rate_limit_cleanup(vec![api_limit.clone(), users_limit.clone()]);
+
+    // シグナルを受けるとシャットダウンを始め、readinessを503にする
+    let shutdown = ShutdownManager::new();
+    spawn_signal_handler(shutdown.clone());
+
+    let mut app = Router::new()
         .route("/", get(|| async { "Hello, World!" }))
-        .nest("/health", create_health_router(pool))
-        .nest("/api", create_blog_router(service.clone()))
-        .nest("/users", create_users_router(service))
+        .nest(
+            "/health",
+            create_health_router(pool.clone(), shutdown.clone()),
+        )
+        .nest(
+            "/api",
+            create_blog_router(service.clone())
+                .merge(create_maze_router(service.clone()))
+                .layer(api_limit.layer()),
+        )
+        .nest(
+            "/users",
+            create_users_router(service.clone()).layer(users_limit.layer()),
+        )
         .fallback(fallback);
+    if serves_uploads {
+        app = app.nest(
+            "/_uploads",
+            create_uploads_router(service).layer(api_limit.layer()),
+        );
+    }
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8000")
         .await
         .expect("error: failed to bind to address");
-    let shutdown = ShutdownManager::new();
 
-    match axum::serve(listener, app).await {
-        Ok(()) => {
-            shutdown.trigger_shutdown(0).ok();
+    // セッション一覧に接続元のIPを記録できるようにする
+    let serve = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(stop_accepting(shutdown.clone(), shutdown_config.clone()));
+    // サーバーが止まるまで、シャットダウンの完了を遅らせる
+    let server = shutdown
+        .wrap_delay_shutdown(serve.into_future())
+        .expect("shutdown must not be completed before the server starts");
+    let server_shutdown = shutdown.clone();
+    let server = tokio::spawn(async move {
+        if let Err(e) = server.await {
+            error!("server error: {}", e);
+            server_shutdown.trigger_shutdown(1).ok();
         }
-        Err(e) => {
-            info!("server error: {}", e);
-            shutdown.trigger_shutdown(1).ok();
+    });
+
+    let exit_code = shutdown.wait_shutdown_triggered().await;
+    info!("Shutting down");
+    let drain = Duration::from_secs(shutdown_config.delay + shutdown_config.timeout);
+    let exit_code = match tokio::time::timeout(drain, shutdown.wait_shutdown_complete()).await {
+        Ok(_) => exit_code,
+        Err(_) => {
+            // R2へのアップロードなどが途中で切れることがある
+            error!(
+                "Shutdown did not finish within {}s, aborting requests in flight",
+                drain.as_secs()
+            );
+            server.abort();
+            1
         }
     };
 
-    let exit_code = shutdown.wait_shutdown_complete().await;
+    // サーバーと一緒にServiceが捨てられ、Redisの接続も閉じる。RedisClientはコマンドごとに接続する
+    pool.close().await;
+    info!("Shut down with exit code {}", exit_code);
     std::process::exit(exit_code);
 }
 
+// SIGINTかSIGTERMを受けたら、シャットダウンを始める
+fn spawn_signal_handler(shutdown: ShutdownManager<i32>) {
+    tokio::spawn(async move {
+        let interrupt = async {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                error!("Failed to listen for SIGINT: {}", e);
+                std::future::pending::<()>().await;
+            }
+        };
+        let terminate = async {
+            match signal(SignalKind::terminate()) {
+                Ok(mut terminate) => {
+                    terminate.recv().await;
+                }
+                Err(e) => {
+                    error!("Failed to listen for SIGTERM: {}", e);
+                    std::future::pending::<()>().await;
+                }
+            }
+        };
+        tokio::select! {
+            _ = interrupt => info!("Received SIGINT"),
+            _ = terminate => info!("Received SIGTERM"),
+        }
+        shutdown.trigger_shutdown(0).ok();
+    });
+}
+
+// シャットダウンが始まってからdelay秒は新しい接続も受け付け、その後は処理中のリクエストだけを待つ
+async fn stop_accepting(shutdown: ShutdownManager<i32>, config: ShutdownConfig) {
+    shutdown.wait_shutdown_triggered().await;
+    tokio::time::sleep(Duration::from_secs(config.delay)).await;
+    info!("Stopped accepting connections, draining requests in flight");
+}
+
 fn create_blog_router(service: Arc<Service>) -> Router {
     let blog_routers = Router::new()
         .route("/", get(Handler::get_blogs).post(Handler::create_blog))
-        .route("/{id}", get(|| async { "Blog get by ID" }))
-        .route("/images", post(Handler::upload_blog_image))
+        .route(
+            "/{id}",
+            get(Handler::get_blog)
+                .put(Handler::put_blog)
+                .patch(Handler::patch_blog)
+                .delete(Handler::delete_blog),
+        )
+        .route("/slug/{slug}", get(Handler::get_blog_by_slug))
+        .route("/{id}/publish", post(Handler::publish_blog))
+        .route("/{id}/unpublish", post(Handler::unpublish_blog))
+        .route("/drafts", post(Handler::create_draft))
+        .route("/drafts/{id}", put(Handler::update_draft))
+        // multipartの区切りなどの分だけ、画像の上限より少し大きくする
+        .route(
+            "/images",
+            post(Handler::upload_blog_image)
+                .layer(DefaultBodyLimit::max(MAX_IMAGE_BYTES + 64 * 1024)),
+        )
         .fallback(api_fallback)
         .with_state(service);
 
     Router::new().nest("/blogs", blog_routers)
 }
 
+fn create_uploads_router(service: Arc<Service>) -> Router {
+    Router::new()
+        .route("/{name}", get(Handler::get_uploaded_image))
+        .fallback(api_fallback)
+        .with_state(service)
+}
+
+fn create_maze_router(service: Arc<Service>) -> Router {
+    let maze_routers = Router::new()
+        .route("/", get(Handler::get_maze))
+        .route(
+            "/saved",
+            get(Handler::get_saved_mazes).post(Handler::save_maze),
+        )
+        .route(
+            "/saved/{id}",
+            get(Handler::get_saved_maze).delete(Handler::delete_saved_maze),
+        )
+        .fallback(api_fallback)
+        .with_state(service);
+
+    Router::new().nest("/mazes", maze_routers)
+}
+
 fn create_users_router(service: Arc<Service>) -> Router {
     Router::new()
         .route("/admin/login", post(Handler::login_admin))
+        .route("/refresh", post(Handler::refresh))
         .route("/logout", post(Handler::logout))
+        .route(
+            "/sessions",
+            get(Handler::get_sessions).delete(Handler::delete_sessions),
+        )
+        .route("/sessions/{id}", delete(Handler::delete_session))
+        .route(
+            "/tokens",
+            get(Handler::get_api_tokens).post(Handler::create_api_token),
+        )
+        .route("/tokens/{id}", delete(Handler::delete_api_token))
         .fallback(api_fallback)
         .with_state(service)
 }
 
-fn create_health_router(pool: PgPool) -> Router {
+#[derive(Clone)]
+struct HealthState {
+    pool: PgPool,
+    shutdown: ShutdownManager<i32>,
+}
+
+fn create_health_router(pool: PgPool, shutdown: ShutdownManager<i32>) -> Router {
     Router::new()
         .route("/", get(health_ok))
         .route("/db", get(db_health_ok))
-        .with_state(pool)
+        .route("/ready", get(ready))
+        .with_state(HealthState { pool, shutdown })
 }
 
-async fn initialize_db() -> PgPool {
-    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+async fn initialize_db(config: &DatabaseConfig) -> PgPool {
     let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&db_url)
+        .max_connections(config.max_connection)
+        .connect(&config.url)
         .await
         .expect("Failed to connect to database");
     pool
 }
 
-async fn initialize_cloud_storage() -> Client {
-    let account_id = env::var("CLOUDFLARE_ACCOUNT_ID").expect("CLOUDFLARE_ACCOUNT_ID must be set");
-    let access_key_id =
-        env::var("CLOUDFLARE_ACCESS_KEY_ID").expect("CLOUDFLARE_ACCESS_KEY_ID must be set");
-    let secret_access_key =
-        env::var("CLOUDFLARE_SECRET_ACCESS_KEY").expect("CLOUDFLARE_SECRET_ACCESS_KEY must be set");
-    let config = aws_config::defaults(BehaviorVersion::latest())
-        .endpoint_url(format!("https://{}.r2.cloudflarestorage.com", account_id))
-        .region(Region::new("auto"))
-        .credentials_provider(Credentials::new(
-            access_key_id,
-            secret_access_key,
-            None,
-            None,
-            "R2",
-        ))
-        .load()
-        .await;
-    Client::new(&config)
-}
-
-fn initialize_redis() -> RedisClient {
-    let config = RedisConfig {
-        host: env::var("REDIS_HOST").expect("REDIS_HOST must be set"),
-        port: env::var("REDIS_PORT").expect("REDIS_PORT must be set"),
-    };
-
+fn initialize_redis(config: RedisConfig) -> RedisClient {
     RedisClient::new(config).expect("creating redis client failed")
 }
 
+fn spawn_rate_limit_cleanup(limits: Vec<RateLimit>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            for limit in &limits {
+                limit.retain_recent();
+            }
+        }
+    });
+}
+
 async fn fallback() -> (StatusCode, &'static str) {
     (StatusCode::NOT_FOUND, "Not Found")
 }
@@ -148,14 +301,22 @@ async fn health_ok() -> StatusCode {
     StatusCode::OK
 }
 
-async fn db_health_ok(State(pool): State<PgPool>) -> StatusCode {
-    let connection_result = sqlx::query("SELECT 1").fetch_one(&pool).await;
+async fn db_health_ok(State(state): State<HealthState>) -> StatusCode {
+    let connection_result = sqlx::query("SELECT 1").fetch_one(&state.pool).await;
     match connection_result {
         Ok(_) => StatusCode::OK,
         Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
     }
 }
 
+// シャットダウン中は新しいリクエストを振り分けられないよう、DBにつながっていても503を返す
+async fn ready(state: State<HealthState>) -> StatusCode {
+    if state.shutdown.is_shutdown_triggered() {
+        return StatusCode::SERVICE_UNAVAILABLE;
+    }
+    db_health_ok(state).await
+}
+
 async fn api_fallback() -> (StatusCode, Json<serde_json::Value>) {
     (
         StatusCode::NOT_FOUND,
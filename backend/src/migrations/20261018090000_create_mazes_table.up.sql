@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE mazes (
+    id UUID PRIMARY KEY,
+    owner_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    title VARCHAR(100),
+    algorithm VARCHAR(30) NOT NULL,
+    seed BIGINT NOT NULL,
+    rows INTEGER NOT NULL CHECK (rows > 0),
+    cols INTEGER NOT NULL CHECK (cols > 0),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX mazes_owner_id_created_at_idx ON mazes (owner_id, created_at DESC);
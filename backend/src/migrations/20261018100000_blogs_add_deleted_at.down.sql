@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS blogs_deleted_at_idx;
+ALTER TABLE blogs DROP COLUMN IF EXISTS deleted_at;
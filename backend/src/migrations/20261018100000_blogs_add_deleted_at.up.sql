@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE blogs ADD COLUMN deleted_at TIMESTAMP;
+
+CREATE INDEX blogs_deleted_at_idx ON blogs (deleted_at) WHERE deleted_at IS NULL;
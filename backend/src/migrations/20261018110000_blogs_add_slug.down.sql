@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS blog_slug_redirects;
+ALTER TABLE blogs DROP CONSTRAINT IF EXISTS blogs_slug_key;
+ALTER TABLE blogs DROP COLUMN IF EXISTS slug;
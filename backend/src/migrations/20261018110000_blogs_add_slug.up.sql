@@ -0,0 +1,12 @@
+-- Add up migration script here
+ALTER TABLE blogs ADD COLUMN slug VARCHAR(255);
+UPDATE blogs SET slug = replace(id::text, '-', '');
+ALTER TABLE blogs ALTER COLUMN slug SET NOT NULL;
+ALTER TABLE blogs ADD CONSTRAINT blogs_slug_key UNIQUE (slug);
+
+-- スラッグを変更した記事の旧URLを新しいURLへ転送するための記録
+CREATE TABLE blog_slug_redirects (
+    old_slug VARCHAR(255) PRIMARY KEY,
+    blog_id UUID NOT NULL REFERENCES blogs (id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE users ADD COLUMN salt TEXT NOT NULL DEFAULT '';
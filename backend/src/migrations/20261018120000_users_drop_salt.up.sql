@@ -0,0 +1,3 @@
+-- Add up migration script here
+-- ソルトはパスワードのPHC文字列に含まれている
+ALTER TABLE users DROP COLUMN salt;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS images;
+ALTER TABLE blogs DROP COLUMN author_id;
+ALTER TABLE users DROP COLUMN role;
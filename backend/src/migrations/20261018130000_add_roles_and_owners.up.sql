@@ -0,0 +1,15 @@
+-- Add up migration script here
+-- これまでログインできた利用者は全ての操作ができたので、既存の利用者は管理者にする
+ALTER TABLE users ADD COLUMN role VARCHAR(10) NOT NULL DEFAULT 'admin'
+    CHECK (role IN ('admin', 'editor', 'viewer'));
+ALTER TABLE users ALTER COLUMN role SET DEFAULT 'viewer';
+
+-- 作成者のいない既存の記事は、管理者だけが変更できる
+ALTER TABLE blogs ADD COLUMN author_id UUID REFERENCES users (id) ON DELETE SET NULL;
+
+-- アップロードした画像の持ち主。記事を完全に削除するとき、作成者の画像だけを消す
+CREATE TABLE images (
+    id VARCHAR(32) PRIMARY KEY,
+    owner_id UUID REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+UPDATE blogs
+SET content_key = legacy_content_key
+WHERE legacy_content_key IS NOT NULL;
+
+ALTER TABLE blogs DROP COLUMN legacy_content_key;
@@ -0,0 +1,9 @@
+-- Add up migration script here
+-- 公開した記事のcontent_keyには記事ページのURLが入っていた。本文は作成時から常にuploads/drafts/{id}に
+-- 保存しているので、オブジェクトは動かさずにキーだけをそろえる。downで戻せるよう元の値を残す
+ALTER TABLE blogs ADD COLUMN legacy_content_key VARCHAR(255);
+
+UPDATE blogs
+SET legacy_content_key = content_key,
+    content_key = 'uploads/drafts/' || id::text
+WHERE content_key <> 'uploads/drafts/' || id::text;
@@ -1,45 +1,361 @@
+use crate::blogs::model::BlogRow;
+
 use super::super::repository::*;
-use aws_sdk_s3::error::ProvideErrorMetadata;
-use aws_sdk_s3::primitives::ByteStream;
 use tracing::error;
 use usecase::errors::repo_error::RepoError;
-use usecase::model::blog::{Blog, BlogFilter};
-use usecase::model::image::Image;
+use usecase::model::blog::{Blog, BlogFilter, BlogStatus, RenderedContent, SlugLookup};
+use usecase::model::image::{IMAGE_VARIANTS, Image};
 use usecase::repository::blog::BlogRepository;
+use usecase::repository::object_storage::ObjectMetadata;
 use usecase::repository::types::Transaction;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
 
 #[async_trait]
 impl BlogRepository for Repository {
-    async fn get_blogs(&self, filter: BlogFilter) -> Vec<Blog> {
-        // let rows = self.client.query("SELECT * FROM blogs", &[]).await.unwrap();
-        return vec![];
+    async fn get_blogs(&self, filter: BlogFilter) -> Result<Vec<Blog>, RepoError> {
+        let (cursor_listed_at, cursor_id) = match filter.cursor {
+            Some(cursor) => (Some(cursor.listed_at), Some(cursor.id)),
+            None => (None, None),
+        };
+        let rows = sqlx::query_as!(
+            BlogRow,
+            "SELECT id, title, slug, content_key, status, created_at, updated_at, published_at, author_id \
+             FROM blogs \
+             WHERE status = 'PUBLISHED' AND deleted_at IS NULL \
+             AND ($1::timestamp IS NULL OR COALESCE(published_at, created_at) >= $1) \
+             AND ($2::timestamp IS NULL OR COALESCE(published_at, created_at) < $2) \
+             AND ($3::timestamp IS NULL OR (COALESCE(published_at, created_at), id) < ($3, $4)) \
+             ORDER BY COALESCE(published_at, created_at) DESC, id DESC \
+             LIMIT $5",
+            filter.start,
+            filter.end,
+            cursor_listed_at,
+            cursor_id,
+            filter.limit + 1
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to get blogs: {}", e);
+            RepoError::Internal("Failed to get blogs".to_string())
+        })?;
+
+        rows.into_iter().map(Blog::try_from).collect()
     }
 
-    async fn create_draft(&self, tx: &mut Transaction<'_>) -> Result<String, RepoError> {
-        let res =
-            sqlx::query!("INSERT INTO blogs (id, status) VALUES (DEFAULT, 'DRAFT') RETURNING id")
-                .fetch_one(&mut **tx)
-                .await
-                .map_err(|e| {
-                    error!("Failed to create draft blog: {}", e);
-                    RepoError::Internal("Failed to create draft blog".to_string())
-                })?;
-        return Ok(res.id.simple().to_string());
+    async fn get_blog(&self, blog_id: Uuid) -> Result<Blog, RepoError> {
+        let row = sqlx::query_as!(
+            BlogRow,
+            "SELECT id, title, slug, content_key, status, created_at, updated_at, published_at, author_id \
+             FROM blogs WHERE id = $1 AND deleted_at IS NULL",
+            blog_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                RepoError::NotFound(format!("Blog with id: {} not found", blog_id))
+            }
+            _ => {
+                error!("Failed to get blog: {}, error: {}", blog_id, e);
+                RepoError::Internal("Failed to get blog".to_string())
+            }
+        })?;
+
+        row.try_into()
+    }
+
+    async fn get_blog_by_slug(&self, slug: &str) -> Result<SlugLookup<Blog>, RepoError> {
+        let row = sqlx::query_as!(
+            BlogRow,
+            "SELECT id, title, slug, content_key, status, created_at, updated_at, published_at, author_id \
+             FROM blogs WHERE slug = $1 AND deleted_at IS NULL",
+            slug
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to get blog by slug: {}, error: {}", slug, e);
+            RepoError::Internal("Failed to get blog".to_string())
+        })?;
+        if let Some(row) = row {
+            return Ok(SlugLookup::Found(row.try_into()?));
+        }
+
+        // 旧スラッグなら現在のスラッグへ転送する
+        let current = sqlx::query_scalar!(
+            "SELECT b.slug FROM blog_slug_redirects r \
+             JOIN blogs b ON b.id = r.blog_id \
+             WHERE r.old_slug = $1 AND b.deleted_at IS NULL",
+            slug
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to get slug redirect: {}, error: {}", slug, e);
+            RepoError::Internal("Failed to get blog".to_string())
+        })?;
+
+        current
+            .map(SlugLookup::Redirect)
+            .ok_or_else(|| RepoError::NotFound(format!("Blog with slug: {} not found", slug)))
+    }
+
+    async fn is_slug_taken(
+        &self,
+        tx: &mut Transaction<'_>,
+        slug: &str,
+        blog_id: Uuid,
+    ) -> Result<bool, RepoError> {
+        // 論理削除した記事のスラッグも、復元できるよう予約したままにする
+        let taken = sqlx::query_scalar!(
+            "SELECT EXISTS (SELECT 1 FROM blogs WHERE slug = $1 AND id <> $2) \
+             OR EXISTS (SELECT 1 FROM blog_slug_redirects WHERE old_slug = $1 AND blog_id <> $2)",
+            slug,
+            blog_id
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to check slug: {}, error: {}", slug, e);
+            RepoError::Internal("Failed to check slug".to_string())
+        })?;
+
+        Ok(taken.unwrap_or(false))
+    }
+
+    async fn rename_blog_slug(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog_id: Uuid,
+        old_slug: &str,
+        new_slug: &str,
+    ) -> Result<(), RepoError> {
+        let map_err = |e: sqlx::Error| {
+            if is_slug_conflict(&e) {
+                return RepoError::Conflict("Blog with the same slug already exists".to_string());
+            }
+            error!("Failed to rename blog slug: {}, error: {}", blog_id, e);
+            RepoError::Internal("Failed to rename blog slug".to_string())
+        };
+
+        // 元のスラッグに戻す場合は、その転送記録はもう要らない
+        sqlx::query!(
+            "DELETE FROM blog_slug_redirects WHERE old_slug = $1 AND blog_id = $2",
+            new_slug,
+            blog_id
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(map_err)?;
+        sqlx::query!(
+            "UPDATE blogs SET slug = $2 WHERE id = $1",
+            blog_id,
+            new_slug
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(map_err)?;
+        sqlx::query!(
+            "INSERT INTO blog_slug_redirects (old_slug, blog_id) VALUES ($1, $2) \
+             ON CONFLICT (old_slug) DO UPDATE SET blog_id = EXCLUDED.blog_id, created_at = NOW()",
+            old_slug,
+            blog_id
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn get_blog_content(&self, blog_id: Uuid) -> Result<String, RepoError> {
+        let body = self
+            .object_storage
+            .get_object(&self.storage_config.blog_bucket, &Blog::draft_key(&blog_id))
+            .await?
+            .ok_or_else(|| {
+                RepoError::NotFound(format!("Content of blog: {} not found", blog_id))
+            })?;
+        String::from_utf8(body.to_vec()).map_err(|e| {
+            error!("Blog content is not utf-8, id: {} err: {}", blog_id, e);
+            RepoError::Internal("Blog content is not utf-8".to_string())
+        })
+    }
+
+    async fn get_blog_rendered(&self, blog_id: Uuid) -> Result<Option<RenderedContent>, RepoError> {
+        let body = self
+            .object_storage
+            .get_object(&self.storage_config.blog_bucket, &rendered_key(blog_id))
+            .await?;
+        body.map(|body| {
+            serde_json::from_slice(&body).map_err(|e| {
+                error!("Rendered blog is malformed, id: {} err: {}", blog_id, e);
+                RepoError::Internal("Rendered blog is malformed".to_string())
+            })
+        })
+        .transpose()
+    }
+
+    async fn lock_blog(&self, tx: &mut Transaction<'_>, blog_id: Uuid) -> Result<Blog, RepoError> {
+        let row = sqlx::query_as!(
+            BlogRow,
+            "SELECT id, title, slug, content_key, status, created_at, updated_at, published_at, author_id \
+             FROM blogs WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+            blog_id
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                RepoError::NotFound(format!("Blog with id: {} not found", blog_id))
+            }
+            _ => {
+                error!("Failed to lock blog: {}, error: {}", blog_id, e);
+                RepoError::Internal("Failed to lock blog".to_string())
+            }
+        })?;
+
+        row.try_into()
+    }
+
+    async fn lock_blog_with_deleted(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog_id: Uuid,
+    ) -> Result<Blog, RepoError> {
+        let row = sqlx::query_as!(
+            BlogRow,
+            "SELECT id, title, slug, content_key, status, created_at, updated_at, published_at, author_id \
+             FROM blogs WHERE id = $1 FOR UPDATE",
+            blog_id
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                RepoError::NotFound(format!("Blog with id: {} not found", blog_id))
+            }
+            _ => {
+                error!("Failed to lock blog: {}, error: {}", blog_id, e);
+                RepoError::Internal("Failed to lock blog".to_string())
+            }
+        })?;
+
+        row.try_into()
+    }
+
+    async fn create_draft(&self, tx: &mut Transaction<'_>, blog: Blog) -> Result<Blog, RepoError> {
+        let row = sqlx::query_as!(
+            BlogRow,
+            "INSERT INTO blogs (id, title, slug, status, content_key, created_at, updated_at, author_id) \
+             VALUES ($1, $2, $3, 'DRAFT', $4, $5, $6, $7) \
+             RETURNING id, title, slug, content_key, status, created_at, updated_at, published_at, author_id",
+            blog.id,
+            blog.title,
+            blog.slug,
+            blog.content_key,
+            blog.created_at,
+            blog.updated_at,
+            blog.author_id
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| {
+            if is_slug_conflict(&e) {
+                return RepoError::Conflict("Blog with the same slug already exists".to_string());
+            }
+            error!("Failed to create draft blog: {}", e);
+            RepoError::Internal("Failed to create draft blog".to_string())
+        })?;
+
+        row.try_into()
+    }
+
+    async fn update_blog_title(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog_id: Uuid,
+        title: &str,
+        updated_at: NaiveDateTime,
+    ) -> Result<Blog, RepoError> {
+        let row = sqlx::query_as!(
+            BlogRow,
+            "UPDATE blogs SET title = $2, updated_at = $3 WHERE id = $1 \
+             RETURNING id, title, slug, content_key, status, created_at, updated_at, published_at, author_id",
+            blog_id,
+            title,
+            updated_at
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                RepoError::NotFound(format!("Blog with id: {} not found", blog_id))
+            }
+            _ => {
+                error!("Failed to update blog title: {}, error: {}", blog_id, e);
+                RepoError::Internal("Failed to update blog title".to_string())
+            }
+        })?;
+
+        row.try_into()
+    }
+
+    async fn update_blog_status(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog_id: Uuid,
+        status: BlogStatus,
+        published_at: Option<NaiveDateTime>,
+        updated_at: NaiveDateTime,
+    ) -> Result<Blog, RepoError> {
+        let row = sqlx::query_as!(
+            BlogRow,
+            "UPDATE blogs SET status = $2, published_at = $3, updated_at = $4 WHERE id = $1 \
+             RETURNING id, title, slug, content_key, status, created_at, updated_at, published_at, author_id",
+            blog_id,
+            status.to_string(),
+            published_at,
+            updated_at
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                RepoError::NotFound(format!("Blog with id: {} not found", blog_id))
+            }
+            _ => {
+                error!("Failed to update blog status: {}, error: {}", blog_id, e);
+                RepoError::Internal("Failed to update blog status".to_string())
+            }
+        })?;
+
+        row.try_into()
     }
 
     async fn create_blog(&self, tx: &mut Transaction<'_>, blog: Blog) -> Result<Blog, RepoError> {
         sqlx::query!(
-            "INSERT INTO blogs (id, title, status, content_key) VALUES ($1, $2, 'PUBLISHED', $3)",
+            "INSERT INTO blogs (id, title, slug, status, content_key, created_at, updated_at, published_at, author_id) \
+             VALUES ($1, $2, $3, 'PUBLISHED', $4, $5, $6, $7, $8)",
             blog.id,
             blog.title,
-            blog.content_key
+            blog.slug,
+            blog.content_key,
+            blog.created_at,
+            blog.updated_at,
+            blog.published_at,
+            blog.author_id
         )
         .execute(&mut **tx)
         .await
         .map_err(|e| {
+            if is_slug_conflict(&e) {
+                return RepoError::Conflict("Blog with the same slug already exists".to_string());
+            }
             if let Some(db_err) = e.as_database_error() {
                 if db_err.code() == Some("23505".into()) {
                     error!(
@@ -55,116 +371,295 @@ impl BlogRepository for Repository {
         Ok(blog)
     }
 
-    async fn upload_image(&self, image_id: String, image_data: Bytes) -> Result<Image, RepoError> {
-        let body = ByteStream::from(image_data);
-        let bucket_name = "blog-assets";
+    async fn soft_delete_blog(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog_id: Uuid,
+        deleted_at: NaiveDateTime,
+    ) -> Result<Blog, RepoError> {
+        let row = sqlx::query_as!(
+            BlogRow,
+            "UPDATE blogs SET deleted_at = $2, updated_at = $2 \
+             WHERE id = $1 AND deleted_at IS NULL \
+             RETURNING id, title, slug, content_key, status, created_at, updated_at, published_at, author_id",
+            blog_id,
+            deleted_at
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                RepoError::NotFound(format!("Blog with id: {} not found", blog_id))
+            }
+            _ => {
+                error!("Failed to delete blog: {}, error: {}", blog_id, e);
+                RepoError::Internal("Failed to delete blog".to_string())
+            }
+        })?;
+
+        row.try_into()
+    }
+
+    async fn purge_blog(&self, tx: &mut Transaction<'_>, blog_id: Uuid) -> Result<Blog, RepoError> {
+        let row = sqlx::query_as!(
+            BlogRow,
+            "DELETE FROM blogs WHERE id = $1 \
+             RETURNING id, title, slug, content_key, status, created_at, updated_at, published_at, author_id",
+            blog_id
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                RepoError::NotFound(format!("Blog with id: {} not found", blog_id))
+            }
+            _ => {
+                error!("Failed to purge blog: {}, error: {}", blog_id, e);
+                RepoError::Internal("Failed to purge blog".to_string())
+            }
+        })?;
+
+        row.try_into()
+    }
+
+    async fn delete_blog_content(&self, blog_id: Uuid) -> Result<(), RepoError> {
+        let bucket = &self.storage_config.blog_bucket;
+        self.object_storage
+            .delete_object(bucket, &Blog::draft_key(&blog_id))
+            .await?;
+        self.object_storage
+            .delete_object(bucket, &rendered_key(blog_id))
+            .await
+    }
 
-        self.r2_client
-            .put_object()
-            .bucket(bucket_name)
-            .key(format!("_uploads/{}", image_id))
-            .body(body)
-            .send()
+    async fn delete_images(&self, image_ids: &[String]) -> Result<(), RepoError> {
+        let bucket = &self.storage_config.blog_image_bucket;
+        for image_id in image_ids {
+            let keys = std::iter::once(Image::object_key(image_id)).chain(
+                IMAGE_VARIANTS
+                    .iter()
+                    .map(|(variant, _)| Image::variant_key(image_id, variant)),
+            );
+            for key in keys {
+                self.object_storage.delete_object(bucket, &key).await?;
+            }
+        }
+        sqlx::query!("DELETE FROM images WHERE id = ANY($1)", image_ids)
+            .execute(&self.pool)
             .await
             .map_err(|e| {
-                error!(image_id = %image_id, error = %e);
-                error!(
-                    code = e.code(),
-                    message = e.message().unwrap_or("No error message")
-                );
-                RepoError::Internal("Failed to upload image".to_string())
+                error!("Failed to delete image records: {}", e);
+                RepoError::Internal("Failed to delete image records".to_string())
             })?;
+        Ok(())
+    }
 
-        Ok(Image {
-            id: image_id.clone(),
-            url: format!("{}/_uploads/{}", self.config.host, image_id),
+    async fn record_image(&self, image_id: &str, owner_id: Uuid) -> Result<(), RepoError> {
+        sqlx::query!(
+            "INSERT INTO images (id, owner_id) VALUES ($1, $2)",
+            image_id,
+            owner_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to record image: {}, error: {}", image_id, e);
+            RepoError::Internal("Failed to record image".to_string())
+        })?;
+        Ok(())
+    }
+
+    async fn images_owned_by(
+        &self,
+        image_ids: &[String],
+        owner_id: Uuid,
+    ) -> Result<Vec<String>, RepoError> {
+        sqlx::query_scalar!(
+            "SELECT id FROM images WHERE id = ANY($1) AND owner_id = $2",
+            image_ids,
+            owner_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to get owned images: {}", e);
+            RepoError::Internal("Failed to get owned images".to_string())
         })
     }
 
-    async fn upload_blog_draft(&self, blog_id: String, content: String) -> Result<(), RepoError> {
-        let body = ByteStream::from(content.into_bytes());
-        let bucket_name = "blog-assets";
-        self.r2_client
-            .put_object()
-            .bucket(bucket_name)
-            .key(format!("uploads/drafts/{}", blog_id))
-            .body(body)
-            .send()
+    async fn upload_image(
+        &self,
+        key: String,
+        image_data: Bytes,
+        mime_type: &str,
+    ) -> Result<String, RepoError> {
+        // キーには毎回新しいidを使い、同じキーの中身は変わらないので長くキャッシュさせる
+        let metadata = ObjectMetadata {
+            content_type: Some(mime_type.to_string()),
+            cache_control: Some("public, max-age=31536000, immutable".to_string()),
+        };
+        self.object_storage
+            .put_object(
+                &self.storage_config.blog_image_bucket,
+                &key,
+                image_data,
+                metadata,
+            )
+            .await?;
+
+        Ok(format!("{}/{}", self.config.host, key))
+    }
+
+    async fn get_image(&self, key: &str) -> Result<Option<Bytes>, RepoError> {
+        self.object_storage
+            .get_object(&self.storage_config.blog_image_bucket, key)
             .await
-            .map_err(|e| {
-                error!("Failed to upload blog draft, id: {} err : {}", blog_id, e);
-                RepoError::Internal("Failed to upload blog draft".to_string())
-            })?;
-        Ok(())
     }
+
+    async fn upload_blog_draft(&self, blog_id: Uuid, content: String) -> Result<(), RepoError> {
+        let metadata = ObjectMetadata {
+            content_type: Some("text/markdown; charset=utf-8".to_string()),
+            cache_control: None,
+        };
+        self.object_storage
+            .put_object(
+                &self.storage_config.blog_bucket,
+                &Blog::draft_key(&blog_id),
+                Bytes::from(content),
+                metadata,
+            )
+            .await
+    }
+
+    async fn upload_blog_rendered(
+        &self,
+        blog_id: Uuid,
+        rendered: &RenderedContent,
+    ) -> Result<(), RepoError> {
+        let json = serde_json::to_vec(rendered).map_err(|e| {
+            error!(
+                "Failed to serialize rendered blog, id: {} err: {}",
+                blog_id, e
+            );
+            RepoError::Internal("Failed to serialize rendered blog".to_string())
+        })?;
+        let metadata = ObjectMetadata {
+            content_type: Some("application/json".to_string()),
+            cache_control: None,
+        };
+        self.object_storage
+            .put_object(
+                &self.storage_config.blog_bucket,
+                &rendered_key(blog_id),
+                Bytes::from(json),
+                metadata,
+            )
+            .await
+    }
+}
+
+// 変換済みの本文は元のMarkdownとは別のキーに保存する
+fn rendered_key(blog_id: Uuid) -> String {
+    format!("uploads/rendered/{}.json", blog_id)
+}
+
+// スラッグの一意制約に違反したか
+fn is_slug_conflict(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .is_some_and(|db_err| db_err.constraint() == Some("blogs_slug_key"))
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::redis::RedisClient;
-    use shared::config::RedisConfig;
-
     use super::*;
+    use crate::test_support::{create_user, repository as initialize_repository, test_config};
     use anyhow::Result;
     use anyhow::anyhow;
-    use aws_config::BehaviorVersion;
-    use aws_sdk_s3::Client;
-    use shared::config::Config;
+    use chrono::{NaiveDate, Utc};
+    use usecase::errors::app_error::{AppError, ErrorStatus};
+    use usecase::model::blog::BlogCursor;
+    use usecase::model::blog::{BlogRequest, MAX_BLOG_TITLE_LENGTH};
+    use usecase::model::image::ImageFormat;
+    use usecase::model::user::{Role, User};
+    use usecase::service::blog::blog_service::BlogService;
+    use usecase::service::service::Service;
     use uuid::Uuid;
 
     #[sqlx::test(migrations = "../src/migrations")]
     async fn test_create_draft_can_fetch_id(pool: sqlx::PgPool) -> Result<()> {
-        let repo = Repository::new(
-            pool,
-            Client::new(&aws_config::load_defaults(BehaviorVersion::latest()).await),
-            RedisClient::new(RedisConfig {
-                host: "test".to_string(),
-                port: "6937".to_string(),
-            })
-            .map_err(|e| anyhow!("uni"))
-            .expect("test"),
-            Config {
-                host: "test".into(),
-                env: "dev".into(),
-                token_ttl: 300,
-                refresh_ttl: 900,
-            },
-        );
+        let repo = initialize_repository(pool);
+        let now = Utc::now().naive_utc();
+        let id = Uuid::now_v7();
+        let draft = Blog {
+            id,
+            title: String::new(),
+            slug: id.simple().to_string(),
+            content_key: Blog::draft_key(&id),
+            status: BlogStatus::Draft,
+            created_at: now,
+            updated_at: now,
+            published_at: None,
+            author_id: None,
+        };
 
         let mut tx = repo.pool.begin().await?;
-        let draft_id = repo.create_draft(&mut tx).await;
+        let created = repo.create_draft(&mut tx, draft).await?;
 
-        if let Ok(id) = draft_id {
-            assert!(!id.is_empty());
-        }
+        assert_eq!(id, created.id);
+        assert!(matches!(created.status, BlogStatus::Draft));
+        assert!(created.published_at.is_none());
+        Ok(())
+    }
 
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_publish_and_unpublish_draft(pool: sqlx::PgPool) -> Result<()> {
+        let repo = initialize_repository(pool);
+        let now = Utc::now().naive_utc();
+        let id = Uuid::now_v7();
+        let draft = Blog {
+            id,
+            title: String::new(),
+            slug: id.simple().to_string(),
+            content_key: Blog::draft_key(&id),
+            status: BlogStatus::Draft,
+            created_at: now,
+            updated_at: now,
+            published_at: None,
+            author_id: None,
+        };
+
+        let mut tx = repo.pool.begin().await?;
+        repo.create_draft(&mut tx, draft).await?;
+        repo.update_blog_title(&mut tx, id, "autosaved", now)
+            .await?;
+        let published = repo
+            .update_blog_status(&mut tx, id, BlogStatus::Published, Some(now), now)
+            .await?;
+        assert_eq!("autosaved", published.title);
+        assert!(published.published_at.is_some());
+
+        let unpublished = repo
+            .update_blog_status(&mut tx, id, BlogStatus::Draft, None, now)
+            .await?;
+        assert!(matches!(unpublished.status, BlogStatus::Draft));
+        assert!(unpublished.published_at.is_none());
         Ok(())
     }
 
     #[sqlx::test(migrations = "../src/migrations")]
     async fn test_create_blog_already_exists(pool: sqlx::PgPool) -> Result<()> {
-        let repo = Repository::new(
-            pool,
-            Client::new(&aws_config::load_defaults(BehaviorVersion::latest()).await),
-            RedisClient::new(RedisConfig {
-                host: "test".to_string(),
-                port: "6937".to_string(),
-            })
-            .map_err(|e| anyhow!("uni"))
-            .expect("test"),
-            Config {
-                host: "test".into(),
-                env: "dev".into(),
-                token_ttl: 300,
-                refresh_ttl: 900,
-            },
-        );
+        let repo = initialize_repository(pool);
         let blog = Blog {
             id: Uuid::now_v7(),
             title: "Test Blog".to_string(),
+            slug: "test-blog".to_string(),
             content_key: "test-blog".to_string(),
             status: usecase::model::blog::BlogStatus::Published,
+            created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
+            published_at: Some(Utc::now().naive_utc()),
+            author_id: None,
         };
 
         let mut tx = repo.pool.begin().await?;
@@ -174,4 +669,344 @@ mod tests {
         assert!(matches!(result.unwrap_err(), RepoError::Conflict(_)));
         Ok(())
     }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_get_blog_not_found(pool: sqlx::PgPool) -> Result<()> {
+        let repo = initialize_repository(pool);
+
+        let result = repo.get_blog(Uuid::now_v7()).await;
+
+        assert!(matches!(result, Err(RepoError::NotFound(_))));
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_get_blog_returns_timestamps(pool: sqlx::PgPool) -> Result<()> {
+        let repo = initialize_repository(pool);
+        let id = published_blog(&repo, "detail", datetime(2026, 5, 5)).await?;
+
+        let blog = repo.get_blog(id).await?;
+
+        assert_eq!("detail", blog.title);
+        assert_eq!(Some(datetime(2026, 5, 5)), blog.published_at);
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_soft_deleted_blog_is_hidden(pool: sqlx::PgPool) -> Result<()> {
+        let repo = initialize_repository(pool);
+        let id = published_blog(&repo, "deleted", datetime(2026, 6, 1)).await?;
+
+        let mut tx = repo.pool.begin().await?;
+        repo.soft_delete_blog(&mut tx, id, Utc::now().naive_utc())
+            .await?;
+        tx.commit().await?;
+
+        assert!(matches!(
+            repo.get_blog(id).await,
+            Err(RepoError::NotFound(_))
+        ));
+        let filter = BlogFilter::new(None, None).map_err(|e| anyhow!(e.message))?;
+        assert!(repo.get_blogs(filter).await?.is_empty());
+
+        let mut tx = repo.pool.begin().await?;
+        let deleted_again = repo
+            .soft_delete_blog(&mut tx, id, Utc::now().naive_utc())
+            .await;
+        assert!(matches!(deleted_again, Err(RepoError::NotFound(_))));
+        // 論理削除済みでも完全に削除できる
+        repo.purge_blog(&mut tx, id).await?;
+        assert!(matches!(
+            repo.purge_blog(&mut tx, id).await,
+            Err(RepoError::NotFound(_))
+        ));
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_images_are_filtered_by_owner(pool: sqlx::PgPool) -> Result<()> {
+        let repo = initialize_repository(pool);
+        let owner = Uuid::now_v7();
+        let other = Uuid::now_v7();
+        for (id, name) in [(owner, "owner"), (other, "other")] {
+            sqlx::query!(
+                "INSERT INTO users (id, name, password) VALUES ($1, $2, 'hash')",
+                id,
+                name
+            )
+            .execute(&repo.pool)
+            .await?;
+        }
+        repo.record_image("owned", owner).await?;
+        repo.record_image("others", other).await?;
+
+        let ids = ["owned", "others", "unrecorded"].map(String::from);
+        assert_eq!(vec!["owned"], repo.images_owned_by(&ids, owner).await?);
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_get_blogs_filters_by_published_month(pool: sqlx::PgPool) -> Result<()> {
+        let repo = initialize_repository(pool);
+        let march = published_blog(&repo, "march", datetime(2026, 3, 10)).await?;
+        published_blog(&repo, "april", datetime(2026, 4, 1)).await?;
+        sqlx::query!(
+            "INSERT INTO blogs (title, slug, status, content_key) \
+             VALUES ('draft', 'draft', 'DRAFT', 'draft')"
+        )
+        .execute(&repo.pool)
+        .await?;
+
+        let filter = BlogFilter::new(Some(&"2026".to_string()), Some(&"3".to_string()))
+            .map_err(|e| anyhow!(e.message))?;
+        let blogs = repo.get_blogs(filter).await?;
+
+        assert_eq!(vec![march], blogs.iter().map(|b| b.id).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_get_blogs_paginates_with_cursor(pool: sqlx::PgPool) -> Result<()> {
+        let repo = initialize_repository(pool);
+        let first = published_blog(&repo, "first", datetime(2026, 1, 1)).await?;
+        let second = published_blog(&repo, "second", datetime(2026, 2, 1)).await?;
+        let third = published_blog(&repo, "third", datetime(2026, 3, 1)).await?;
+
+        let filter = BlogFilter::new(None, None)
+            .and_then(|f| f.with_limit(Some(&"2".to_string())))
+            .map_err(|e| anyhow!(e.message))?;
+        let page = repo.get_blogs(filter).await?;
+        // 次のページの判定用に1件多く返る
+        assert_eq!(
+            vec![third, second, first],
+            page.iter().map(|b| b.id).collect::<Vec<_>>()
+        );
+
+        let cursor = BlogCursor::from(&page[1]).encode();
+        let filter = BlogFilter::new(None, None)
+            .and_then(|f| f.with_cursor(Some(&cursor)))
+            .map_err(|e| anyhow!(e.message))?;
+        let rest = repo.get_blogs(filter).await?;
+        assert_eq!(vec![first], rest.iter().map(|b| b.id).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_create_blog_rejects_long_title(pool: sqlx::PgPool) -> Result<()> {
+        let service = Service::new(test_config(), Box::new(initialize_repository(pool)));
+        let editor = User {
+            id: Uuid::now_v7(),
+            name: "editor".to_string(),
+            password: String::new(),
+            role: Role::Editor,
+        };
+        let request = BlogRequest {
+            title: "a".repeat(MAX_BLOG_TITLE_LENGTH + 1),
+            slug: None,
+            content: "# body".to_string(),
+        };
+
+        let result = service.create_blog(&editor, request).await;
+        assert!(matches!(
+            result,
+            Err(AppError {
+                status: ErrorStatus::Invalid,
+                ..
+            })
+        ));
+        let filter = BlogFilter::new(None, None).map_err(|e| anyhow!(e.message))?;
+        assert!(service.repository.get_blogs(filter).await?.is_empty());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_delete_blog_by_other_editor_changes_nothing(pool: sqlx::PgPool) -> Result<()> {
+        let author = editor(&pool, "author").await?;
+        let other = editor(&pool, "other").await?;
+        let service = Service::new(test_config(), Box::new(initialize_repository(pool)));
+        let blog = service
+            .create_blog(
+                &author,
+                BlogRequest {
+                    title: "mine".to_string(),
+                    slug: None,
+                    content: "# body".to_string(),
+                },
+            )
+            .await
+            .map_err(|e| anyhow!(e.message))?;
+
+        for purge in [false, true] {
+            let result = service
+                .delete_blog(&other, &blog.id.to_string(), purge)
+                .await;
+            assert!(matches!(
+                result,
+                Err(AppError {
+                    status: ErrorStatus::Forbidden,
+                    ..
+                })
+            ));
+        }
+        assert_eq!(blog.id, service.repository.get_blog(blog.id).await?.id);
+        assert_eq!(
+            "# body",
+            service.repository.get_blog_content(blog.id).await?
+        );
+        assert!(
+            service
+                .repository
+                .get_blog_rendered(blog.id)
+                .await?
+                .is_some()
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_uploaded_image_can_be_fetched(pool: sqlx::PgPool) -> Result<()> {
+        let author = editor(&pool, "author").await?;
+        let service = Service::new(test_config(), Box::new(initialize_repository(pool)));
+        let png = maze_core::maze::png::render(
+            &maze_core::Maze::closed(2, 2),
+            &maze_core::RenderStyle::new(),
+        )?;
+
+        let image = service
+            .upload_blog_image(&author, Bytes::from(png))
+            .await
+            .map_err(|e| anyhow!(e.message))?;
+        // URLの最後の部分が、/_uploads/{name}で受ける名前になる
+        for url in std::iter::once(&image.url).chain(image.variants.iter().map(|v| &v.url)) {
+            let name = url.rsplit('/').next().unwrap();
+            let (data, format) = service
+                .get_uploaded_image(name)
+                .await
+                .map_err(|e| anyhow!(e.message))?;
+            assert_eq!(ImageFormat::Png, format);
+            assert!(!data.is_empty());
+        }
+        assert!(matches!(
+            service.get_uploaded_image("../drafts/x").await,
+            Err(AppError {
+                status: ErrorStatus::NotFound,
+                ..
+            })
+        ));
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_rename_slug_redirects_old_slug(pool: sqlx::PgPool) -> Result<()> {
+        let repo = initialize_repository(pool);
+        let id = published_blog(&repo, "old-slug", datetime(2026, 7, 1)).await?;
+
+        let mut tx = repo.pool.begin().await?;
+        assert!(!repo.is_slug_taken(&mut tx, "old-slug", id).await?);
+        assert!(
+            repo.is_slug_taken(&mut tx, "old-slug", Uuid::now_v7())
+                .await?
+        );
+        repo.rename_blog_slug(&mut tx, id, "old-slug", "new-slug")
+            .await?;
+        tx.commit().await?;
+
+        assert!(matches!(
+            repo.get_blog_by_slug("new-slug").await?,
+            // 本文のキーはスラッグを変えても変わらない
+            SlugLookup::Found(blog) if blog.id == id && blog.content_key == Blog::draft_key(&id)
+        ));
+        assert!(matches!(
+            repo.get_blog_by_slug("old-slug").await?,
+            SlugLookup::Redirect(current) if current == "new-slug"
+        ));
+        // 転送元のスラッグは他の記事には使わせない
+        let mut tx = repo.pool.begin().await?;
+        assert!(
+            repo.is_slug_taken(&mut tx, "old-slug", Uuid::now_v7())
+                .await?
+        );
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_rename_back_to_old_slug(pool: sqlx::PgPool) -> Result<()> {
+        let repo = initialize_repository(pool);
+        let id = published_blog(&repo, "first", datetime(2026, 7, 1)).await?;
+
+        let mut tx = repo.pool.begin().await?;
+        repo.rename_blog_slug(&mut tx, id, "first", "second")
+            .await?;
+        repo.rename_blog_slug(&mut tx, id, "second", "first")
+            .await?;
+        tx.commit().await?;
+
+        assert!(matches!(
+            repo.get_blog_by_slug("first").await?,
+            SlugLookup::Found(blog) if blog.id == id
+        ));
+        assert!(matches!(
+            repo.get_blog_by_slug("second").await?,
+            SlugLookup::Redirect(current) if current == "first"
+        ));
+        assert!(matches!(
+            repo.get_blog_by_slug("third").await,
+            Err(RepoError::NotFound(_))
+        ));
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_create_blog_with_duplicate_slug(pool: sqlx::PgPool) -> Result<()> {
+        let repo = initialize_repository(pool);
+        published_blog(&repo, "same", datetime(2026, 7, 1)).await?;
+
+        let result = published_blog(&repo, "same", datetime(2026, 7, 2)).await;
+
+        assert!(matches!(
+            result.map_err(|e| e.downcast::<RepoError>()),
+            Err(Ok(RepoError::Conflict(_)))
+        ));
+        Ok(())
+    }
+
+    fn datetime(year: i32, month: u32, day: u32) -> chrono::NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    async fn editor(pool: &sqlx::PgPool, name: &str) -> Result<User> {
+        let id = create_user(pool, name).await?;
+        Ok(User {
+            id,
+            name: name.to_string(),
+            password: String::new(),
+            role: Role::Editor,
+        })
+    }
+
+    async fn published_blog(
+        repo: &Repository,
+        title: &str,
+        published_at: chrono::NaiveDateTime,
+    ) -> Result<Uuid> {
+        let id = Uuid::now_v7();
+        let blog = Blog {
+            id,
+            title: title.to_string(),
+            slug: title.to_string(),
+            content_key: Blog::draft_key(&id),
+            status: usecase::model::blog::BlogStatus::Published,
+            created_at: published_at,
+            updated_at: published_at,
+            published_at: Some(published_at),
+            author_id: None,
+        };
+        let mut tx = repo.pool.begin().await?;
+        repo.create_blog(&mut tx, blog.clone()).await?;
+        tx.commit().await?;
+        Ok(blog.id)
+    }
 }
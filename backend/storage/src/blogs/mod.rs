@@ -1 +1,2 @@
 pub mod blog_repository;
+pub mod model;
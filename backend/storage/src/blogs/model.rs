@@ -0,0 +1,44 @@
+use chrono::NaiveDateTime;
+use usecase::{
+    errors::repo_error::RepoError,
+    model::blog::{Blog, BlogStatus},
+};
+use uuid::Uuid;
+
+// blogsテーブルの1行
+pub struct BlogRow {
+    pub id: Uuid,
+    pub title: String,
+    pub slug: String,
+    pub content_key: String,
+    pub status: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub published_at: Option<NaiveDateTime>,
+    pub author_id: Option<Uuid>,
+}
+
+impl TryFrom<BlogRow> for Blog {
+    type Error = RepoError;
+
+    fn try_from(row: BlogRow) -> Result<Self, Self::Error> {
+        // statusが未設定の行は下書きとして扱う
+        let status = match row.status {
+            Some(status) => status.parse::<BlogStatus>().map_err(|e| {
+                RepoError::Internal(format!("Invalid status of blog: {}, error: {}", row.id, e))
+            })?,
+            None => BlogStatus::Draft,
+        };
+        Ok(Self {
+            id: row.id,
+            title: row.title,
+            slug: row.slug,
+            content_key: row.content_key,
+            status,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            published_at: row.published_at,
+            author_id: row.author_id,
+        })
+    }
+}
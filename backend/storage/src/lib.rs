@@ -1,6 +1,11 @@
 pub mod base;
 pub mod blogs;
 pub mod database;
+pub mod mazes;
+pub mod object_storage;
 pub mod redis;
 pub mod repository;
 pub mod users;
+
+#[cfg(test)]
+pub(crate) mod test_support;
@@ -0,0 +1,180 @@
+use crate::mazes::model::MazeRow;
+
+use super::super::repository::*;
+use async_trait::async_trait;
+use tracing::error;
+use usecase::errors::repo_error::RepoError;
+use usecase::model::maze::SavedMaze;
+use usecase::repository::maze::MazeRepository;
+use uuid::Uuid;
+
+#[async_trait]
+impl MazeRepository for Repository {
+    async fn create_maze(&self, maze: SavedMaze) -> Result<SavedMaze, RepoError> {
+        let row: MazeRow = maze.into();
+        let row = sqlx::query_as!(
+            MazeRow,
+            "INSERT INTO mazes (id, owner_id, title, algorithm, seed, rows, cols) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) \
+             RETURNING id, owner_id, title, algorithm, seed, rows, cols, created_at",
+            row.id,
+            row.owner_id,
+            row.title,
+            row.algorithm,
+            row.seed,
+            row.rows,
+            row.cols
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            if let Some(db_err) = e.as_database_error()
+                && db_err.code() == Some("23505".into())
+            {
+                return RepoError::Conflict("Maze with the same id already exists".to_string());
+            }
+            error!("Failed to create maze: {}", e);
+            RepoError::Internal("Failed to create maze".to_string())
+        })?;
+
+        row.try_into()
+    }
+
+    async fn get_mazes(&self, owner_id: Uuid) -> Result<Vec<SavedMaze>, RepoError> {
+        let rows = sqlx::query_as!(
+            MazeRow,
+            "SELECT id, owner_id, title, algorithm, seed, rows, cols, created_at \
+             FROM mazes WHERE owner_id = $1 ORDER BY created_at DESC, id DESC",
+            owner_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to get mazes of user: {}, error: {}", owner_id, e);
+            RepoError::Internal("Failed to get mazes".to_string())
+        })?;
+
+        rows.into_iter().map(SavedMaze::try_from).collect()
+    }
+
+    async fn get_maze(&self, owner_id: Uuid, maze_id: Uuid) -> Result<SavedMaze, RepoError> {
+        let row = sqlx::query_as!(
+            MazeRow,
+            "SELECT id, owner_id, title, algorithm, seed, rows, cols, created_at \
+             FROM mazes WHERE id = $1 AND owner_id = $2",
+            maze_id,
+            owner_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => {
+                RepoError::NotFound(format!("Maze with id: {} not found", maze_id))
+            }
+            _ => RepoError::Internal(format!("Failed to get maze: {}, error: {}", maze_id, e)),
+        })?;
+
+        row.try_into()
+    }
+
+    async fn delete_maze(&self, owner_id: Uuid, maze_id: Uuid) -> Result<u64, RepoError> {
+        let result = sqlx::query!(
+            "DELETE FROM mazes WHERE id = $1 AND owner_id = $2",
+            maze_id,
+            owner_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            RepoError::Internal(format!("Failed to delete maze: {}, error: {}", maze_id, e))
+        })?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound(format!(
+                "Maze with id: {} not found",
+                maze_id
+            )));
+        }
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::test_support::{create_user, repository as initialize_repository};
+    use anyhow::Result;
+    use chrono::Utc;
+    use maze_core::MazeType;
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_create_and_get_maze(pool: sqlx::PgPool) -> Result<()> {
+        let owner_id = create_user(&pool, "maze_owner").await?;
+        let repo = initialize_repository(pool);
+
+        let maze = new_maze(owner_id, u64::MAX);
+        let created = repo.create_maze(maze.clone()).await?;
+        let fetched = repo.get_maze(owner_id, maze.id).await?;
+
+        assert_eq!(maze.id, created.id);
+        assert_eq!(u64::MAX, fetched.seed);
+        assert_eq!(MazeType::GrowingTree, fetched.maze_type);
+        assert_eq!((12, 34), (fetched.rows, fetched.cols));
+        assert_eq!(Some("gallery".to_string()), fetched.title);
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_get_maze_of_other_user_is_not_found(pool: sqlx::PgPool) -> Result<()> {
+        let owner_id = create_user(&pool, "maze_owner").await?;
+        let other_id = create_user(&pool, "other_user").await?;
+        let repo = initialize_repository(pool);
+
+        let maze = repo.create_maze(new_maze(owner_id, 1)).await?;
+
+        assert!(matches!(
+            repo.get_maze(other_id, maze.id).await,
+            Err(RepoError::NotFound(_))
+        ));
+        assert!(matches!(
+            repo.delete_maze(other_id, maze.id).await,
+            Err(RepoError::NotFound(_))
+        ));
+        assert!(repo.get_mazes(other_id).await?.is_empty());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../src/migrations")]
+    async fn test_list_and_delete_mazes(pool: sqlx::PgPool) -> Result<()> {
+        let owner_id = create_user(&pool, "maze_owner").await?;
+        let repo = initialize_repository(pool);
+
+        let first = repo.create_maze(new_maze(owner_id, 1)).await?;
+        let second = repo.create_maze(new_maze(owner_id, 2)).await?;
+
+        let mazes = repo.get_mazes(owner_id).await?;
+        assert_eq!(vec![second.id, first.id], ids(&mazes));
+
+        assert_eq!(1, repo.delete_maze(owner_id, first.id).await?);
+        assert_eq!(vec![second.id], ids(&repo.get_mazes(owner_id).await?));
+        Ok(())
+    }
+
+    fn ids(mazes: &[SavedMaze]) -> Vec<Uuid> {
+        mazes.iter().map(|maze| maze.id).collect()
+    }
+
+    fn new_maze(owner_id: Uuid, seed: u64) -> SavedMaze {
+        SavedMaze {
+            id: Uuid::now_v7(),
+            owner_id,
+            title: Some("gallery".to_string()),
+            maze_type: MazeType::GrowingTree,
+            seed,
+            rows: 12,
+            cols: 34,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod maze_repository;
+pub mod model;
@@ -0,0 +1,55 @@
+use chrono::NaiveDateTime;
+use maze_core::MazeType;
+use usecase::{errors::repo_error::RepoError, model::maze::SavedMaze};
+use uuid::Uuid;
+
+// mazesテーブルの1行
+pub struct MazeRow {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub title: Option<String>,
+    pub algorithm: String,
+    pub seed: i64,
+    pub rows: i32,
+    pub cols: i32,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<SavedMaze> for MazeRow {
+    fn from(maze: SavedMaze) -> Self {
+        Self {
+            id: maze.id,
+            owner_id: maze.owner_id,
+            title: maze.title,
+            algorithm: maze.maze_type.to_string(),
+            // BIGINTは符号付きなので、u64のビット列をそのまま保存する
+            seed: maze.seed as i64,
+            rows: maze.rows as i32,
+            cols: maze.cols as i32,
+            created_at: maze.created_at,
+        }
+    }
+}
+
+impl TryFrom<MazeRow> for SavedMaze {
+    type Error = RepoError;
+
+    fn try_from(row: MazeRow) -> Result<Self, Self::Error> {
+        let maze_type = row.algorithm.parse::<MazeType>().map_err(|e| {
+            RepoError::Internal(format!(
+                "Invalid algorithm of maze: {}, error: {}",
+                row.id, e
+            ))
+        })?;
+        Ok(Self {
+            id: row.id,
+            owner_id: row.owner_id,
+            title: row.title,
+            maze_type,
+            seed: row.seed as u64,
+            rows: row.rows as usize,
+            cols: row.cols as usize,
+            created_at: row.created_at,
+        })
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use tracing::error;
+use usecase::errors::repo_error::RepoError;
+use usecase::repository::object_storage::{ObjectMetadata, ObjectStorage};
+
+// ローカル開発用の保存先。root/{bucket}/{key}にファイルとして保存する。
+// Content-Typeなどのヘッダーは配信しないので保存しない
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    // rootの外を指すバケット名やキーは受け付けない
+    fn path(&self, bucket: &str, key: &str) -> Result<PathBuf, RepoError> {
+        let relative = Path::new(bucket).join(key);
+        let is_normal = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if bucket.is_empty() || key.is_empty() || !is_normal {
+            return Err(RepoError::Internal(format!(
+                "Invalid object key: {}/{}",
+                bucket, key
+            )));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for LocalStorage {
+    async fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: Bytes,
+        _: ObjectMetadata,
+    ) -> Result<(), RepoError> {
+        let path = self.path(bucket, key)?;
+        let write = async {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(&path, body).await
+        };
+        write.await.map_err(|e| {
+            error!("Failed to put object, path: {} err: {}", path.display(), e);
+            RepoError::Internal("Failed to put object".to_string())
+        })
+    }
+
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Option<Bytes>, RepoError> {
+        let path = self.path(bucket, key)?;
+        match tokio::fs::read(&path).await {
+            Ok(data) => Ok(Some(Bytes::from(data))),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => {
+                error!("Failed to get object, path: {} err: {}", path.display(), e);
+                Err(RepoError::Internal("Failed to get object".to_string()))
+            }
+        }
+    }
+
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<(), RepoError> {
+        let path = self.path(bucket, key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => {
+                error!(
+                    "Failed to delete object, path: {} err: {}",
+                    path.display(),
+                    e
+                );
+                Err(RepoError::Internal("Failed to delete object".to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("local-storage-{}-{}", name, uuid::Uuid::now_v7()))
+    }
+
+    #[tokio::test]
+    async fn put_get_and_delete_object() {
+        let root = temp_root("roundtrip");
+        let storage = LocalStorage::new(root.clone());
+
+        storage
+            .put_object(
+                "blog-assets",
+                "uploads/drafts/1",
+                Bytes::from("# title"),
+                ObjectMetadata::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            Some(Bytes::from("# title")),
+            storage
+                .get_object("blog-assets", "uploads/drafts/1")
+                .await
+                .unwrap()
+        );
+
+        storage
+            .delete_object("blog-assets", "uploads/drafts/1")
+            .await
+            .unwrap();
+        assert_eq!(
+            None,
+            storage
+                .get_object("blog-assets", "uploads/drafts/1")
+                .await
+                .unwrap()
+        );
+        // 存在しないキーの削除はエラーにしない
+        storage
+            .delete_object("blog-assets", "uploads/drafts/1")
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reject_keys_outside_root() {
+        let storage = LocalStorage::new(temp_root("outside"));
+
+        for key in ["../secret", "/etc/passwd", "uploads/../../secret", ""] {
+            assert!(matches!(
+                storage.get_object("blog-assets", key).await,
+                Err(RepoError::Internal(_))
+            ));
+        }
+        assert!(matches!(
+            storage.get_object("..", "key").await,
+            Err(RepoError::Internal(_))
+        ));
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use usecase::errors::repo_error::RepoError;
+use usecase::repository::object_storage::{ObjectMetadata, ObjectStorage};
+
+// (バケット, キー)ごとの中身
+type Objects = HashMap<(String, String), Bytes>;
+
+// テスト用の保存先。プロセスが終わると消える
+#[derive(Default)]
+pub struct InMemoryStorage {
+    objects: Mutex<Objects>,
+}
+
+impl InMemoryStorage {
+    fn objects(&self) -> Result<MutexGuard<'_, Objects>, RepoError> {
+        self.objects
+            .lock()
+            .map_err(|_| RepoError::Internal("Object storage is poisoned".to_string()))
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for InMemoryStorage {
+    async fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: Bytes,
+        _: ObjectMetadata,
+    ) -> Result<(), RepoError> {
+        self.objects()?
+            .insert((bucket.to_string(), key.to_string()), body);
+        Ok(())
+    }
+
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Option<Bytes>, RepoError> {
+        Ok(self
+            .objects()?
+            .get(&(bucket.to_string(), key.to_string()))
+            .cloned())
+    }
+
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<(), RepoError> {
+        self.objects()?
+            .remove(&(bucket.to_string(), key.to_string()));
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+pub mod local;
+pub mod memory;
+pub mod s3;
+
+use shared::config::{StorageBackend, StorageConfig};
+use usecase::repository::object_storage::ObjectStorage;
+
+use self::local::LocalStorage;
+use self::memory::InMemoryStorage;
+use self::s3::S3Storage;
+
+// 設定に応じた保存先を作る
+pub async fn connect(config: &StorageConfig) -> Box<dyn ObjectStorage> {
+    match &config.backend {
+        StorageBackend::R2 {
+            account_id,
+            access_key_id,
+            secret_access_key,
+        } => Box::new(S3Storage::r2(account_id, access_key_id, secret_access_key).await),
+        StorageBackend::Local { root } => Box::new(LocalStorage::new(root.clone())),
+        StorageBackend::Memory => Box::new(InMemoryStorage::default()),
+    }
+}
@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_s3::Client;
+use aws_sdk_s3::config::Credentials;
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::primitives::ByteStream;
+use bytes::Bytes;
+use tracing::error;
+use usecase::errors::repo_error::RepoError;
+use usecase::repository::object_storage::{ObjectMetadata, ObjectStorage};
+
+// S3互換のAPIで接続する保存先
+pub struct S3Storage {
+    client: Client,
+}
+
+impl S3Storage {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    // Cloudflare R2に接続する
+    pub async fn r2(account_id: &str, access_key_id: &str, secret_access_key: &str) -> Self {
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .endpoint_url(format!("https://{}.r2.cloudflarestorage.com", account_id))
+            .region(Region::new("auto"))
+            .credentials_provider(Credentials::new(
+                access_key_id,
+                secret_access_key,
+                None,
+                None,
+                "R2",
+            ))
+            .load()
+            .await;
+        Self::new(Client::new(&config))
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for S3Storage {
+    async fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: Bytes,
+        metadata: ObjectMetadata,
+    ) -> Result<(), RepoError> {
+        self.client
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .set_content_type(metadata.content_type)
+            .set_cache_control(metadata.cache_control)
+            .body(ByteStream::from(body))
+            .send()
+            .await
+            .map_err(|e| {
+                error!(bucket = %bucket, key = %key, error = %e);
+                error!(
+                    code = e.code(),
+                    message = e.message().unwrap_or("No error message")
+                );
+                RepoError::Internal("Failed to put object".to_string())
+            })?;
+        Ok(())
+    }
+
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Option<Bytes>, RepoError> {
+        let output = self
+            .client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await;
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                let e = e.into_service_error();
+                if e.is_no_such_key() {
+                    return Ok(None);
+                }
+                error!("Failed to get object, key: {} err: {}", key, e);
+                return Err(RepoError::Internal("Failed to get object".to_string()));
+            }
+        };
+
+        let body = output.body.collect().await.map_err(|e| {
+            error!("Failed to read object, key: {} err: {}", key, e);
+            RepoError::Internal("Failed to read object".to_string())
+        })?;
+        Ok(Some(body.into_bytes()))
+    }
+
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<(), RepoError> {
+        self.client
+            .delete_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| {
+                error!("Failed to delete object, key: {} err: {}", key, e);
+                RepoError::Internal("Failed to delete object".to_string())
+            })?;
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use redis::{AsyncCommands, Client};
 use shared::config::RedisConfig;
 use usecase::errors::repo_error::RepoError;
 
-use self::model::{RedisKey, RedisValue};
+use self::model::{KeyTtl, RedisKey, RedisValue};
 
 pub struct RedisClient {
     client: Client,
@@ -23,14 +23,14 @@ impl RedisClient {
         ttl: u64,
     ) -> Result<(), RepoError> {
         let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let _: () = conn.set_ex(key.inner(), val.inner(), ttl).await?;
+        let _: () = conn.set_ex(key.inner(), val.encode()?, ttl).await?;
         Ok(())
     }
 
     pub async fn get<T: RedisKey>(&self, key: T) -> Result<Option<T::Value>, RepoError> {
         let mut conn = self.client.get_multiplexed_async_connection().await?;
         let res: Option<String> = conn.get(key.inner()).await?;
-        res.map(T::Value::try_from).transpose()
+        res.map(T::Value::decode).transpose()
     }
 
     pub async fn delete<T: RedisKey>(&self, key: T) -> Result<u64, RepoError> {
@@ -38,4 +38,130 @@ impl RedisClient {
         let count: u64 = conn.del(key.inner()).await?;
         Ok(count)
     }
+
+    // 複数の値を同じ時点の状態で読む。keysと同じ順に並ぶ
+    pub async fn get_many<T: RedisKey>(
+        &self,
+        keys: &[T],
+    ) -> Result<Vec<Option<T::Value>>, RepoError> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let keys: Vec<String> = keys.iter().map(RedisKey::inner).collect();
+        let res: Vec<Option<String>> = redis::cmd("MGET").arg(keys).query_async(&mut conn).await?;
+        res.into_iter()
+            .map(|val| val.map(T::Value::decode).transpose())
+            .collect()
+    }
+
+    pub async fn ttl<T: RedisKey>(&self, key: &T) -> Result<KeyTtl, RepoError> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let ttl: i64 = redis::cmd("TTL")
+            .arg(key.inner())
+            .query_async(&mut conn)
+            .await?;
+        Ok(KeyTtl::from(ttl))
+    }
+
+    // 1増やして増やした後の値を返し、有効期限をttlにする。キーがなければ0から数える
+    pub async fn incr_ex<T: RedisKey<Value = u64>>(
+        &self,
+        key: &T,
+        ttl: u64,
+    ) -> Result<u64, RepoError> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let (count,): (u64,) = redis::pipe()
+            .atomic()
+            .incr(key.inner(), 1)
+            .expire(key.inner(), ttl as i64)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok(count)
+    }
+
+    // 値を書き換え、書き換える前の値を返す。残りの有効期限は変えず、キーがなければ何もしない
+    pub async fn replace<T: RedisKey>(
+        &self,
+        key: &T,
+        val: &T::Value,
+    ) -> Result<Option<T::Value>, RepoError> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let res: Option<String> = redis::cmd("SET")
+            .arg(key.inner())
+            .arg(val.encode()?)
+            .arg("XX")
+            .arg("KEEPTTL")
+            .arg("GET")
+            .query_async(&mut conn)
+            .await?;
+        res.map(T::Value::decode).transpose()
+    }
+
+    // 集合に値を加え、集合の有効期限をttlにする
+    pub async fn add_member<T: RedisKey>(
+        &self,
+        key: &T,
+        member: &T::Value,
+        ttl: u64,
+    ) -> Result<(), RepoError> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let _: () = redis::pipe()
+            .atomic()
+            .sadd(key.inner(), member.encode()?)
+            .ignore()
+            .expire(key.inner(), ttl as i64)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn members<T: RedisKey>(&self, key: &T) -> Result<Vec<T::Value>, RepoError> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let members: Vec<String> = conn.smembers(key.inner()).await?;
+        members.into_iter().map(T::Value::decode).collect()
+    }
+
+    pub async fn remove_members<T: RedisKey>(
+        &self,
+        key: &T,
+        members: &[T::Value],
+    ) -> Result<u64, RepoError> {
+        if members.is_empty() {
+            return Ok(0);
+        }
+        let members = members
+            .iter()
+            .map(RedisValue::encode)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let count: u64 = conn.srem(key.inner(), members).await?;
+        Ok(count)
+    }
+
+    // 有効期限をttlにする。キーがなければfalse
+    pub async fn expire<T: RedisKey>(&self, key: &T, ttl: u64) -> Result<bool, RepoError> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let updated: bool = conn.expire(key.inner(), ttl as i64).await?;
+        Ok(updated)
+    }
+
+    // 集合の値をキーとみなして、集合ごとまとめて削除する。消したキーの数を返す
+    //
+    // 消すキーは全てコマンドの引数として渡す。読んでから消すまでの間に加わった値のキーは残るが、
+    // どのキーにも有効期限があるので、いずれ消える
+    pub async fn delete_with_members<T: RedisKey>(&self, key: &T) -> Result<u64, RepoError> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let mut keys: Vec<String> = conn.smembers(key.inner()).await?;
+        keys.push(key.inner());
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for key in &keys {
+            pipe.del(key);
+        }
+        let counts: Vec<u64> = pipe.query_async(&mut conn).await?;
+        Ok(counts.into_iter().sum())
+    }
 }
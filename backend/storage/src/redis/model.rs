@@ -1,10 +1,139 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use usecase::errors::repo_error::RepoError;
 
+// 全てのキーの先頭に付ける接頭辞。同じRedisを他のアプリと共有しても衝突しない
+pub const KEY_PREFIX: &str = "maze_creator";
+
+// キーは"{KEY_PREFIX}:{NAMESPACE}:v{VERSION}:{id}"の形になる
 pub trait RedisKey {
-    type Value: RedisValue + TryFrom<String, Error = RepoError>;
-    fn inner(&self) -> String;
+    type Value: RedisValue;
+    // キーの種類ごとの名前空間。セッション、レート制限、キャッシュなどで重ならないようにする
+    const NAMESPACE: &'static str;
+    // 値の形を変えたら上げる。古い形式で書かれた値を読み違えないようにする
+    const VERSION: u32;
+
+    fn id(&self) -> String;
+
+    // 名前空間とバージョンまでの部分
+    fn prefix() -> String {
+        format!("{}:{}:v{}:", KEY_PREFIX, Self::NAMESPACE, Self::VERSION)
+    }
+
+    fn inner(&self) -> String {
+        format!("{}{}", Self::prefix(), self.id())
+    }
+}
+
+pub trait RedisValue: Sized {
+    fn encode(&self) -> Result<String, RepoError>;
+    fn decode(value: String) -> Result<Self, RepoError>;
+}
+
+// 文字列のまま保存する値
+impl RedisValue for String {
+    fn encode(&self) -> Result<String, RepoError> {
+        Ok(self.clone())
+    }
+
+    fn decode(value: String) -> Result<Self, RepoError> {
+        Ok(value)
+    }
+}
+
+// INCRで数える値
+impl RedisValue for u64 {
+    fn encode(&self) -> Result<String, RepoError> {
+        Ok(self.to_string())
+    }
+
+    fn decode(value: String) -> Result<Self, RepoError> {
+        value
+            .parse()
+            .map_err(|e| RepoError::Internal(format!("Failed to decode redis value: {e}")))
+    }
+}
+
+// JSONにして保存する値
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Json<T>(pub T);
+
+impl<T: Serialize + DeserializeOwned> RedisValue for Json<T> {
+    fn encode(&self) -> Result<String, RepoError> {
+        serde_json::to_string(&self.0)
+            .map_err(|e| RepoError::Internal(format!("Failed to encode redis value: {e}")))
+    }
+
+    fn decode(value: String) -> Result<Self, RepoError> {
+        serde_json::from_str(&value)
+            .map(Json)
+            .map_err(|e| RepoError::Internal(format!("Failed to decode redis value: {e}")))
+    }
+}
+
+// キーの残り寿命
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyTtl {
+    Missing,
+    Persistent,
+    Seconds(u64),
 }
 
-pub trait RedisValue {
-    fn inner(&self) -> String;
+impl From<i64> for KeyTtl {
+    // TTLコマンドはキーがなければ-2、期限がなければ-1を返す
+    fn from(ttl: i64) -> Self {
+        match ttl {
+            -2 => KeyTtl::Missing,
+            ttl if ttl < 0 => KeyTtl::Persistent,
+            ttl => KeyTtl::Seconds(ttl as u64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    struct CacheKey(&'static str);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Entry {
+        count: u32,
+    }
+
+    impl RedisKey for CacheKey {
+        type Value = Json<Entry>;
+        const NAMESPACE: &'static str = "cache";
+        const VERSION: u32 = 2;
+
+        fn id(&self) -> String {
+            self.0.to_string()
+        }
+    }
+
+    #[test]
+    fn keys_are_namespaced_and_versioned() {
+        assert_eq!("maze_creator:cache:v2:", CacheKey::prefix());
+        assert_eq!("maze_creator:cache:v2:abc", CacheKey("abc").inner());
+    }
+
+    #[test]
+    fn json_values_round_trip() {
+        let encoded = Json(Entry { count: 3 }).encode().unwrap();
+
+        assert_eq!(r#"{"count":3}"#, encoded);
+        assert_eq!(
+            Json(Entry { count: 3 }),
+            Json::<Entry>::decode(encoded).unwrap()
+        );
+        assert!(Json::<Entry>::decode("3".to_string()).is_err());
+    }
+
+    #[test]
+    fn ttl_replies_are_classified() {
+        assert_eq!(KeyTtl::Missing, KeyTtl::from(-2));
+        assert_eq!(KeyTtl::Persistent, KeyTtl::from(-1));
+        assert_eq!(KeyTtl::Seconds(30), KeyTtl::from(30));
+    }
 }
@@ -1,21 +1,29 @@
 use crate::redis::RedisClient;
-use aws_sdk_s3::Client;
-use shared::config::Config;
+use shared::config::{Config, StorageConfig};
 use sqlx::PgPool;
+use usecase::repository::object_storage::ObjectStorage;
 use usecase::repository::repositories::Repositories;
 
 pub struct Repository {
     pub pool: PgPool,
-    pub r2_client: Client,
+    pub object_storage: Box<dyn ObjectStorage>,
+    pub storage_config: StorageConfig,
     pub redis_client: RedisClient,
     pub config: Config,
 }
 
 impl Repository {
-    pub fn new(pool: PgPool, r2_client: Client, redis_client: RedisClient, config: Config) -> Self {
+    pub fn new(
+        pool: PgPool,
+        object_storage: Box<dyn ObjectStorage>,
+        storage_config: StorageConfig,
+        redis_client: RedisClient,
+        config: Config,
+    ) -> Self {
         Self {
             pool,
-            r2_client,
+            object_storage,
+            storage_config,
             redis_client,
             config,
         }
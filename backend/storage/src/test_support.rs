@@ -0,0 +1,58 @@
+// ストレージ層のテストで共有するリポジトリの組み立て
+use crate::object_storage::memory::InMemoryStorage;
+use crate::redis::RedisClient;
+use crate::repository::Repository;
+use shared::config::{
+    Config, Pepper, Peppers, RedisConfig, StorageBackend, StorageConfig, TrustedProxies,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub fn test_config() -> Config {
+    Config {
+        host: "test".into(),
+        env: "dev".into(),
+        token_ttl: 300,
+        refresh_ttl: 900,
+        password_peppers: Peppers::new(Pepper::new("1", "pepper")),
+        cookie_secure: true,
+        trusted_proxies: TrustedProxies::default(),
+    }
+}
+
+// 接続しないダミーのRedisクライアント(Redisを使わないテスト向け)
+pub fn dummy_redis() -> RedisClient {
+    RedisClient::new(RedisConfig {
+        host: "test".to_string(),
+        port: "6937".to_string(),
+    })
+    .expect("creating redis client failed")
+}
+
+pub fn repository_with_redis(pool: PgPool, redis: RedisClient) -> Repository {
+    Repository::new(
+        pool,
+        Box::new(InMemoryStorage::default()),
+        StorageConfig {
+            blog_bucket: "blog-assets".into(),
+            blog_image_bucket: "blog-assets".into(),
+            backend: StorageBackend::Memory,
+        },
+        redis,
+        test_config(),
+    )
+}
+
+pub fn repository(pool: PgPool) -> Repository {
+    repository_with_redis(pool, dummy_redis())
+}
+
+pub async fn create_user(pool: &PgPool, name: &str) -> anyhow::Result<Uuid> {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, name, password) VALUES ($1, $2, '')")
+        .bind(id)
+        .bind(name)
+        .execute(pool)
+        .await?;
+    Ok(id)
+}
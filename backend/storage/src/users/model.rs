@@ -1,11 +1,106 @@
-use crate::redis::model::RedisValue;
-
-use super::super::redis::model::RedisKey;
-use usecase::{errors::repo_error::RepoError, model::user::Token};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use usecase::errors::repo_error::RepoError;
+use usecase::model::user::{
+    ApiToken, LoginScope, Permission, RefreshToken, Role, Session, SessionToken, Token, User,
+};
 use uuid::Uuid;
 
+use super::super::redis::model::{Json, RedisKey};
+
+// usersテーブルの1行
+pub struct UserRow {
+    pub id: Uuid,
+    pub name: String,
+    pub password: String,
+    pub role: String,
+}
+
+impl TryFrom<UserRow> for User {
+    type Error = RepoError;
+
+    fn try_from(row: UserRow) -> Result<Self, Self::Error> {
+        let role = row.role.parse::<Role>().map_err(|e| {
+            RepoError::Internal(format!("Invalid role of user: {}, error: {}", row.id, e))
+        })?;
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            password: row.password,
+            role,
+        })
+    }
+}
+
+// api_tokensテーブルの1行
+pub struct ApiTokenRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: NaiveDateTime,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+impl TryFrom<ApiTokenRow> for ApiToken {
+    type Error = RepoError;
+
+    fn try_from(row: ApiTokenRow) -> Result<Self, Self::Error> {
+        let scopes = row
+            .scopes
+            .iter()
+            .map(|scope| scope.parse::<Permission>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                RepoError::Internal(format!(
+                    "Invalid scope of api token: {}, error: {}",
+                    row.id, e
+                ))
+            })?;
+        Ok(Self {
+            id: row.id,
+            user_id: row.user_id,
+            name: row.name,
+            prefix: row.prefix,
+            scopes,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+        })
+    }
+}
+
 pub struct AccessToken(pub String);
-pub struct AuthorizedUserId(pub String);
+
+// アクセストークンに紐づけて保存する情報
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEntry {
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub csrf_token: String,
+}
+
+impl From<&SessionToken> for SessionEntry {
+    fn from(session: &SessionToken) -> Self {
+        Self {
+            user_id: session.user_id,
+            family_id: session.session_id,
+            csrf_token: session.csrf_token.clone(),
+        }
+    }
+}
+
+impl From<SessionEntry> for SessionToken {
+    fn from(entry: SessionEntry) -> Self {
+        Self {
+            user_id: entry.user_id,
+            session_id: entry.family_id,
+            csrf_token: entry.csrf_token,
+        }
+    }
+}
 
 impl From<Token> for AccessToken {
     fn from(value: Token) -> Self {
@@ -20,28 +115,155 @@ impl From<String> for AccessToken {
 }
 
 impl RedisKey for AccessToken {
-    type Value = AuthorizedUserId;
-    fn inner(&self) -> String {
+    type Value = Json<SessionEntry>;
+    const NAMESPACE: &'static str = "session";
+    // 2: CSRFトークンを加えた
+    const VERSION: u32 = 2;
+
+    fn id(&self) -> String {
         self.0.clone()
     }
 }
 
-impl RedisValue for AuthorizedUserId {
-    fn inner(&self) -> String {
+// アクセストークンと取り違えないよう、リフレッシュトークンは別の名前空間に置く
+pub struct RefreshTokenKey(pub String);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshTokenEntry {
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub csrf_token: String,
+    pub rotated: bool,
+}
+
+impl RedisKey for RefreshTokenKey {
+    type Value = Json<RefreshTokenEntry>;
+    const NAMESPACE: &'static str = "refresh_token";
+    // 2: CSRFトークンを加えた
+    const VERSION: u32 = 2;
+
+    fn id(&self) -> String {
         self.0.clone()
     }
 }
 
-impl From<Uuid> for AuthorizedUserId {
-    fn from(value: Uuid) -> Self {
-        Self(value.to_string())
+impl From<RefreshTokenEntry> for RefreshToken {
+    fn from(entry: RefreshTokenEntry) -> Self {
+        Self {
+            user_id: entry.user_id,
+            family_id: entry.family_id,
+            csrf_token: entry.csrf_token,
+            rotated: entry.rotated,
+        }
     }
 }
 
-impl TryFrom<String> for AuthorizedUserId {
-    type Error = RepoError;
+// 1回のログインから続くトークンのキーを集めた集合
+pub struct TokenFamilyKey(pub Uuid);
+
+impl RedisKey for TokenFamilyKey {
+    type Value = String;
+    const NAMESPACE: &'static str = "token_family";
+    const VERSION: u32 = 1;
+
+    fn id(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+// ログインした端末の情報。キーはセッションのid(family_id)
+pub struct SessionInfoKey(pub Uuid);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfoEntry {
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl RedisKey for SessionInfoKey {
+    type Value = Json<SessionInfoEntry>;
+    const NAMESPACE: &'static str = "session_info";
+    const VERSION: u32 = 1;
+
+    fn id(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl From<&Session> for SessionInfoEntry {
+    fn from(session: &Session) -> Self {
+        Self {
+            user_id: session.user_id,
+            user_agent: session.user_agent.clone(),
+            ip: session.ip.clone(),
+            created_at: session.created_at,
+        }
+    }
+}
+
+impl SessionInfoEntry {
+    pub fn into_session(self, id: Uuid) -> Session {
+        Session {
+            id,
+            user_id: self.user_id,
+            user_agent: self.user_agent,
+            ip: self.ip,
+            created_at: self.created_at,
+        }
+    }
+}
+
+// 利用者ごとのセッションのidの集合。期限切れのidは一覧を作るときに取り除く
+pub struct UserSessionsKey(pub Uuid);
+
+impl RedisKey for UserSessionsKey {
+    type Value = String;
+    const NAMESPACE: &'static str = "user_sessions";
+    const VERSION: u32 = 1;
+
+    fn id(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+fn login_scope_id(scope: &LoginScope) -> String {
+    match scope {
+        LoginScope::Ip(ip) => format!("ip:{}", ip),
+        // IPには"/"が含まれないので、最初の"/"で区切れる
+        LoginScope::Account { username, ip } => {
+            format!(
+                "account:{}/{}",
+                ip.as_deref().unwrap_or("unknown"),
+                username
+            )
+        }
+    }
+}
+
+// ログインに続けて失敗した回数
+pub struct LoginFailuresKey<'a>(pub &'a LoginScope);
+
+impl RedisKey for LoginFailuresKey<'_> {
+    type Value = u64;
+    const NAMESPACE: &'static str = "login_failures";
+    const VERSION: u32 = 1;
+
+    fn id(&self) -> String {
+        login_scope_id(self.0)
+    }
+}
+
+// ログインを止めている印。値はロックした秒数で、キーの有効期限が切れると解除される
+pub struct LoginLockKey<'a>(pub &'a LoginScope);
+
+impl RedisKey for LoginLockKey<'_> {
+    type Value = u64;
+    const NAMESPACE: &'static str = "login_lock";
+    const VERSION: u32 = 1;
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        Ok(Self(value.clone()))
+    fn id(&self) -> String {
+        login_scope_id(self.0)
     }
 }
@@ -1,22 +1,27 @@
-use std::str::FromStr;
-
-use crate::redis::model::RedisValue;
-use crate::users::model::{AccessToken, AuthorizedUserId};
+use crate::redis::model::{Json, KeyTtl, RedisKey};
+use crate::users::model::{
+    AccessToken, ApiTokenRow, LoginFailuresKey, LoginLockKey, RefreshTokenEntry, RefreshTokenKey,
+    SessionEntry, SessionInfoEntry, SessionInfoKey, TokenFamilyKey, UserRow, UserSessionsKey,
+};
 
 use super::super::repository::*;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use sqlx;
+use tracing::error;
 use usecase::errors::repo_error::RepoError;
-use usecase::model::user::{Token, User};
+use usecase::model::user::{
+    ApiToken, LoginScope, RefreshToken, Role, Session, SessionToken, Token, User,
+};
 use usecase::repository::user::UserRepository;
 use uuid::Uuid;
 
 #[async_trait]
 impl UserRepository for Repository {
     async fn get_user_by_username(&self, username: &String) -> Result<User, RepoError> {
-        let user = sqlx::query_as!(
-            User,
-            "SELECT id, name, password, salt FROM users WHERE name = $1",
+        let row = sqlx::query_as!(
+            UserRow,
+            "SELECT id, name, password, role FROM users WHERE name = $1",
             username
         )
         .fetch_one(&self.pool)
@@ -31,13 +36,13 @@ impl UserRepository for Repository {
             )),
         })?;
 
-        Ok(user)
+        row.try_into()
     }
 
     async fn get_user(&self, user_id: Uuid) -> Result<User, RepoError> {
-        let user = sqlx::query_as!(
-            User,
-            "SELECT id, name, password, salt FROM users WHERE id = $1",
+        let row = sqlx::query_as!(
+            UserRow,
+            "SELECT id, name, password, role FROM users WHERE id = $1",
             user_id
         )
         .fetch_one(&self.pool)
@@ -52,14 +57,81 @@ impl UserRepository for Repository {
             )),
         })?;
 
-        Ok(user)
+        row.try_into()
+    }
+
+    async fn upsert_user(&self, user: &User) -> Result<Uuid, RepoError> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO users (id, name, password, role) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (name) DO UPDATE SET password = EXCLUDED.password
+            RETURNING id
+            "#,
+            user.id,
+            user.name,
+            user.password,
+            user.role.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            RepoError::Internal(format!("Failed to save user: {}, error: {}", user.name, e))
+        })?;
+        Ok(id)
+    }
+
+    async fn update_password(&self, user_id: Uuid, password: &str) -> Result<(), RepoError> {
+        let result = sqlx::query!(
+            "UPDATE users SET password = $1 WHERE id = $2",
+            password,
+            user_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            RepoError::Internal(format!(
+                "Failed to update password of user: {}, error: {}",
+                user_id, e
+            ))
+        })?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound(format!(
+                "User with user_id: {} not found",
+                user_id
+            )));
+        }
+        Ok(())
+    }
+
+    async fn update_role(&self, username: &str, role: Role) -> Result<(), RepoError> {
+        let result = sqlx::query!(
+            "UPDATE users SET role = $1 WHERE name = $2",
+            role.to_string(),
+            username
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            RepoError::Internal(format!(
+                "Failed to update role of user: {}, error: {}",
+                username, e
+            ))
+        })?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound(format!(
+                "User with username: {} not found",
+                username
+            )));
+        }
+        Ok(())
     }
 
-    async fn create_token(&self, user_id: Uuid, ttl: u64) -> Result<Token, RepoError> {
-        let token = Token::new(user_id.clone());
+    async fn create_token(&self, session: &SessionToken, ttl: u64) -> Result<Token, RepoError> {
+        let token = Token::new(session.user_id);
         let key: AccessToken = token.access_token.clone().into();
-        let val: AuthorizedUserId = user_id.into();
+        let val = Json(SessionEntry::from(session));
         self.redis_client.set_ex(&key, &val, ttl).await?;
+        self.add_to_family(session.session_id, &key).await?;
         Ok(token)
     }
 
@@ -68,13 +140,273 @@ impl UserRepository for Repository {
         self.redis_client.delete(key).await
     }
 
-    async fn fetch_user_id_by_token(&self, access_token: String) -> Option<Uuid> {
+    async fn fetch_session_by_token(&self, access_token: String) -> Option<SessionToken> {
         let key: AccessToken = access_token.into();
         match self.redis_client.get(key).await {
-            Ok(user_id) => user_id.and_then(|uid| Uuid::from_str(&uid.inner()).ok()),
+            Ok(entry) => entry.map(|Json(entry)| entry.into()),
             Err(_) => None,
         }
     }
+
+    async fn create_refresh_token(
+        &self,
+        session: &SessionToken,
+        ttl: u64,
+    ) -> Result<String, RepoError> {
+        let (user_id, family_id) = (session.user_id, session.session_id);
+        let refresh_token = Token::new(user_id).access_token;
+        let key = RefreshTokenKey(refresh_token.clone());
+        let val = Json(RefreshTokenEntry {
+            user_id,
+            family_id,
+            csrf_token: session.csrf_token.clone(),
+            rotated: false,
+        });
+        self.redis_client.set_ex(&key, &val, ttl).await?;
+        self.add_to_family(family_id, &key).await?;
+        // 使われ続けているセッションの情報と一覧は、リフレッシュトークンと一緒に延命する
+        self.redis_client
+            .expire(&SessionInfoKey(family_id), ttl)
+            .await?;
+        self.redis_client
+            .expire(&UserSessionsKey(user_id), ttl)
+            .await?;
+        Ok(refresh_token)
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Option<RefreshToken>, RepoError> {
+        let key = RefreshTokenKey(refresh_token.to_string());
+        let Some(Json(entry)) = self.redis_client.get(key).await? else {
+            return Ok(None);
+        };
+        // 同時に使われても、ローテーション前の状態を受け取れるのは1つだけになる
+        let key = RefreshTokenKey(refresh_token.to_string());
+        let rotated = Json(RefreshTokenEntry {
+            rotated: true,
+            ..entry
+        });
+        Ok(self
+            .redis_client
+            .replace(&key, &rotated)
+            .await?
+            .map(|Json(entry)| entry.into()))
+    }
+
+    async fn revoke_token_family(&self, family_id: Uuid) -> Result<u64, RepoError> {
+        self.redis_client
+            .delete_with_members(&TokenFamilyKey(family_id))
+            .await
+    }
+
+    async fn create_session(&self, session: &Session, ttl: u64) -> Result<(), RepoError> {
+        let key = SessionInfoKey(session.id);
+        self.redis_client
+            .set_ex(&key, &Json(SessionInfoEntry::from(session)), ttl)
+            .await?;
+        // 系列に含めておき、トークンと一緒に消えるようにする
+        self.add_to_family(session.id, &key).await?;
+        self.redis_client
+            .add_member(
+                &UserSessionsKey(session.user_id),
+                &session.id.to_string(),
+                ttl,
+            )
+            .await
+    }
+
+    async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, RepoError> {
+        let index = UserSessionsKey(user_id);
+        let members = self.redis_client.members(&index).await?;
+        let ids: Vec<Uuid> = members
+            .iter()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect();
+        let keys: Vec<SessionInfoKey> = ids.iter().copied().map(SessionInfoKey).collect();
+        let entries = self.redis_client.get_many(&keys).await?;
+
+        let mut sessions = Vec::new();
+        let mut stale = Vec::new();
+        for (id, entry) in ids.into_iter().zip(entries) {
+            match entry {
+                Some(Json(entry)) if entry.user_id == user_id => {
+                    sessions.push(entry.into_session(id))
+                }
+                _ => stale.push(id.to_string()),
+            }
+        }
+        self.redis_client.remove_members(&index, &stale).await?;
+
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.created_at));
+        Ok(sessions)
+    }
+
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<(), RepoError> {
+        let owned = matches!(
+            self.redis_client.get(SessionInfoKey(session_id)).await?,
+            Some(Json(entry)) if entry.user_id == user_id
+        );
+        if !owned {
+            return Err(RepoError::NotFound(format!(
+                "Session with session_id: {} not found",
+                session_id
+            )));
+        }
+        self.revoke_token_family(session_id).await?;
+        self.redis_client
+            .remove_members(&UserSessionsKey(user_id), &[session_id.to_string()])
+            .await?;
+        Ok(())
+    }
+
+    async fn revoke_all_sessions(&self, user_id: Uuid) -> Result<u64, RepoError> {
+        let index = UserSessionsKey(user_id);
+        let mut revoked = 0;
+        for id in self.redis_client.members(&index).await? {
+            if let Ok(family_id) = Uuid::parse_str(&id)
+                && self.revoke_token_family(family_id).await? > 0
+            {
+                revoked += 1;
+            }
+        }
+        self.redis_client.delete(index).await?;
+        Ok(revoked)
+    }
+
+    async fn login_lockout(&self, scope: &LoginScope) -> Result<Option<u64>, RepoError> {
+        // lock_loginは必ず期限を付けるので、期限のない印は残っていない
+        match self.redis_client.ttl(&LoginLockKey(scope)).await? {
+            KeyTtl::Seconds(remaining) => Ok(Some(remaining)),
+            KeyTtl::Missing | KeyTtl::Persistent => Ok(None),
+        }
+    }
+
+    async fn record_login_failure(
+        &self,
+        scope: &LoginScope,
+        window: u64,
+    ) -> Result<u64, RepoError> {
+        self.redis_client
+            .incr_ex(&LoginFailuresKey(scope), window)
+            .await
+    }
+
+    async fn lock_login(&self, scope: &LoginScope, ttl: u64) -> Result<(), RepoError> {
+        self.redis_client
+            .set_ex(&LoginLockKey(scope), &ttl, ttl)
+            .await
+    }
+
+    async fn clear_login_failures(&self, scope: &LoginScope) -> Result<(), RepoError> {
+        self.redis_client.delete(LoginFailuresKey(scope)).await?;
+        Ok(())
+    }
+
+    async fn create_api_token(&self, token: &ApiToken, token_hash: &str) -> Result<(), RepoError> {
+        let scopes: Vec<String> = token.scopes.iter().map(ToString::to_string).collect();
+        sqlx::query!(
+            "INSERT INTO api_tokens \
+             (id, user_id, name, prefix, token_hash, scopes, created_at, expires_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            token.id,
+            token.user_id,
+            token.name,
+            token.prefix,
+            token_hash,
+            &scopes,
+            token.created_at,
+            token.expires_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to create api token of user: {}, error: {}",
+                token.user_id, e
+            );
+            RepoError::Internal("Failed to create api token".to_string())
+        })?;
+        Ok(())
+    }
+
+    async fn list_api_tokens(&self, user_id: Uuid) -> Result<Vec<ApiToken>, RepoError> {
+        let rows = sqlx::query_as!(
+            ApiTokenRow,
+            "SELECT id, user_id, name, prefix, scopes, created_at, expires_at, last_used_at \
+             FROM api_tokens WHERE user_id = $1 ORDER BY created_at DESC, id DESC",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to get api tokens of user: {}, error: {}",
+                user_id, e
+            );
+            RepoError::Internal("Failed to get api tokens".to_string())
+        })?;
+
+        rows.into_iter().map(ApiToken::try_from).collect()
+    }
+
+    async fn delete_api_token(&self, user_id: Uuid, token_id: Uuid) -> Result<(), RepoError> {
+        let result = sqlx::query!(
+            "DELETE FROM api_tokens WHERE id = $1 AND user_id = $2",
+            token_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete api token: {}, error: {}", token_id, e);
+            RepoError::Internal("Failed to delete api token".to_string())
+        })?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound(format!(
+                "Api token with id: {} not found",
+                token_id
+            )));
+        }
+        Ok(())
+    }
+
+    async fn use_api_token(
+        &self,
+        token_hash: &str,
+        now: NaiveDateTime,
+    ) -> Result<Option<ApiToken>, RepoError> {
+        let row = sqlx::query_as!(
+            ApiTokenRow,
+            "UPDATE api_tokens SET last_used_at = $2 \
+             WHERE token_hash = $1 AND (expires_at IS NULL OR expires_at > $2) \
+             RETURNING id, user_id, name, prefix, scopes, created_at, expires_at, last_used_at",
+            token_hash,
+            now
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to use api token: {}", e);
+            RepoError::Internal("Failed to use api token".to_string())
+        })?;
+
+        row.map(ApiToken::try_from).transpose()
+    }
+}
+
+impl Repository {
+    // 系列の集合は、一番長く残るリフレッシュトークンに合わせて保持する
+    async fn add_to_family<T: RedisKey>(&self, family_id: Uuid, key: &T) -> Result<(), RepoError> {
+        self.redis_client
+            .add_member(
+                &TokenFamilyKey(family_id),
+                &key.inner(),
+                self.config.refresh_ttl,
+            )
+            .await
+    }
 }
 
 #[cfg(test)]
@@ -84,12 +416,11 @@ mod tests {
     use shared::config::RedisConfig;
 
     use super::*;
+    use crate::test_support::repository_with_redis;
     use anyhow::Result;
-    use aws_config::BehaviorVersion;
-    use aws_sdk_s3::Client;
-    use shared::config::Config;
     use sqlx::postgres::{PgPool, PgPoolOptions};
     use std::env;
+    use usecase::model::user::Permission;
     use uuid::Uuid;
 
     #[tokio::test]
@@ -98,7 +429,10 @@ mod tests {
 
         let current_user_id = Uuid::now_v7();
         let token = repo
-            .create_token(current_user_id, repo.config.token_ttl)
+            .create_token(
+                &SessionToken::new(current_user_id, Uuid::now_v7()),
+                repo.config.token_ttl,
+            )
             .await?;
 
         assert_eq!(current_user_id, token.id);
@@ -111,7 +445,10 @@ mod tests {
 
         let current_user_id = Uuid::now_v7();
         let token = repo
-            .create_token(current_user_id, repo.config.token_ttl)
+            .create_token(
+                &SessionToken::new(current_user_id, Uuid::now_v7()),
+                repo.config.token_ttl,
+            )
             .await?;
 
         let deleted_item_num = repo.delete_token(token).await?;
@@ -126,31 +463,222 @@ mod tests {
 
         let current_user_id = Uuid::now_v7();
         let token = repo
-            .create_token(current_user_id, repo.config.token_ttl)
+            .create_token(
+                &SessionToken::new(current_user_id, Uuid::now_v7()),
+                repo.config.token_ttl,
+            )
             .await?;
 
-        let user_id = repo.fetch_user_id_by_token(token.access_token).await;
+        let session = repo.fetch_session_by_token(token.access_token).await;
 
-        assert!(user_id.is_some());
-        assert_eq!(current_user_id, user_id.unwrap());
+        let session = session.unwrap();
+        assert_eq!(current_user_id, session.user_id);
+        assert_eq!(64, session.csrf_token.len());
         Ok(())
     }
 
-    async fn initialize_repository() -> Repository {
-        let repo = Repository::new(
-            initialize_db().await,
-            Client::new(&aws_config::load_defaults(BehaviorVersion::latest()).await),
-            initialize_redis().await,
-            Config {
-                host: "test".into(),
-                env: "dev".into(),
-                token_ttl: 300,
-                refresh_ttl: 900,
-            },
+    #[tokio::test]
+    async fn rotated_refresh_token_is_marked_and_family_is_revoked() -> Result<(), RepoError> {
+        let repo = initialize_repository().await;
+
+        let (user_id, family_id) = (Uuid::now_v7(), Uuid::now_v7());
+        let session = SessionToken::new(user_id, family_id);
+        let token = repo.create_token(&session, repo.config.token_ttl).await?;
+        let refresh_token = repo
+            .create_refresh_token(&session, repo.config.refresh_ttl)
+            .await?;
+
+        // リフレッシュトークンはセッションIDとして使えない
+        assert!(
+            repo.fetch_session_by_token(refresh_token.clone())
+                .await
+                .is_none()
         );
 
-        repo
+        let first = repo.rotate_refresh_token(&refresh_token).await?.unwrap();
+        let second = repo.rotate_refresh_token(&refresh_token).await?.unwrap();
+        assert!(!first.rotated);
+        assert!(second.rotated);
+        assert_eq!(family_id, second.family_id);
+        // ローテーションしてもCSRFトークンは変わらない
+        assert_eq!(session.csrf_token, second.csrf_token);
+
+        assert_eq!(3, repo.revoke_token_family(family_id).await?);
+        assert!(
+            repo.fetch_session_by_token(token.access_token)
+                .await
+                .is_none()
+        );
+        assert_eq!(None, repo.rotate_refresh_token(&refresh_token).await?);
+        Ok(())
     }
+
+    #[tokio::test]
+    async fn sessions_are_listed_and_revoked() -> Result<(), RepoError> {
+        let repo = initialize_repository().await;
+
+        let user_id = Uuid::now_v7();
+        let mut tokens = Vec::new();
+        for ip in ["192.0.2.1", "192.0.2.2"] {
+            let session = Session {
+                id: Uuid::now_v7(),
+                user_id,
+                user_agent: Some("test".to_string()),
+                ip: Some(ip.to_string()),
+                created_at: chrono::Utc::now().naive_utc(),
+            };
+            repo.create_session(&session, repo.config.refresh_ttl)
+                .await?;
+            let token = repo
+                .create_token(
+                    &SessionToken::new(user_id, session.id),
+                    repo.config.token_ttl,
+                )
+                .await?;
+            tokens.push((session.id, token));
+        }
+
+        let sessions = repo.list_sessions(user_id).await?;
+        assert_eq!(2, sessions.len());
+        assert_eq!(Some("192.0.2.2".to_string()), sessions[0].ip);
+
+        // 他人のセッションは消せない
+        assert!(
+            repo.revoke_session(Uuid::now_v7(), tokens[0].0)
+                .await
+                .is_err()
+        );
+        repo.revoke_session(user_id, tokens[0].0).await?;
+        assert_eq!(1, repo.list_sessions(user_id).await?.len());
+
+        assert_eq!(1, repo.revoke_all_sessions(user_id).await?);
+        assert!(repo.list_sessions(user_id).await?.is_empty());
+        assert!(
+            repo.fetch_session_by_token(tokens[1].1.access_token.clone())
+                .await
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn upserting_user_keeps_id_and_replaces_password() -> Result<(), RepoError> {
+        let repo = initialize_repository().await;
+
+        let name = format!("admin-{}", &Uuid::now_v7().simple().to_string()[..16]);
+        let user = User {
+            id: Uuid::now_v7(),
+            name: name.clone(),
+            password: "first".to_string(),
+            role: Role::Editor,
+        };
+        assert_eq!(user.id, repo.upsert_user(&user).await?);
+
+        let replaced = User {
+            id: Uuid::now_v7(),
+            password: "second".to_string(),
+            role: Role::Admin,
+            ..user.clone()
+        };
+        assert_eq!(user.id, repo.upsert_user(&replaced).await?);
+        let saved = repo.get_user_by_username(&name).await?;
+        assert_eq!("second", saved.password);
+        // 既存の利用者の役割は変えない
+        assert_eq!(Role::Editor, saved.role);
+
+        repo.update_role(&name, Role::Viewer).await?;
+        assert_eq!(Role::Viewer, repo.get_user(user.id).await?.role);
+        assert!(repo.update_role("no-such-user", Role::Admin).await.is_err());
+
+        repo.update_password(user.id, "third").await?;
+        assert_eq!("third", repo.get_user(user.id).await?.password);
+        assert!(repo.update_password(Uuid::now_v7(), "x").await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn api_tokens_are_used_until_expired_or_deleted() -> Result<(), RepoError> {
+        let repo = initialize_repository().await;
+
+        let user = User {
+            id: Uuid::now_v7(),
+            name: format!("ci-{}", &Uuid::now_v7().simple().to_string()[..16]),
+            password: "hash".to_string(),
+            role: Role::Editor,
+        };
+        repo.upsert_user(&user).await?;
+        let now = chrono::Utc::now().naive_utc();
+        let token = |expires_at| ApiToken {
+            id: Uuid::now_v7(),
+            user_id: user.id,
+            name: "ci".to_string(),
+            prefix: "mzc_0123".to_string(),
+            scopes: vec![Permission::WriteBlogs],
+            created_at: now,
+            expires_at,
+            last_used_at: None,
+        };
+        let active = token(None);
+        let expired = token(Some(now - chrono::Duration::days(1)));
+        // 共有のDBに前回の行が残っていても衝突しないよう、ハッシュは毎回変える
+        let active_hash = ApiToken::hash_secret(&ApiToken::generate_secret());
+        let expired_hash = ApiToken::hash_secret(&ApiToken::generate_secret());
+        repo.create_api_token(&active, &active_hash).await?;
+        repo.create_api_token(&expired, &expired_hash).await?;
+
+        let used = repo.use_api_token(&active_hash, now).await?.unwrap();
+        assert_eq!(active.id, used.id);
+        assert_eq!(vec![Permission::WriteBlogs], used.scopes);
+        assert!(used.last_used_at.is_some());
+        assert_eq!(None, repo.use_api_token(&expired_hash, now).await?);
+        assert_eq!(2, repo.list_api_tokens(user.id).await?.len());
+
+        assert!(
+            repo.delete_api_token(Uuid::now_v7(), active.id)
+                .await
+                .is_err()
+        );
+        repo.delete_api_token(user.id, active.id).await?;
+        assert_eq!(None, repo.use_api_token(&active_hash, now).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn login_failures_are_counted_and_locked() -> Result<(), RepoError> {
+        let repo = initialize_repository().await;
+
+        let name = format!("user-{}", Uuid::now_v7());
+        let scope = LoginScope::Account {
+            username: name.clone(),
+            ip: Some("192.0.2.3".to_string()),
+        };
+        assert_eq!(1, repo.record_login_failure(&scope, 60).await?);
+        assert_eq!(2, repo.record_login_failure(&scope, 60).await?);
+        assert_eq!(None, repo.login_lockout(&scope).await?);
+
+        repo.lock_login(&scope, 30).await?;
+        let remaining = repo.login_lockout(&scope).await?.unwrap();
+        assert!(0 < remaining && remaining <= 30);
+        // 同じ名前でもIPとは別に数え、他のIPからのログインはロックしない
+        assert_eq!(
+            None,
+            repo.login_lockout(&LoginScope::Ip(name.clone())).await?
+        );
+        let other_ip = LoginScope::Account {
+            username: name,
+            ip: Some("192.0.2.4".to_string()),
+        };
+        assert_eq!(None, repo.login_lockout(&other_ip).await?);
+
+        repo.clear_login_failures(&scope).await?;
+        assert_eq!(1, repo.record_login_failure(&scope, 60).await?);
+        Ok(())
+    }
+
+    async fn initialize_repository() -> Repository {
+        repository_with_redis(initialize_db().await, initialize_redis().await)
+    }
+
     async fn initialize_db() -> PgPool {
         let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
         let pool = PgPoolOptions::new()
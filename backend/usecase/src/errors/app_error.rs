@@ -1,12 +1,20 @@
 use super::repo_error::RepoError;
+use crate::service::blog::image_processing::ImageError;
+use crate::service::blog::markdown::MarkdownError;
+use maze_core::MazeError;
 use std::fmt;
 
+#[derive(Debug)]
 pub enum ErrorStatus {
     NotFound,
     AlreadyExist,
     InternalError,
     Unauthorized,
+    // ログインしているが、その操作をする権限がない
+    Forbidden,
     Invalid,
+    // 試行が多すぎる。retry_after秒たてば受け付ける
+    TooManyRequests { retry_after: u64 },
 }
 
 impl fmt::Display for ErrorStatus {
@@ -16,11 +24,16 @@ impl fmt::Display for ErrorStatus {
             ErrorStatus::AlreadyExist => write!(f, "Already Exist"),
             ErrorStatus::InternalError => write!(f, "Internal Error"),
             ErrorStatus::Unauthorized => write!(f, "Unauthorized"),
+            ErrorStatus::Forbidden => write!(f, "Forbidden"),
             ErrorStatus::Invalid => write!(f, "Invalid"),
+            ErrorStatus::TooManyRequests { retry_after } => {
+                write!(f, "Too Many Requests (retry after {}s)", retry_after)
+            }
         }
     }
 }
 
+#[derive(Debug)]
 pub struct AppError {
     pub status: ErrorStatus,
     pub message: String,
@@ -66,6 +79,19 @@ impl AppError {
         }
     }
 
+    pub fn forbidden(message: Option<&str>) -> Self {
+        match message {
+            Some(msg) => AppError {
+                status: ErrorStatus::Forbidden,
+                message: msg.into(),
+            },
+            None => AppError {
+                status: ErrorStatus::Forbidden,
+                message: "Forbidden".into(),
+            },
+        }
+    }
+
     pub fn already_exist(message: Option<&str>) -> Self {
         match message {
             Some(msg) => AppError {
@@ -91,6 +117,13 @@ impl AppError {
             },
         }
     }
+
+    pub fn too_many_requests(message: Option<&str>, retry_after: u64) -> Self {
+        AppError {
+            status: ErrorStatus::TooManyRequests { retry_after },
+            message: message.unwrap_or("Too many requests").into(),
+        }
+    }
 }
 
 impl fmt::Display for AppError {
@@ -108,3 +141,27 @@ impl From<RepoError> for AppError {
         }
     }
 }
+
+impl From<MazeError> for AppError {
+    fn from(error: MazeError) -> Self {
+        match error {
+            MazeError::Encode(_) => AppError::internal(Some(&error.to_string())),
+            _ => AppError::invalid(Some(&error.to_string())),
+        }
+    }
+}
+
+impl From<MarkdownError> for AppError {
+    fn from(error: MarkdownError) -> Self {
+        AppError::invalid(Some(&error.to_string()))
+    }
+}
+
+impl From<ImageError> for AppError {
+    fn from(error: ImageError) -> Self {
+        match error {
+            ImageError::Encode(_) => AppError::internal(Some(&error.to_string())),
+            _ => AppError::invalid(Some(&error.to_string())),
+        }
+    }
+}
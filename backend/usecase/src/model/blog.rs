@@ -1,9 +1,17 @@
 use std::fmt;
+use std::str::FromStr;
 
-use chrono::{Months, NaiveDateTime};
+use chrono::{DateTime, Months, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::errors::app_error::AppError;
+use crate::model::user::{Permission, User};
+
+pub const DEFAULT_BLOG_PAGE_SIZE: i64 = 20;
+pub const MAX_BLOG_TITLE_LENGTH: usize = 30;
+pub const MAX_BLOG_PAGE_SIZE: i64 = 100;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum BlogStatus {
@@ -20,53 +28,313 @@ impl fmt::Display for BlogStatus {
     }
 }
 
+impl FromStr for BlogStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DRAFT" => Ok(BlogStatus::Draft),
+            "PUBLISHED" => Ok(BlogStatus::Published),
+            _ => Err(format!("unknown blog status: {s}")),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Blog {
     pub id: Uuid,
     pub title: String,
+    pub slug: String,
+    // 本文を保存しているオブジェクトのキー。常にBlog::draft_keyと同じで、スラッグには依存しない
     pub content_key: String,
     pub status: BlogStatus,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub published_at: Option<NaiveDateTime>,
+    // 作成者。作成者を記録する前からある記事ではNone
+    pub author_id: Option<Uuid>,
+}
+
+impl Blog {
+    // 編集者は自分の記事だけ、管理者は全ての記事を変更できる
+    pub fn is_editable_by(&self, user: &User) -> bool {
+        user.role.permits(Permission::ManageAllBlogs)
+            || (user.role.permits(Permission::WriteBlogs) && self.author_id == Some(user.id))
+    }
+
+    // 本文はR2のこのキーに保存する
+    pub fn draft_key(id: &Uuid) -> String {
+        format!("uploads/drafts/{}", id)
+    }
+
+    // 一覧の並び順と絞り込みに使う日時。公開日時がなければ作成日時を使う
+    pub fn listed_at(&self) -> NaiveDateTime {
+        self.published_at.unwrap_or(self.created_at)
+    }
+}
+
+// 記事本体をR2から読み込んだ記事
+#[derive(Debug, Clone)]
+pub struct BlogDetail {
+    pub blog: Blog,
+    pub content: String,
+    pub rendered: RenderedContent,
+}
+
+// 目次の1項目。idは本文中の見出しのアンカー
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TocEntry {
+    pub level: u8,
+    pub id: String,
+    pub title: String,
+}
+
+// 本文のMarkdownを変換した公開用のHTMLと目次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedContent {
+    pub html: String,
+    pub toc: Vec<TocEntry>,
+}
+
+// 指定された項目だけを更新する
+#[derive(Debug, Clone, Default)]
+pub struct BlogPatch {
+    pub title: Option<String>,
+    pub slug: Option<String>,
+    pub content: Option<String>,
+}
+
+// スラッグで記事を探した結果。旧スラッグの場合は現在のスラッグを返す
+#[derive(Debug, Clone)]
+pub enum SlugLookup<T> {
+    Found(T),
+    Redirect(String),
 }
 
 #[derive(Debug, Clone)]
 pub struct BlogRequest {
     pub title: String,
+    // 指定がなければタイトルから作る
+    pub slug: Option<String>,
     pub content: String,
 }
 
+// 一覧の続きを取得するためのカーソル。直前のページの最後の記事を指す
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlogCursor {
+    pub listed_at: NaiveDateTime,
+    pub id: Uuid,
+}
+
+impl BlogCursor {
+    pub fn encode(&self) -> String {
+        format!(
+            "{}_{}",
+            self.listed_at.and_utc().timestamp_micros(),
+            self.id.simple()
+        )
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, AppError> {
+        let invalid = || AppError::invalid(Some("cursor is malformed"));
+        let (micros, id) = cursor.split_once('_').ok_or_else(invalid)?;
+        let listed_at = micros
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(invalid)?
+            .naive_utc();
+        let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+        Ok(Self { listed_at, id })
+    }
+}
+
+impl From<&Blog> for BlogCursor {
+    fn from(blog: &Blog) -> Self {
+        Self {
+            listed_at: blog.listed_at(),
+            id: blog.id,
+        }
+    }
+}
+
+pub struct BlogPage {
+    pub blogs: Vec<Blog>,
+    pub next_cursor: Option<String>,
+}
+
 pub struct BlogFilter {
     pub start: Option<NaiveDateTime>,
     pub end: Option<NaiveDateTime>,
+    pub cursor: Option<BlogCursor>,
+    pub limit: i64,
 }
 
 impl BlogFilter {
-    pub fn new(year: Option<&String>, month: Option<&String>) -> Self {
-        let (start, end) = converter_string_to_datetime(year, month);
-        Self { start, end }
+    pub fn new(year: Option<&String>, month: Option<&String>) -> Result<Self, AppError> {
+        let (start, end) = converter_string_to_datetime(year, month)?;
+        Ok(Self {
+            start,
+            end,
+            cursor: None,
+            limit: DEFAULT_BLOG_PAGE_SIZE,
+        })
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<&String>) -> Result<Self, AppError> {
+        self.cursor = cursor.map(|c| BlogCursor::decode(c)).transpose()?;
+        Ok(self)
+    }
+
+    pub fn with_limit(mut self, limit: Option<&String>) -> Result<Self, AppError> {
+        if let Some(limit) = limit {
+            self.limit = limit
+                .parse::<i64>()
+                .ok()
+                .filter(|l| (1..=MAX_BLOG_PAGE_SIZE).contains(l))
+                .ok_or_else(|| {
+                    AppError::invalid(Some(&format!(
+                        "limit must be between 1 and {MAX_BLOG_PAGE_SIZE}"
+                    )))
+                })?;
+        }
+        Ok(self)
     }
 }
 
 fn converter_string_to_datetime(
     year: Option<&String>,
     month: Option<&String>,
-) -> (Option<NaiveDateTime>, Option<NaiveDateTime>) {
-    if year.is_none() {
-        return (None, None);
-    }
-
-    let y = year.unwrap().parse::<i32>().unwrap();
-    let start = NaiveDateTime::new(
-        chrono::NaiveDate::from_ymd_opt(y, 1, 1).unwrap(),
-        chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
-    );
-
-    if let Some(m) = month {
-        let month_num = m.parse::<u32>().unwrap();
-        return (
-            start.checked_add_months(Months::new(month_num - 1)),
-            start.checked_add_months(Months::new(month_num)),
+) -> Result<(Option<NaiveDateTime>, Option<NaiveDateTime>), AppError> {
+    let Some(year) = year else {
+        if month.is_some() {
+            return Err(AppError::invalid(Some(
+                "month cannot be specified without year",
+            )));
+        }
+        return Ok((None, None));
+    };
+
+    let start = year
+        .parse::<i32>()
+        .ok()
+        .filter(|y| (1..=9999).contains(y))
+        .and_then(|y| NaiveDate::from_ymd_opt(y, 1, 1))
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .ok_or_else(|| AppError::invalid(Some("year must be between 1 and 9999")))?;
+
+    if let Some(month) = month {
+        let month = month
+            .parse::<u32>()
+            .ok()
+            .filter(|m| (1..=12).contains(m))
+            .ok_or_else(|| AppError::invalid(Some("month must be between 1 and 12")))?;
+        return Ok((
+            start.checked_add_months(Months::new(month - 1)),
+            start.checked_add_months(Months::new(month)),
+        ));
+    }
+
+    Ok((Some(start), start.checked_add_months(Months::new(12))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::user::Role;
+
+    fn filter(year: Option<&str>, month: Option<&str>) -> Result<BlogFilter, AppError> {
+        BlogFilter::new(
+            year.map(String::from).as_ref(),
+            month.map(String::from).as_ref(),
+        )
+    }
+
+    #[test]
+    fn filter_by_month() {
+        let filter = filter(Some("2026"), Some("12")).ok().unwrap();
+
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2026, 12, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0),
+            filter.start
+        );
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2027, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0),
+            filter.end
         );
     }
 
-    (Some(start), start.checked_add_months(Months::new(12)))
+    #[test]
+    fn malformed_year_or_month_is_invalid() {
+        for (year, month) in [
+            (Some("20x6"), None),
+            (Some("0"), None),
+            (Some("2026"), Some("13")),
+            (Some("2026"), Some("0")),
+            (None, Some("1")),
+        ] {
+            assert!(matches!(
+                filter(year, month),
+                Err(AppError {
+                    status: crate::errors::app_error::ErrorStatus::Invalid,
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn editors_can_edit_only_their_own_blogs() {
+        let user = |role| User {
+            id: Uuid::now_v7(),
+            name: "user".to_string(),
+            password: String::new(),
+            role,
+        };
+        let (admin, editor, viewer) = (user(Role::Admin), user(Role::Editor), user(Role::Viewer));
+        let now = NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let blog = |author_id| Blog {
+            id: Uuid::now_v7(),
+            title: "title".to_string(),
+            slug: "title".to_string(),
+            content_key: "key".to_string(),
+            status: BlogStatus::Draft,
+            created_at: now,
+            updated_at: now,
+            published_at: None,
+            author_id,
+        };
+
+        let own = blog(Some(editor.id));
+        assert!(own.is_editable_by(&editor));
+        assert!(own.is_editable_by(&admin));
+        assert!(!blog(Some(admin.id)).is_editable_by(&editor));
+        // 作成者のいない古い記事は管理者だけが変更できる
+        assert!(!blog(None).is_editable_by(&editor));
+        assert!(blog(None).is_editable_by(&admin));
+
+        let viewers_own = blog(Some(viewer.id));
+        assert!(!viewers_own.is_editable_by(&viewer));
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = BlogCursor {
+            listed_at: NaiveDate::from_ymd_opt(2026, 3, 1)
+                .unwrap()
+                .and_hms_micro_opt(12, 34, 56, 789)
+                .unwrap(),
+            id: Uuid::now_v7(),
+        };
+
+        assert_eq!(cursor, BlogCursor::decode(&cursor.encode()).ok().unwrap());
+        assert!(BlogCursor::decode("not-a-cursor").is_err());
+    }
 }
@@ -1,7 +1,181 @@
 use serde::{Deserialize, Serialize};
 
+// アップロードできる画像の最大バイト数
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+// 元画像とは別に保存する縮小版。名前と、縦横それぞれの最大ピクセル数
+pub const IMAGE_VARIANTS: [(&str, u32); 2] = [("thumbnail", 320), ("medium", 1280)];
+
+// アップロードを受け付ける画像の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    WebP,
+    Gif,
+}
+
+impl ImageFormat {
+    // 拡張子や申告されたContent-Typeではなく、先頭のマジックバイトで形式を判定する
+    pub fn sniff(data: &[u8]) -> Option<Self> {
+        match data {
+            [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, ..] => Some(ImageFormat::Png),
+            [0xff, 0xd8, 0xff, ..] => Some(ImageFormat::Jpeg),
+            [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some(ImageFormat::Gif),
+            [
+                b'R',
+                b'I',
+                b'F',
+                b'F',
+                _,
+                _,
+                _,
+                _,
+                b'W',
+                b'E',
+                b'B',
+                b'P',
+                ..,
+            ] => Some(ImageFormat::WebP),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::WebP => "image/webp",
+            ImageFormat::Gif => "image/gif",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Image {
     pub id: String,
     pub url: String,
+    pub width: u32,
+    pub height: u32,
+    pub mime_type: String,
+    pub variants: Vec<ImageVariant>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageVariant {
+    pub name: String,
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+    pub mime_type: String,
+}
+
+impl Image {
+    // 元画像を保存するキー
+    pub fn object_key(id: &str) -> String {
+        format!("_uploads/{id}")
+    }
+
+    // 縮小版を保存するキー。idの後ろに続けるので、referenced_idsは元画像と同じidを返す。
+    // ローカルのファイルシステムでは元画像と同じ名前のディレクトリを作れないので"/"では区切らない
+    pub fn variant_key(id: &str, variant: &str) -> String {
+        format!("_uploads/{id}_{variant}")
+    }
+
+    // /_uploads/{name}のnameから保存したキーを作る。アップロードで作る名前の形でなければNone
+    pub fn key_from_name(name: &str) -> Option<String> {
+        let (id, variant) = match name.split_once('_') {
+            Some((id, variant)) => (id, Some(variant)),
+            None => (name, None),
+        };
+        let valid_id = id.len() == 32
+            && id
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+        if !valid_id {
+            return None;
+        }
+        match variant {
+            None => Some(Self::object_key(id)),
+            Some(variant) if IMAGE_VARIANTS.iter().any(|(name, _)| *name == variant) => {
+                Some(Self::variant_key(id, variant))
+            }
+            Some(_) => None,
+        }
+    }
+
+    // 本文中の画像URL(.../_uploads/{id})から、アップロードした画像のidを取り出す
+    pub fn referenced_ids(content: &str) -> Vec<String> {
+        let mut ids: Vec<String> = Vec::new();
+        for (idx, _) in content.match_indices("/_uploads/") {
+            let id: String = content[idx + "/_uploads/".len()..]
+                .chars()
+                .take_while(|c| c.is_ascii_hexdigit())
+                .collect();
+            if id.len() == 32 && !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn referenced_ids_are_unique_uploaded_images() {
+        let id = "0199f1a2b3c47d8e9f00112233445566";
+        let content = format!(
+            "![a](https://example.com/_uploads/{id})\n![b](https://example.com/_uploads/{id})\n\
+             ![c](https://example.com/_uploads/short)\n![d](https://example.com/images/{id})"
+        );
+
+        assert_eq!(vec![id.to_string()], Image::referenced_ids(&content));
+    }
+
+    #[test]
+    fn only_uploaded_names_are_keys() {
+        let id = "0199f1a2b3c47d8e9f00112233445566";
+
+        assert_eq!(Some(format!("_uploads/{id}")), Image::key_from_name(id));
+        assert_eq!(
+            Some(format!("_uploads/{id}_thumbnail")),
+            Image::key_from_name(&format!("{id}_thumbnail"))
+        );
+        assert_eq!(None, Image::key_from_name(&format!("{id}_huge")));
+        assert_eq!(None, Image::key_from_name(&id.to_uppercase()));
+        assert_eq!(
+            None,
+            Image::key_from_name("../drafts/0199f1a2b3c47d8e9f0011223344")
+        );
+    }
+
+    #[test]
+    fn sniff_allowed_formats() {
+        assert_eq!(
+            Some(ImageFormat::Png),
+            ImageFormat::sniff(b"\x89PNG\r\n\x1a\n....")
+        );
+        assert_eq!(
+            Some(ImageFormat::Jpeg),
+            ImageFormat::sniff(&[0xff, 0xd8, 0xff, 0xe0])
+        );
+        assert_eq!(Some(ImageFormat::Gif), ImageFormat::sniff(b"GIF89a...."));
+        assert_eq!(
+            Some(ImageFormat::WebP),
+            ImageFormat::sniff(b"RIFF\x10\0\0\0WEBPVP8L")
+        );
+    }
+
+    #[test]
+    fn sniff_rejects_other_formats() {
+        assert_eq!(
+            None,
+            ImageFormat::sniff(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>")
+        );
+        assert_eq!(None, ImageFormat::sniff(b"RIFF\x10\0\0\0WAVEfmt "));
+        assert_eq!(None, ImageFormat::sniff(b"\x89PN"));
+    }
 }
@@ -0,0 +1,55 @@
+use chrono::NaiveDateTime;
+use maze_core::{Maze, MazeType};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MazeFormat {
+    Json,
+    Svg,
+    Png,
+}
+
+#[derive(Debug, Clone)]
+pub struct MazeRequest {
+    pub rows: usize,
+    pub cols: usize,
+    pub maze_type: Option<String>,
+    pub seed: Option<u64>,
+    pub format: MazeFormat,
+}
+
+#[derive(Debug, Clone)]
+pub enum MazeBody {
+    Json(Maze),
+    Svg(String),
+    Png(Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneratedMaze {
+    pub seed: u64,
+    pub maze_type: MazeType,
+    pub body: MazeBody,
+}
+
+#[derive(Debug, Clone)]
+pub struct SaveMazeRequest {
+    pub title: Option<String>,
+    pub rows: usize,
+    pub cols: usize,
+    pub maze_type: Option<String>,
+    pub seed: Option<u64>,
+}
+
+// 保存された迷路。迷路自体はseedとアルゴリズムから再生成できるので、壁は保存しない
+#[derive(Debug, Clone)]
+pub struct SavedMaze {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub title: Option<String>,
+    pub maze_type: MazeType,
+    pub seed: u64,
+    pub rows: usize,
+    pub cols: usize,
+    pub created_at: NaiveDateTime,
+}
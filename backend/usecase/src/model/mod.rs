@@ -1,3 +1,5 @@
 pub mod blog;
 pub mod image;
+pub mod maze;
+pub mod slug;
 pub mod user;
@@ -0,0 +1,82 @@
+// 記事のURLに使うスラッグ
+//
+// 日本語のタイトルも読めるURLにしたいので、英数字(ASCII以外の文字を含む)はそのまま残し、
+// それ以外の文字は'-'に置き換える。URLに埋め込むときはencodeでパーセントエンコードする
+
+pub const MAX_SLUG_LENGTH: usize = 80;
+
+// 入力を小文字化し、英数字以外を'-'にまとめたスラッグを返す。使える文字がなければ空文字列になる
+pub fn slugify(input: &str) -> String {
+    let mut slug = String::new();
+    let mut pending_hyphen = false;
+    for c in input.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            if pending_hyphen && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_hyphen = false;
+            slug.push(c);
+        } else {
+            pending_hyphen = true;
+        }
+    }
+
+    if slug.chars().count() > MAX_SLUG_LENGTH {
+        slug = slug.chars().take(MAX_SLUG_LENGTH).collect();
+        slug.truncate(slug.trim_end_matches('-').len());
+    }
+    slug
+}
+
+// 重複した場合に付ける連番。1番目は元のスラッグのまま
+pub fn with_suffix(slug: &str, n: usize) -> String {
+    if n <= 1 {
+        slug.to_string()
+    } else {
+        format!("{slug}-{n}")
+    }
+}
+
+// URLのパスの1要素として使えるようにパーセントエンコードする
+pub fn encode(slug: &str) -> String {
+    let mut encoded = String::with_capacity(slug.len());
+    for byte in slug.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugify_ascii_title() {
+        assert_eq!("hello-world-2026", slugify("  Hello, World! / 2026 "));
+        assert_eq!("", slugify("!!! ///"));
+    }
+
+    #[test]
+    fn slugify_keeps_japanese() {
+        assert_eq!("迷路の作り方-part-1", slugify("迷路の作り方 (Part 1)"));
+    }
+
+    #[test]
+    fn slugify_truncates_long_title() {
+        let slug = slugify(&"a-".repeat(100));
+
+        assert!(slug.chars().count() <= MAX_SLUG_LENGTH);
+        assert!(!slug.ends_with('-'));
+    }
+
+    #[test]
+    fn encode_non_ascii() {
+        assert_eq!("%E8%BF%B7%E8%B7%AF-1", encode("迷路-1"));
+        assert_eq!("maze-2", encode(&with_suffix("maze", 2)));
+    }
+}
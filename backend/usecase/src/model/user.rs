@@ -1,10 +1,99 @@
+use std::fmt;
+use std::str::FromStr;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::NaiveDateTime;
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
+
+// CSRFトークンのバイト数。16進の文字列にすると倍の長さになる
+const CSRF_TOKEN_BYTES: usize = 32;
+// APIトークンの平文の形式。先頭で何のトークンか分かるようにする
+pub const API_TOKEN_PREFIX: &str = "mzc_";
+const API_TOKEN_BYTES: usize = 32;
+// 一覧に表示する平文の先頭の文字数
+const API_TOKEN_DISPLAY_CHARS: usize = 12;
+
 #[derive(Clone)]
 pub struct User {
     pub id: Uuid,
     pub name: String,
+    // ソルトとパラメータを含むPHC文字列
     pub password: String,
-    pub salt: String,
+    pub role: Role,
+}
+
+// 利用者の役割。できることはpermitsで決まる
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    // 全ての記事を編集できる
+    Admin,
+    // 自分の記事だけを編集できる
+    Editor,
+    // 記事は読むだけ。迷路の保存などはできる
+    Viewer,
+}
+
+// ルートや操作ごとに必要な権限
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    // 記事を書き、画像をアップロードする
+    WriteBlogs,
+    // 他人の記事や作成者のいない記事も変更する
+    ManageAllBlogs,
+}
+
+impl Role {
+    pub fn permits(&self, permission: Permission) -> bool {
+        match permission {
+            Permission::WriteBlogs => matches!(self, Role::Admin | Role::Editor),
+            Permission::ManageAllBlogs => matches!(self, Role::Admin),
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Permission::WriteBlogs => write!(f, "blogs:write"),
+            Permission::ManageAllBlogs => write!(f, "blogs:manage"),
+        }
+    }
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blogs:write" => Ok(Permission::WriteBlogs),
+            "blogs:manage" => Ok(Permission::ManageAllBlogs),
+            _ => Err(format!("unknown scope: {s}")),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Admin => write!(f, "admin"),
+            Role::Editor => write!(f, "editor"),
+            Role::Viewer => write!(f, "viewer"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "admin" => Ok(Role::Admin),
+            "editor" => Ok(Role::Editor),
+            "viewer" => Ok(Role::Viewer),
+            _ => Err(format!("unknown role: {s}")),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -21,3 +110,202 @@ impl Token {
         }
     }
 }
+
+// リフレッシュトークンに紐づく情報。同じログインから続くトークンは同じfamily_idを持つ
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefreshToken {
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    // ローテーションしても同じCSRFトークンを引き継ぐ
+    pub csrf_token: String,
+    // ローテーション済みならtrue。もう一度使われたら漏れたとみなす
+    pub rotated: bool,
+}
+
+// ログイン1回分のセッション。idはトークンの系列(family_id)と同じ
+#[derive(Clone, Debug)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+// ログインしてきた端末の情報
+#[derive(Clone, Debug, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+// アクセストークンから分かる利用者とセッション
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionToken {
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    // 状態を変えるリクエストでは、Cookieとは別にヘッダーで送らせて比べる
+    pub csrf_token: String,
+}
+
+impl SessionToken {
+    // ログインのたびに、新しいCSRFトークンを作る
+    pub fn new(user_id: Uuid, session_id: Uuid) -> Self {
+        let mut bytes = [0u8; CSRF_TOKEN_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        Self {
+            user_id,
+            session_id,
+            csrf_token: hex::encode(bytes),
+        }
+    }
+
+    // 何文字目まで一致したかが応答までの時間から分からないよう、全ての文字を比べる
+    pub fn verify_csrf_token(&self, token: &str) -> bool {
+        csrf_tokens_match(&self.csrf_token, token)
+    }
+}
+
+// CSRFトークンを比べる。空のトークンは一致とみなさない
+pub fn csrf_tokens_match(expected: &str, actual: &str) -> bool {
+    let expected = expected.as_bytes();
+    let actual = actual.as_bytes();
+    if expected.is_empty() || expected.len() != actual.len() {
+        return false;
+    }
+    expected
+        .iter()
+        .zip(actual)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+// ログインとリフレッシュで発行するトークン
+pub struct IssuedTokens {
+    pub token: Token,
+    pub refresh_token: String,
+    pub csrf_token: String,
+}
+
+// 個人用のAPIトークン。ブラウザを使わないクライアントがAuthorization: Bearerで送る
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    // 平文の先頭。一覧でトークンを見分けるためのもので、認証には使わない
+    pub prefix: String,
+    // このトークンでできること。利用者の役割が許す範囲に限られる
+    pub scopes: Vec<Permission>,
+    pub created_at: NaiveDateTime,
+    // Noneなら期限なし
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+impl ApiToken {
+    // 平文のトークンを作る。保存するのはhash_secretしたものだけにする
+    pub fn generate_secret() -> String {
+        let mut bytes = [0u8; API_TOKEN_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        format!("{API_TOKEN_PREFIX}{}", hex::encode(bytes))
+    }
+
+    // 推測できない長さの乱数なので、パスワードと違いソルトや遅いハッシュは使わず、引くときにそのまま比べる
+    pub fn hash_secret(secret: &str) -> String {
+        hex::encode(Sha256::digest(secret.as_bytes()))
+    }
+
+    pub fn display_prefix(secret: &str) -> String {
+        secret.chars().take(API_TOKEN_DISPLAY_CHARS).collect()
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        self.scopes.contains(&permission)
+    }
+}
+
+// 発行したトークン。平文を返すのはこのときだけ
+pub struct NewApiToken {
+    pub token: ApiToken,
+    pub secret: String,
+}
+
+pub struct ApiTokenRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    // 省略すると期限なし
+    pub expires_in_days: Option<u32>,
+}
+
+// ログインの失敗を数える単位。同じIPからの試行と、同じIPから同じ利用者名への試行を別々に数える
+//
+// 利用者名だけで数えると、誰でも他人(管理者も)をロックできてしまうので、利用者名はIPと組にする
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoginScope {
+    Ip(String),
+    // IPが分からないときは、IPの分からない試行どうしで数える
+    Account {
+        username: String,
+        ip: Option<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_token_secrets_are_prefixed_and_hashed() {
+        let secret = ApiToken::generate_secret();
+
+        assert!(secret.starts_with(API_TOKEN_PREFIX));
+        assert_eq!(API_TOKEN_PREFIX.len() + API_TOKEN_BYTES * 2, secret.len());
+        assert_ne!(secret, ApiToken::generate_secret());
+        assert_eq!(64, ApiToken::hash_secret(&secret).len());
+        assert_eq!(
+            ApiToken::hash_secret(&secret),
+            ApiToken::hash_secret(&secret)
+        );
+        assert_eq!(
+            &secret[..API_TOKEN_DISPLAY_CHARS],
+            ApiToken::display_prefix(&secret)
+        );
+    }
+
+    #[test]
+    fn scopes_round_trip() {
+        for permission in [Permission::WriteBlogs, Permission::ManageAllBlogs] {
+            assert_eq!(Ok(permission), permission.to_string().parse());
+        }
+        assert!("blogs:delete".parse::<Permission>().is_err());
+    }
+
+    #[test]
+    fn csrf_tokens_are_random_and_compared_exactly() {
+        let session = SessionToken::new(Uuid::now_v7(), Uuid::now_v7());
+
+        assert_eq!(CSRF_TOKEN_BYTES * 2, session.csrf_token.len());
+        assert_ne!(
+            session.csrf_token,
+            SessionToken::new(session.user_id, session.session_id).csrf_token
+        );
+        assert!(session.verify_csrf_token(&session.csrf_token.clone()));
+        assert!(!session.verify_csrf_token(""));
+        assert!(!session.verify_csrf_token(&session.csrf_token[1..]));
+        assert!(!session.verify_csrf_token(&session.csrf_token.to_uppercase()));
+    }
+
+    #[test]
+    fn roles_round_trip_and_grant_permissions() {
+        for role in [Role::Admin, Role::Editor, Role::Viewer] {
+            assert_eq!(Ok(role), role.to_string().parse());
+        }
+        assert!("owner".parse::<Role>().is_err());
+
+        assert!(Role::Admin.permits(Permission::ManageAllBlogs));
+        assert!(Role::Editor.permits(Permission::WriteBlogs));
+        assert!(!Role::Editor.permits(Permission::ManageAllBlogs));
+        assert!(!Role::Viewer.permits(Permission::WriteBlogs));
+    }
+}
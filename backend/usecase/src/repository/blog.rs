@@ -1,17 +1,93 @@
 use bytes::Bytes;
 
-use crate::model::image::Image;
-
 use super::super::errors::repo_error::RepoError;
 use super::super::model::blog::*;
 use super::types::Transaction;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
 
 #[async_trait]
 pub trait BlogRepository: Send + Sync {
-    async fn get_blogs(&self, filter: BlogFilter) -> Vec<Blog>;
-    async fn create_draft(&self, tx: &mut Transaction<'_>) -> Result<String, RepoError>;
+    // 次のページの有無を判定できるよう、最大でfilter.limit + 1件を返す
+    async fn get_blogs(&self, filter: BlogFilter) -> Result<Vec<Blog>, RepoError>;
+    async fn get_blog(&self, blog_id: Uuid) -> Result<Blog, RepoError>;
+    async fn get_blog_by_slug(&self, slug: &str) -> Result<SlugLookup<Blog>, RepoError>;
+    // blog_id以外の記事が使っている、または転送元として記録されているスラッグか
+    async fn is_slug_taken(
+        &self,
+        tx: &mut Transaction<'_>,
+        slug: &str,
+        blog_id: Uuid,
+    ) -> Result<bool, RepoError>;
+    // スラッグを変更し、旧スラッグからの転送を記録する
+    async fn rename_blog_slug(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog_id: Uuid,
+        old_slug: &str,
+        new_slug: &str,
+    ) -> Result<(), RepoError>;
+    async fn get_blog_content(&self, blog_id: Uuid) -> Result<String, RepoError>;
+    // 変換済みの本文。まだ変換していない記事ではNoneを返す
+    async fn get_blog_rendered(&self, blog_id: Uuid) -> Result<Option<RenderedContent>, RepoError>;
+    // 更新中に他の更新が割り込まないよう、行をロックして取得する
+    async fn lock_blog(&self, tx: &mut Transaction<'_>, blog_id: Uuid) -> Result<Blog, RepoError>;
+    // 完全に削除する前に使う。ゴミ箱に入れた記事もロックする
+    async fn lock_blog_with_deleted(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog_id: Uuid,
+    ) -> Result<Blog, RepoError>;
+    async fn create_draft(&self, tx: &mut Transaction<'_>, blog: Blog) -> Result<Blog, RepoError>;
+    async fn update_blog_title(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog_id: Uuid,
+        title: &str,
+        updated_at: NaiveDateTime,
+    ) -> Result<Blog, RepoError>;
+    async fn update_blog_status(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog_id: Uuid,
+        status: BlogStatus,
+        published_at: Option<NaiveDateTime>,
+        updated_at: NaiveDateTime,
+    ) -> Result<Blog, RepoError>;
     async fn create_blog(&self, tx: &mut Transaction<'_>, blog: Blog) -> Result<Blog, RepoError>;
-    async fn upload_image(&self, blog_id: String, image_data: Bytes) -> Result<Image, RepoError>;
-    async fn upload_blog_draft(&self, blog_id: String, content: String) -> Result<(), RepoError>;
+    // 論理削除。削除済みの記事は取得や更新の対象外になる。削除した記事を返す
+    async fn soft_delete_blog(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog_id: Uuid,
+        deleted_at: NaiveDateTime,
+    ) -> Result<Blog, RepoError>;
+    // 論理削除済みの記事も含めて行を削除する。削除した記事を返す
+    async fn purge_blog(&self, tx: &mut Transaction<'_>, blog_id: Uuid) -> Result<Blog, RepoError>;
+    async fn delete_blog_content(&self, blog_id: Uuid) -> Result<(), RepoError>;
+    // 画像と、その持ち主の記録を削除する
+    async fn delete_images(&self, image_ids: &[String]) -> Result<(), RepoError>;
+    async fn record_image(&self, image_id: &str, owner_id: Uuid) -> Result<(), RepoError>;
+    // image_idsのうち、owner_idがアップロードした画像のid
+    async fn images_owned_by(
+        &self,
+        image_ids: &[String],
+        owner_id: Uuid,
+    ) -> Result<Vec<String>, RepoError>;
+    // keyに画像を保存し、公開URLを返す
+    async fn upload_image(
+        &self,
+        key: String,
+        image_data: Bytes,
+        mime_type: &str,
+    ) -> Result<String, RepoError>;
+    // アップロードした画像を読む。なければNone
+    async fn get_image(&self, key: &str) -> Result<Option<Bytes>, RepoError>;
+    async fn upload_blog_draft(&self, blog_id: Uuid, content: String) -> Result<(), RepoError>;
+    async fn upload_blog_rendered(
+        &self,
+        blog_id: Uuid,
+        rendered: &RenderedContent,
+    ) -> Result<(), RepoError>;
 }
@@ -0,0 +1,14 @@
+use crate::errors::repo_error::RepoError;
+
+use super::super::model::maze::SavedMaze;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+// 迷路の取得と削除は所有者で絞り込み、他人の迷路は存在しないものとして扱う
+#[async_trait]
+pub trait MazeRepository: Send + Sync {
+    async fn create_maze(&self, maze: SavedMaze) -> Result<SavedMaze, RepoError>;
+    async fn get_mazes(&self, owner_id: Uuid) -> Result<Vec<SavedMaze>, RepoError>;
+    async fn get_maze(&self, owner_id: Uuid, maze_id: Uuid) -> Result<SavedMaze, RepoError>;
+    async fn delete_maze(&self, owner_id: Uuid, maze_id: Uuid) -> Result<u64, RepoError>;
+}
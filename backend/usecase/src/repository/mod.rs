@@ -1,5 +1,7 @@
 pub mod base_repository;
 pub mod blog;
+pub mod maze;
+pub mod object_storage;
 pub mod repositories;
 pub mod types;
 pub mod user;
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use super::super::errors::repo_error::RepoError;
+
+// 記事の本文や画像を置くオブジェクトストレージ。R2のほか、ローカルのディレクトリやメモリにも置ける
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    async fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: Bytes,
+        metadata: ObjectMetadata,
+    ) -> Result<(), RepoError>;
+    // キーが存在しなければNoneを返す
+    async fn get_object(&self, bucket: &str, key: &str) -> Result<Option<Bytes>, RepoError>;
+    // キーが存在しなくてもエラーにはしない
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<(), RepoError>;
+}
+
+// 配信時に使うHTTPヘッダー。保存先が対応していなければ無視される
+#[derive(Debug, Clone, Default)]
+pub struct ObjectMetadata {
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+}
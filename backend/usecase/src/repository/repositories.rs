@@ -1,5 +1,6 @@
 use crate::repository::base_repository::BaseRepository;
 use crate::repository::blog::BlogRepository;
+use crate::repository::maze::MazeRepository;
 use crate::repository::user::UserRepository;
 
-pub trait Repositories: BaseRepository + BlogRepository + UserRepository {}
+pub trait Repositories: BaseRepository + BlogRepository + MazeRepository + UserRepository {}
@@ -2,13 +2,59 @@ use crate::errors::repo_error::RepoError;
 
 use super::super::model::user::*;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use uuid::Uuid;
 
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn get_user_by_username(&self, username: &String) -> Result<User, RepoError>;
     async fn get_user(&self, user_id: Uuid) -> Result<User, RepoError>;
-    async fn create_token(&self, user_id: Uuid, ttl: u64) -> Result<Token, RepoError>;
+    // 同じ名前の利用者がいればパスワードだけを置き換え、役割は変えない。保存された利用者のidを返す
+    async fn upsert_user(&self, user: &User) -> Result<Uuid, RepoError>;
+    async fn update_password(&self, user_id: Uuid, password: &str) -> Result<(), RepoError>;
+    // 利用者がいなければNotFound
+    async fn update_role(&self, username: &str, role: Role) -> Result<(), RepoError>;
+    // アクセストークンを発行し、session.session_idの系列に登録する
+    async fn create_token(&self, session: &SessionToken, ttl: u64) -> Result<Token, RepoError>;
     async fn delete_token(&self, token: Token) -> Result<u64, RepoError>;
-    async fn fetch_user_id_by_token(&self, access_token: String) -> Option<Uuid>;
+    async fn fetch_session_by_token(&self, access_token: String) -> Option<SessionToken>;
+    // リフレッシュトークンを発行し、session.session_idの系列に登録する
+    async fn create_refresh_token(
+        &self,
+        session: &SessionToken,
+        ttl: u64,
+    ) -> Result<String, RepoError>;
+    // ローテーション済みの印を付け、付ける前の状態を返す。期限切れや未発行ならNone
+    async fn rotate_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Option<RefreshToken>, RepoError>;
+    // 系列に登録したアクセストークンとリフレッシュトークンを全て無効にする
+    async fn revoke_token_family(&self, family_id: Uuid) -> Result<u64, RepoError>;
+    // セッションを記録し、利用者ごとの一覧に加える
+    async fn create_session(&self, session: &Session, ttl: u64) -> Result<(), RepoError>;
+    // 期限切れのものを除き、新しい順に返す
+    async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, RepoError>;
+    // 他人のセッションや期限切れのセッションならNotFound
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<(), RepoError>;
+    // 無効にしたセッションの数を返す
+    async fn revoke_all_sessions(&self, user_id: Uuid) -> Result<u64, RepoError>;
+    // ロック中なら、解除までの秒数を返す
+    async fn login_lockout(&self, scope: &LoginScope) -> Result<Option<u64>, RepoError>;
+    // 失敗を1回数え、それまでの回数を返す。最後の失敗からwindow秒たつと0に戻る
+    async fn record_login_failure(&self, scope: &LoginScope, window: u64)
+    -> Result<u64, RepoError>;
+    async fn lock_login(&self, scope: &LoginScope, ttl: u64) -> Result<(), RepoError>;
+    async fn clear_login_failures(&self, scope: &LoginScope) -> Result<(), RepoError>;
+    async fn create_api_token(&self, token: &ApiToken, token_hash: &str) -> Result<(), RepoError>;
+    // 新しい順に返す。期限切れのものも含む
+    async fn list_api_tokens(&self, user_id: Uuid) -> Result<Vec<ApiToken>, RepoError>;
+    // 他人のトークンならNotFound
+    async fn delete_api_token(&self, user_id: Uuid, token_id: Uuid) -> Result<(), RepoError>;
+    // 期限内のトークンなら最後に使った日時をnowにして返す
+    async fn use_api_token(
+        &self,
+        token_hash: &str,
+        now: NaiveDateTime,
+    ) -> Result<Option<ApiToken>, RepoError>;
 }
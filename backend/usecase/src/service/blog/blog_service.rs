@@ -1,64 +1,235 @@
 use crate::errors::app_error::AppError;
-use crate::model::blog::{Blog, BlogFilter, BlogRequest, BlogStatus};
-use crate::model::image::Image;
+use crate::errors::repo_error::RepoError;
+use crate::model::blog::{
+    Blog, BlogCursor, BlogDetail, BlogFilter, BlogPage, BlogPatch, BlogRequest, BlogStatus,
+    MAX_BLOG_TITLE_LENGTH, SlugLookup,
+};
+use crate::model::image::{Image, ImageFormat, ImageVariant};
+use crate::model::slug;
+use crate::model::user::{Permission, User};
+use crate::repository::types::Transaction;
 
 use super::super::service::Service;
+use super::image_processing::{self, EncodedImage};
+use super::markdown;
 use async_trait::async_trait;
 use bytes::Bytes;
-use std::env;
+use chrono::Utc;
 use tracing::error;
 use uuid::Uuid;
 
+// 連番を付けて空いているスラッグを探す回数の上限
+const MAX_SLUG_ATTEMPTS: usize = 100;
+
 #[async_trait]
 pub trait BlogService {
-    fn get_blogs(&self, year: Option<&String>, month: Option<&String>);
-    async fn create_blog(&self, blog: BlogRequest) -> Result<Blog, AppError>;
-    async fn create_draft(&self) -> Result<String, AppError>;
-    async fn upload_blog_image(&self, image_data: Bytes) -> Result<Image, AppError>;
+    async fn get_blogs(
+        &self,
+        year: Option<&String>,
+        month: Option<&String>,
+        cursor: Option<&String>,
+        limit: Option<&String>,
+    ) -> Result<BlogPage, AppError>;
+    async fn get_blog(&self, id: &str) -> Result<BlogDetail, AppError>;
+    async fn get_blog_by_slug(&self, slug: &str) -> Result<SlugLookup<BlogDetail>, AppError>;
+    // 以下の変更はactorの役割と、記事の作成者で許可を決める
+    async fn create_blog(&self, actor: &User, blog: BlogRequest) -> Result<Blog, AppError>;
+    async fn create_draft(&self, actor: &User, draft: BlogRequest) -> Result<Blog, AppError>;
+    async fn update_draft(
+        &self,
+        actor: &User,
+        id: &str,
+        draft: BlogRequest,
+    ) -> Result<Blog, AppError>;
+    async fn publish_blog(&self, actor: &User, id: &str) -> Result<Blog, AppError>;
+    async fn unpublish_blog(&self, actor: &User, id: &str) -> Result<Blog, AppError>;
+    async fn update_blog(&self, actor: &User, id: &str, patch: BlogPatch)
+    -> Result<Blog, AppError>;
+    async fn delete_blog(&self, actor: &User, id: &str, purge: bool) -> Result<(), AppError>;
+    async fn upload_blog_image(&self, actor: &User, image_data: Bytes) -> Result<Image, AppError>;
+    // アップロードした画像と形式。R2を使わないとき、画像のURLをこのサーバーから配信するために使う
+    async fn get_uploaded_image(&self, name: &str) -> Result<(Bytes, ImageFormat), AppError>;
 }
 
 #[async_trait]
 impl BlogService for Service {
-    fn get_blogs(&self, year: Option<&String>, month: Option<&String>) {
-        if year.is_none() && !month.is_none() {
-            return;
+    async fn get_blogs(
+        &self,
+        year: Option<&String>,
+        month: Option<&String>,
+        cursor: Option<&String>,
+        limit: Option<&String>,
+    ) -> Result<BlogPage, AppError> {
+        let filter = BlogFilter::new(year, month)?
+            .with_cursor(cursor)?
+            .with_limit(limit)?;
+        let limit = filter.limit as usize;
+
+        let mut blogs = self.repository.get_blogs(filter).await?;
+        // 1件多く取得できた場合のみ次のページがある
+        let next_cursor = if blogs.len() > limit {
+            blogs.truncate(limit);
+            blogs.last().map(|blog| BlogCursor::from(blog).encode())
+        } else {
+            None
+        };
+
+        Ok(BlogPage { blogs, next_cursor })
+    }
+
+    async fn get_blog(&self, id: &str) -> Result<BlogDetail, AppError> {
+        let blog_id = parse_blog_id(id)?;
+
+        let blog = self.repository.get_blog(blog_id).await?;
+        self.published_detail(blog).await
+    }
+
+    async fn get_blog_by_slug(&self, slug: &str) -> Result<SlugLookup<BlogDetail>, AppError> {
+        let blog = match self.repository.get_blog_by_slug(slug).await? {
+            SlugLookup::Found(blog) => blog,
+            SlugLookup::Redirect(current) => return Ok(SlugLookup::Redirect(current)),
+        };
+        Ok(SlugLookup::Found(self.published_detail(blog).await?))
+    }
+
+    async fn create_draft(&self, actor: &User, draft: BlogRequest) -> Result<Blog, AppError> {
+        require(actor, Permission::WriteBlogs)?;
+        validate_title(&draft.title)?;
+        let id = Uuid::now_v7();
+        let now = Utc::now().naive_utc();
+
+        let mut tx = self.repository.create_transaction().await?;
+        let slug = self
+            .unique_slug(&mut tx, &requested_slug(&draft, &id)?, id)
+            .await?;
+        let blog = Blog {
+            id,
+            title: draft.title,
+            slug,
+            content_key: Blog::draft_key(&id),
+            status: BlogStatus::Draft,
+            created_at: now,
+            updated_at: now,
+            published_at: None,
+            author_id: Some(actor.id),
+        };
+        let blog = self.repository.create_draft(&mut tx, blog).await?;
+        self.repository.upload_blog_draft(id, draft.content).await?;
+        commit(tx, "creating draft").await?;
+        Ok(blog)
+    }
+
+    async fn update_draft(
+        &self,
+        actor: &User,
+        id: &str,
+        draft: BlogRequest,
+    ) -> Result<Blog, AppError> {
+        let blog_id = parse_blog_id(id)?;
+        validate_title(&draft.title)?;
+
+        let mut tx = self.repository.create_transaction().await?;
+        let blog = self.lock_editable_blog(&mut tx, actor, blog_id).await?;
+        if !matches!(blog.status, BlogStatus::Draft) {
+            return Err(AppError::invalid(Some(
+                "Published blog must be unpublished before editing the draft",
+            )));
+        }
+        if let Some(requested) = &draft.slug {
+            self.rename_slug(&mut tx, &blog, &validate_slug(requested)?)
+                .await?;
         }
-        let filter = BlogFilter::new(year, month);
-        let blogs = self.repository.get_blogs(filter);
+        let blog = self
+            .repository
+            .update_blog_title(&mut tx, blog_id, &draft.title, Utc::now().naive_utc())
+            .await?;
+        // R2への書き込みに失敗した場合はトランザクションごと破棄する
+        self.repository
+            .upload_blog_draft(blog_id, draft.content)
+            .await?;
+        commit(tx, "updating draft").await?;
+        Ok(blog)
     }
 
-    async fn create_draft(&self) -> Result<String, AppError> {
+    async fn publish_blog(&self, actor: &User, id: &str) -> Result<Blog, AppError> {
+        let blog_id = parse_blog_id(id)?;
+
         let mut tx = self.repository.create_transaction().await?;
-        let id = self.repository.create_draft(&mut tx).await?;
-        tx.commit().await.map_err(|e| {
-            error!("Failed to commit transaction for creating draft: {e}");
-            AppError::internal(Some("Transaction commit failed"))
-        })?;
-        Ok(id)
+        let blog = self.lock_editable_blog(&mut tx, actor, blog_id).await?;
+        if matches!(blog.status, BlogStatus::Published) {
+            return Err(AppError::already_exist(Some("Blog is already published")));
+        }
+        if blog.title.trim().is_empty() {
+            return Err(AppError::invalid(Some(
+                "Blog without title cannot be published",
+            )));
+        }
+        // 公開ページで変換しなくて済むよう、公開する時点の本文を変換して保存しておく
+        let content = self.repository.get_blog_content(blog_id).await?;
+        let rendered = markdown::render(&content)?;
+        self.repository
+            .upload_blog_rendered(blog_id, &rendered)
+            .await?;
+        let now = Utc::now().naive_utc();
+        let blog = self
+            .repository
+            .update_blog_status(&mut tx, blog_id, BlogStatus::Published, Some(now), now)
+            .await?;
+        commit(tx, "publishing blog").await?;
+        Ok(blog)
     }
 
-    async fn create_blog(&self, blog_req: BlogRequest) -> Result<Blog, AppError> {
-        let uuid = Uuid::now_v7();
-        let blog_url = env::var("BLOG_PAGE");
+    async fn unpublish_blog(&self, actor: &User, id: &str) -> Result<Blog, AppError> {
+        let blog_id = parse_blog_id(id)?;
 
-        if let Err(e) = blog_url {
-            error!("BLOG_PAGE environment variable is not set: {e}");
-            return Err(AppError::internal(Some("environment variable is not set")));
+        let mut tx = self.repository.create_transaction().await?;
+        let blog = self.lock_editable_blog(&mut tx, actor, blog_id).await?;
+        if matches!(blog.status, BlogStatus::Draft) {
+            return Err(AppError::already_exist(Some("Blog is already a draft")));
         }
-        let content_key = format!("{}/{}", blog_url.unwrap(), blog_req.title);
+        let blog = self
+            .repository
+            .update_blog_status(
+                &mut tx,
+                blog_id,
+                BlogStatus::Draft,
+                None,
+                Utc::now().naive_utc(),
+            )
+            .await?;
+        commit(tx, "unpublishing blog").await?;
+        Ok(blog)
+    }
 
-        let blog = Blog {
-            id: uuid,
-            title: blog_req.title,
-            content_key: content_key,
-            status: BlogStatus::Published,
-        };
+    async fn create_blog(&self, actor: &User, blog_req: BlogRequest) -> Result<Blog, AppError> {
+        require(actor, Permission::WriteBlogs)?;
+        validate_title(&blog_req.title)?;
+        let uuid = Uuid::now_v7();
+        let rendered = markdown::render(&blog_req.content)?;
 
+        let now = Utc::now().naive_utc();
         let result = {
             let mut tx = self.repository.create_transaction().await?;
+            let slug = self
+                .unique_slug(&mut tx, &requested_slug(&blog_req, &uuid)?, uuid)
+                .await?;
+            let blog = Blog {
+                id: uuid,
+                title: blog_req.title,
+                content_key: Blog::draft_key(&uuid),
+                slug,
+                status: BlogStatus::Published,
+                created_at: now,
+                updated_at: now,
+                published_at: Some(now),
+                author_id: Some(actor.id),
+            };
             let blog = self.repository.create_blog(&mut tx, blog).await?;
             self.repository
-                .upload_blog_draft(uuid.to_string(), blog_req.content)
+                .upload_blog_draft(uuid, blog_req.content)
+                .await?;
+            self.repository
+                .upload_blog_rendered(uuid, &rendered)
                 .await?;
 
             tx.commit().await.map_err(|e| {
@@ -72,14 +243,315 @@ impl BlogService for Service {
         result
     }
 
-    async fn upload_blog_image(&self, image_data: Bytes) -> Result<Image, AppError> {
+    async fn update_blog(
+        &self,
+        actor: &User,
+        id: &str,
+        patch: BlogPatch,
+    ) -> Result<Blog, AppError> {
+        let blog_id = parse_blog_id(id)?;
+        if patch.title.is_none() && patch.slug.is_none() && patch.content.is_none() {
+            return Err(AppError::invalid(Some("Nothing to update")));
+        }
+        if let Some(title) = &patch.title {
+            validate_title(title)?;
+        }
+        let new_slug = match &patch.slug {
+            Some(requested) => Some(validate_slug(requested)?),
+            None => None,
+        };
+
+        let mut tx = self.repository.create_transaction().await?;
+        let blog = self.lock_editable_blog(&mut tx, actor, blog_id).await?;
+        if matches!(blog.status, BlogStatus::Published)
+            && patch.title.as_ref().is_some_and(|t| t.trim().is_empty())
+        {
+            return Err(AppError::invalid(Some("Published blog must have a title")));
+        }
+        // タイトルを変えてもスラッグは変えない。既存のURLを壊さないよう、変更は明示された場合だけ
+        if let Some(new_slug) = new_slug {
+            self.rename_slug(&mut tx, &blog, &new_slug).await?;
+        }
+        // 公開中の記事は変換済みの本文も差し替える。変換できない本文なら何も更新しない
+        let rendered = match &patch.content {
+            Some(content) if matches!(blog.status, BlogStatus::Published) => {
+                Some(markdown::render(content)?)
+            }
+            _ => None,
+        };
+        // 本文だけの更新でもupdated_atは進める
+        let title = patch.title.unwrap_or(blog.title);
+        let blog = self
+            .repository
+            .update_blog_title(&mut tx, blog_id, &title, Utc::now().naive_utc())
+            .await?;
+        if let Some(content) = patch.content {
+            self.repository.upload_blog_draft(blog_id, content).await?;
+        }
+        if let Some(rendered) = rendered {
+            self.repository
+                .upload_blog_rendered(blog_id, &rendered)
+                .await?;
+        }
+        commit(tx, "updating blog").await?;
+        Ok(blog)
+    }
+
+    async fn delete_blog(&self, actor: &User, id: &str, purge: bool) -> Result<(), AppError> {
+        let blog_id = parse_blog_id(id)?;
+
+        // 行やオブジェクトを変える前に、作成者を確かめる
+        let mut tx = self.repository.create_transaction().await?;
+        if !purge {
+            self.lock_editable_blog(&mut tx, actor, blog_id).await?;
+            self.repository
+                .soft_delete_blog(&mut tx, blog_id, Utc::now().naive_utc())
+                .await?;
+            return commit(tx, "deleting blog").await;
+        }
+
+        let blog = self
+            .repository
+            .lock_blog_with_deleted(&mut tx, blog_id)
+            .await?;
+        ensure_editable(&blog, actor)?;
+        self.repository.purge_blog(&mut tx, blog_id).await?;
+        let referenced = match self.repository.get_blog_content(blog_id).await {
+            Ok(content) => Image::referenced_ids(&content),
+            Err(RepoError::NotFound(_)) => vec![],
+            Err(e) => return Err(e.into()),
+        };
+        // 他人の画像を本文に書いて消せないよう、作成者がアップロードした画像だけを消す。
+        // 作成者のいない古い記事は管理者しか消せないので、参照している画像を全て消す
+        let image_ids = match blog.author_id {
+            Some(author_id) => {
+                self.repository
+                    .images_owned_by(&referenced, author_id)
+                    .await?
+            }
+            None => referenced,
+        };
+        // R2の削除に失敗した場合は行を残し、やり直せるようにする
+        self.repository.delete_images(&image_ids).await?;
+        self.repository.delete_blog_content(blog_id).await?;
+        commit(tx, "purging blog").await
+    }
+
+    async fn upload_blog_image(&self, actor: &User, image_data: Bytes) -> Result<Image, AppError> {
+        require(actor, Permission::WriteBlogs)?;
+        // 縮小はCPUを使い続けるので、非同期のワーカーを塞がないよう別スレッドで行う
+        let processed = tokio::task::spawn_blocking(move || image_processing::process(&image_data))
+            .await
+            .map_err(|e| {
+                error!("Failed to process blog image: {e}");
+                AppError::internal(Some("Failed to process blog image"))
+            })??;
+
         let image_id = Uuid::now_v7().to_string().replace("-", "");
+        let mut variants = Vec::with_capacity(processed.variants.len());
+        for (name, variant) in processed.variants {
+            let url = self
+                .upload_encoded_image(Image::variant_key(&image_id, name), &variant)
+                .await?;
+            variants.push(ImageVariant {
+                name: name.to_string(),
+                url,
+                width: variant.width,
+                height: variant.height,
+                mime_type: variant.format.mime_type().to_string(),
+            });
+        }
+        let original = processed.original;
+        let url = self
+            .upload_encoded_image(Image::object_key(&image_id), &original)
+            .await?;
+        self.repository.record_image(&image_id, actor.id).await?;
+
+        Ok(Image {
+            id: image_id,
+            url,
+            width: original.width,
+            height: original.height,
+            mime_type: original.format.mime_type().to_string(),
+            variants,
+        })
+    }
+
+    async fn get_uploaded_image(&self, name: &str) -> Result<(Bytes, ImageFormat), AppError> {
+        let not_found = || AppError::not_found(Some("Image not found"));
+        let key = Image::key_from_name(name).ok_or_else(not_found)?;
+        let data = self
+            .repository
+            .get_image(&key)
+            .await?
+            .ok_or_else(not_found)?;
+        let format = ImageFormat::sniff(&data).ok_or_else(|| {
+            error!("Stored image is not a supported format: {key}");
+            AppError::internal(Some("Stored image is broken"))
+        })?;
+        Ok((data, format))
+    }
+}
+
+impl Service {
+    // 記事をロックし、actorが変更できる記事か確かめる
+    async fn lock_editable_blog(
+        &self,
+        tx: &mut Transaction<'_>,
+        actor: &User,
+        blog_id: Uuid,
+    ) -> Result<Blog, AppError> {
+        let blog = self.repository.lock_blog(tx, blog_id).await?;
+        ensure_editable(&blog, actor)?;
+        Ok(blog)
+    }
+
+    // baseが使われていれば連番を付けて、空いているスラッグを探す
+    async fn unique_slug(
+        &self,
+        tx: &mut Transaction<'_>,
+        base: &str,
+        blog_id: Uuid,
+    ) -> Result<String, AppError> {
+        for n in 1..=MAX_SLUG_ATTEMPTS {
+            let candidate = slug::with_suffix(base, n);
+            if !self
+                .repository
+                .is_slug_taken(tx, &candidate, blog_id)
+                .await?
+            {
+                return Ok(candidate);
+            }
+        }
+        Err(AppError::already_exist(Some("Slug is already in use")))
+    }
+
+    async fn upload_encoded_image(
+        &self,
+        key: String,
+        image: &EncodedImage,
+    ) -> Result<String, AppError> {
         self.repository
-            .upload_image(image_id, image_data)
+            .upload_image(
+                key,
+                Bytes::copy_from_slice(&image.data),
+                image.format.mime_type(),
+            )
             .await
             .map_err(|e| {
                 error!("Failed to upload blog image: {e}");
                 AppError::internal(Some("Failed to upload blog image"))
             })
     }
+
+    // 下書きは公開されていないので、存在しないものとして扱う
+    async fn published_detail(&self, blog: Blog) -> Result<BlogDetail, AppError> {
+        if !matches!(blog.status, BlogStatus::Published) {
+            return Err(AppError::not_found(Some("Blog not found")));
+        }
+        let content = self.repository.get_blog_content(blog.id).await?;
+        let rendered = match self.repository.get_blog_rendered(blog.id).await? {
+            Some(rendered) => rendered,
+            // 変換を保存する前に公開された記事は、初めて読まれたときに一度だけ変換する
+            None => {
+                let rendered = markdown::render(&content)?;
+                self.repository
+                    .upload_blog_rendered(blog.id, &rendered)
+                    .await?;
+                rendered
+            }
+        };
+
+        Ok(BlogDetail {
+            blog,
+            content,
+            rendered,
+        })
+    }
+
+    // スラッグを変更し、旧スラッグからの転送を残す
+    async fn rename_slug(
+        &self,
+        tx: &mut Transaction<'_>,
+        blog: &Blog,
+        new_slug: &str,
+    ) -> Result<(), AppError> {
+        if new_slug == blog.slug {
+            return Ok(());
+        }
+        if self.repository.is_slug_taken(tx, new_slug, blog.id).await? {
+            return Err(AppError::already_exist(Some("Slug is already in use")));
+        }
+        self.repository
+            .rename_blog_slug(tx, blog.id, &blog.slug, new_slug)
+            .await?;
+        Ok(())
+    }
+}
+
+// 指定されたスラッグ、なければタイトルから作ったスラッグ。どちらも空ならidを使う
+fn requested_slug(req: &BlogRequest, id: &Uuid) -> Result<String, AppError> {
+    if let Some(requested) = &req.slug {
+        return validate_slug(requested);
+    }
+    let slug = slug::slugify(&req.title);
+    if slug.is_empty() {
+        return Ok(id.simple().to_string());
+    }
+    Ok(slug)
+}
+
+// 利用者が指定したスラッグを正規化する。正規化して空になるものは受け付けない
+fn validate_slug(requested: &str) -> Result<String, AppError> {
+    if requested.chars().count() > slug::MAX_SLUG_LENGTH {
+        return Err(AppError::invalid(Some(&format!(
+            "slug must be at most {} characters",
+            slug::MAX_SLUG_LENGTH
+        ))));
+    }
+    let slug = slug::slugify(requested);
+    if slug.is_empty() {
+        return Err(AppError::invalid(Some(
+            "slug must contain at least one letter or digit",
+        )));
+    }
+    Ok(slug)
+}
+
+fn require(actor: &User, permission: Permission) -> Result<(), AppError> {
+    if !actor.role.permits(permission) {
+        return Err(AppError::forbidden(Some(
+            "You do not have permission to do this",
+        )));
+    }
+    Ok(())
+}
+
+fn ensure_editable(blog: &Blog, actor: &User) -> Result<(), AppError> {
+    if !blog.is_editable_by(actor) {
+        return Err(AppError::forbidden(Some(
+            "You can only modify your own blogs",
+        )));
+    }
+    Ok(())
+}
+
+fn parse_blog_id(id: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(id).map_err(|_| AppError::invalid(Some("blog id must be a uuid")))
+}
+
+fn validate_title(title: &str) -> Result<(), AppError> {
+    if title.chars().count() > MAX_BLOG_TITLE_LENGTH {
+        return Err(AppError::invalid(Some(&format!(
+            "title must be at most {MAX_BLOG_TITLE_LENGTH} characters"
+        ))));
+    }
+    Ok(())
+}
+
+async fn commit(tx: Transaction<'_>, action: &str) -> Result<(), AppError> {
+    tx.commit().await.map_err(|e| {
+        error!("Failed to commit transaction for {action}: {e}");
+        AppError::internal(Some("Transaction commit failed"))
+    })
 }
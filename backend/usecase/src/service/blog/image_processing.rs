@@ -0,0 +1,254 @@
+// アップロードされた画像を検証し、メタデータを取り除いた元画像と縮小版を作る
+
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageReader, Limits};
+use thiserror::Error;
+
+use crate::model::image::{IMAGE_VARIANTS, ImageFormat, MAX_IMAGE_BYTES};
+
+// 縦横それぞれの最大ピクセル数。小さなファイルに巨大な画像を詰めた入力でメモリを使い果たさないようにする
+const MAX_IMAGE_DIMENSION: u32 = 8192;
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ImageError {
+    #[error("image must be at most {MAX_IMAGE_BYTES} bytes")]
+    TooLarge,
+    #[error("image must be PNG, JPEG, WebP or GIF")]
+    Unsupported,
+    #[error("image is broken: {0}")]
+    Decode(String),
+    #[error("failed to encode image: {0}")]
+    Encode(String),
+}
+
+// 保存する1枚分の画像
+pub struct EncodedImage {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub format: ImageFormat,
+}
+
+pub struct ProcessedImage {
+    pub original: EncodedImage,
+    // IMAGE_VARIANTSと同じ順に並ぶ
+    pub variants: Vec<(&'static str, EncodedImage)>,
+}
+
+pub fn process(data: &[u8]) -> Result<ProcessedImage, ImageError> {
+    if data.len() > MAX_IMAGE_BYTES {
+        return Err(ImageError::TooLarge);
+    }
+    let format = ImageFormat::sniff(data).ok_or(ImageError::Unsupported)?;
+    let image = decode(data, format)?;
+
+    // JPEGとPNGは画素だけを書き出し直してEXIFなどを落とす。
+    // WebPは可逆圧縮でしか書き出せず写真だと大きくなるので、メタデータのチャンクだけを取り除く。
+    // GIFにはEXIFがなく、書き出し直すとアニメーションが失われるのでそのまま保存する
+    let original = match format {
+        ImageFormat::Jpeg => encode_jpeg(&image)?,
+        ImageFormat::Png => encode_png(&image)?,
+        ImageFormat::WebP => EncodedImage {
+            data: strip_webp_metadata(data)?,
+            width: image.width(),
+            height: image.height(),
+            format,
+        },
+        ImageFormat::Gif => EncodedImage {
+            data: data.to_vec(),
+            width: image.width(),
+            height: image.height(),
+            format,
+        },
+    };
+
+    let variants = IMAGE_VARIANTS
+        .iter()
+        .map(|&(name, max)| {
+            // 元画像より大きくはしない
+            let resized = if image.width() > max || image.height() > max {
+                image.resize(max, max, FilterType::Lanczos3)
+            } else {
+                image.clone()
+            };
+            let encoded = if resized.color().has_alpha() {
+                encode_png(&resized)?
+            } else {
+                encode_jpeg(&resized)?
+            };
+            Ok((name, encoded))
+        })
+        .collect::<Result<_, ImageError>>()?;
+
+    Ok(ProcessedImage { original, variants })
+}
+
+fn decode(data: &[u8], format: ImageFormat) -> Result<DynamicImage, ImageError> {
+    let decode_error = |e: image::ImageError| ImageError::Decode(e.to_string());
+    let format = match format {
+        ImageFormat::Png => image::ImageFormat::Png,
+        ImageFormat::Jpeg => image::ImageFormat::Jpeg,
+        ImageFormat::WebP => image::ImageFormat::WebP,
+        ImageFormat::Gif => image::ImageFormat::Gif,
+    };
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    let mut decoder = reader.into_decoder().map_err(decode_error)?;
+    // EXIFを落とす前に、撮影時の向きを画素に反映しておく
+    let orientation = decoder.orientation().map_err(decode_error)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+fn encode_jpeg(image: &DynamicImage) -> Result<EncodedImage, ImageError> {
+    let mut data = Vec::new();
+    JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(|e| ImageError::Encode(e.to_string()))?;
+    Ok(EncodedImage {
+        data,
+        width: image.width(),
+        height: image.height(),
+        format: ImageFormat::Jpeg,
+    })
+}
+
+fn encode_png(image: &DynamicImage) -> Result<EncodedImage, ImageError> {
+    let mut data = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+        .map_err(|e| ImageError::Encode(e.to_string()))?;
+    Ok(EncodedImage {
+        data,
+        width: image.width(),
+        height: image.height(),
+        format: ImageFormat::Png,
+    })
+}
+
+// RIFFコンテナからEXIFとXMPのチャンクを取り除き、VP8Xのフラグとファイルサイズを書き直す
+fn strip_webp_metadata(data: &[u8]) -> Result<Vec<u8>, ImageError> {
+    const EXIF_FLAG: u8 = 0x08;
+    const XMP_FLAG: u8 = 0x04;
+    let malformed = || ImageError::Decode("malformed WebP container".to_string());
+
+    let mut output = data.get(..12).ok_or_else(malformed)?.to_vec();
+    let mut rest = &data[12..];
+    while !rest.is_empty() {
+        let header = rest.get(..8).ok_or_else(malformed)?;
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        // チャンクは偶数バイトに揃えられている
+        let padded = size + size % 2;
+        let chunk = rest.get(..8 + padded).ok_or_else(malformed)?;
+        match &header[..4] {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" => {
+                let flags_at = output.len() + 8;
+                output.extend_from_slice(chunk);
+                *output.get_mut(flags_at).ok_or_else(malformed)? &= !(EXIF_FLAG | XMP_FLAG);
+            }
+            _ => output.extend_from_slice(chunk),
+        }
+        rest = &rest[8 + padded..];
+    }
+
+    let riff_size = (output.len() - 8) as u32;
+    output[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        encode_png(&DynamicImage::new_rgb8(width, height))
+            .unwrap()
+            .data
+    }
+
+    #[test]
+    fn reject_unsupported_and_oversized_images() {
+        assert_eq!(Some(ImageError::Unsupported), process(b"<svg></svg>").err());
+        let mut large = png(1, 1);
+        large.resize(MAX_IMAGE_BYTES + 1, 0);
+        assert_eq!(Some(ImageError::TooLarge), process(&large).err());
+        assert!(matches!(
+            process(&png(1, 1)[..20]),
+            Err(ImageError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn variants_are_shrunk_but_not_enlarged() {
+        let processed = process(&png(2000, 1000)).unwrap();
+
+        assert_eq!(
+            (2000, 1000),
+            (processed.original.width, processed.original.height)
+        );
+        let sizes: Vec<_> = processed
+            .variants
+            .iter()
+            .map(|(name, v)| (*name, v.width, v.height, v.format))
+            .collect();
+        assert_eq!(
+            vec![
+                ("thumbnail", 320, 160, ImageFormat::Jpeg),
+                ("medium", 1280, 640, ImageFormat::Jpeg),
+            ],
+            sizes
+        );
+
+        let small = process(&png(100, 50)).unwrap();
+        assert!(small.variants.iter().all(|(_, v)| v.width == 100));
+    }
+
+    #[test]
+    fn exif_is_stripped_from_jpeg() {
+        let jpeg = encode_jpeg(&DynamicImage::new_rgb8(4, 4)).unwrap().data;
+        // SOIの直後に、GPS情報などを載せるAPP1(EXIF)セグメントを差し込む
+        let exif = b"Exif\0\0II*\0\x08\0\0\0\0\0\0\0\0\0";
+        let mut with_exif = jpeg[..2].to_vec();
+        with_exif.extend_from_slice(&[0xff, 0xe1]);
+        with_exif.extend_from_slice(&((exif.len() + 2) as u16).to_be_bytes());
+        with_exif.extend_from_slice(exif);
+        with_exif.extend_from_slice(&jpeg[2..]);
+
+        let processed = process(&with_exif).unwrap();
+
+        assert_eq!(ImageFormat::Jpeg, processed.original.format);
+        assert!(!processed.original.data.windows(4).any(|w| w == b"Exif"));
+    }
+
+    #[test]
+    fn exif_chunk_is_stripped_from_webp() {
+        let mut webp = b"RIFF\0\0\0\0WEBP".to_vec();
+        // 透過(0x10)とEXIF(0x08)のフラグを立てたVP8X
+        webp.extend_from_slice(b"VP8X\x0a\0\0\0");
+        webp.extend_from_slice(&[0x18, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        webp.extend_from_slice(b"EXIF\x03\0\0\0abc\0");
+        webp.extend_from_slice(b"ALPH\x02\0\0\0xy");
+        let size = (webp.len() - 8) as u32;
+        webp[4..8].copy_from_slice(&size.to_le_bytes());
+
+        let stripped = strip_webp_metadata(&webp).unwrap();
+
+        assert!(!stripped.windows(4).any(|w| w == b"EXIF"));
+        assert_eq!(0x10, stripped[20]);
+        assert_eq!(
+            (stripped.len() - 8) as u32,
+            u32::from_le_bytes(stripped[4..8].try_into().unwrap())
+        );
+        assert!(stripped.ends_with(b"ALPH\x02\0\0\0xy"));
+    }
+}
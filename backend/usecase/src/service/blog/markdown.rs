@@ -0,0 +1,297 @@
+// 記事本文のMarkdownを公開用のHTMLに変換する
+//
+// 見出しにはアンカー用のidを付けて目次を作り、コードブロックは言語が分かればsyntectで色分けする。
+// 生のHTMLも書けるので、最後にammoniaで許可したタグと属性以外を取り除く。
+// サーバーで付けるidには接頭辞を付け、接頭辞のないidは書き手が書いたものとして取り除く
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use ammonia::Builder;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd, html};
+use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+use thiserror::Error;
+
+use crate::model::blog::{RenderedContent, TocEntry};
+use crate::model::slug;
+
+// 色分けしたコードのspanに付けるクラス名の接頭辞。CSS側はこの名前でテーマを当てる
+const HIGHLIGHT_CLASS_PREFIX: &str = "hl-";
+
+// 見出しと脚注に付けるidの接頭辞。ページ側の要素のidと衝突しないようにする
+const ID_PREFIX: &str = "user-content-";
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+static SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::default();
+    for heading in ["h1", "h2", "h3", "h4", "h5", "h6"] {
+        builder.add_tag_attributes(heading, &["id"]);
+    }
+    builder
+        .add_tag_attributes("a", &["class"])
+        .add_tag_attributes("div", &["class", "id"])
+        .add_tag_attributes("sup", &["class"])
+        .add_tag_attributes("pre", &["class"])
+        .add_tag_attributes("code", &["class"])
+        .add_tag_attributes("span", &["class"])
+        .attribute_filter(|_, attribute, value| match attribute {
+            "id" if !value.starts_with(ID_PREFIX) => None,
+            _ => Some(Cow::Borrowed(value)),
+        });
+    builder
+});
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MarkdownError {
+    #[error("footnote [^{0}] is referenced but not defined")]
+    UndefinedFootnote(String),
+    #[error("failed to highlight {0} code block")]
+    Highlight(String),
+}
+
+pub fn render(source: &str) -> Result<RenderedContent, MarkdownError> {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_SMART_PUNCTUATION;
+    let events: Vec<Event> = Parser::new_ext(source, options).collect();
+    check_footnotes(&events)?;
+    let events = prefix_footnotes(events);
+    let (events, toc) = anchor_headings(events);
+    let events = highlight_code_blocks(events)?;
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, events.into_iter());
+    Ok(RenderedContent {
+        html: SANITIZER.clean(&unsafe_html).to_string(),
+        toc,
+    })
+}
+
+// 定義のない脚注を参照していたら、書き間違いとしてエラーにする
+//
+// 定義のない参照はFootnoteReferenceにならず、"[", "^label", "]"の3つのテキストに分かれて残る。
+// エスケープした"\[^label]"やコード中の文字列はこの形にならない
+fn check_footnotes(events: &[Event]) -> Result<(), MarkdownError> {
+    for window in events.windows(3) {
+        if let [Event::Text(open), Event::Text(label), Event::Text(close)] = window
+            && open.as_ref() == "["
+            && close.as_ref() == "]"
+            && let Some(label) = label.strip_prefix('^')
+            && !label.is_empty()
+            && !label.contains(char::is_whitespace)
+        {
+            return Err(MarkdownError::UndefinedFootnote(label.to_string()));
+        }
+    }
+    Ok(())
+}
+
+// 見出しに重複しないidと自身へのリンクを付け、目次を作る
+fn anchor_headings(events: Vec<Event>) -> (Vec<Event>, Vec<TocEntry>) {
+    let mut toc = Vec::new();
+    let mut used: HashMap<String, usize> = HashMap::new();
+    let mut output = Vec::with_capacity(events.len());
+    let mut iter = events.into_iter();
+
+    while let Some(event) = iter.next() {
+        let Event::Start(Tag::Heading {
+            level,
+            classes,
+            attrs,
+            ..
+        }) = event
+        else {
+            output.push(event);
+            continue;
+        };
+
+        let mut inner = Vec::new();
+        let mut title = String::new();
+        for event in iter.by_ref() {
+            match &event {
+                Event::End(TagEnd::Heading(_)) => break,
+                Event::Text(text) | Event::Code(text) => title.push_str(text),
+                _ => {}
+            }
+            inner.push(event);
+        }
+
+        let base = match slug::slugify(&title) {
+            base if base.is_empty() => "section".to_string(),
+            base => base,
+        };
+        let count = used.entry(base.clone()).or_insert(0);
+        *count += 1;
+        let id = format!("{ID_PREFIX}{}", slug::with_suffix(&base, *count));
+
+        output.push(Event::Start(Tag::Heading {
+            level,
+            id: Some(CowStr::from(id.clone())),
+            classes,
+            attrs,
+        }));
+        output.push(Event::InlineHtml(CowStr::from(format!(
+            "<a class=\"heading-anchor\" href=\"#{id}\">#</a>"
+        ))));
+        output.extend(inner);
+        output.push(Event::End(TagEnd::Heading(level)));
+        toc.push(TocEntry {
+            level: level as u8,
+            id,
+            title: title.trim().to_string(),
+        });
+    }
+    (output, toc)
+}
+
+// 脚注のラベルに接頭辞を付ける。ラベルはそのまま参照先のhrefと定義のidになる
+fn prefix_footnotes(events: Vec<Event>) -> Vec<Event> {
+    events
+        .into_iter()
+        .map(|event| match event {
+            Event::FootnoteReference(label) => {
+                Event::FootnoteReference(CowStr::from(format!("{ID_PREFIX}{label}")))
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => Event::Start(Tag::FootnoteDefinition(
+                CowStr::from(format!("{ID_PREFIX}{label}")),
+            )),
+            event => event,
+        })
+        .collect()
+}
+
+// 言語が分かるコードブロックをクラス名付きのspanで色分けする。分からなければそのまま残す
+fn highlight_code_blocks(events: Vec<Event>) -> Result<Vec<Event>, MarkdownError> {
+    let mut output = Vec::with_capacity(events.len());
+    let mut iter = events.into_iter();
+
+    while let Some(event) = iter.next() {
+        let Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) = &event else {
+            output.push(event);
+            continue;
+        };
+        let lang = info
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let syntax = SYNTAXES.find_syntax_by_token(&lang).filter(|_| {
+            lang.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-#_".contains(c))
+        });
+        let Some(syntax) = syntax else {
+            output.push(event);
+            continue;
+        };
+
+        let mut code = String::new();
+        for event in iter.by_ref() {
+            match event {
+                Event::End(TagEnd::CodeBlock) => break,
+                Event::Text(text) => code.push_str(&text),
+                _ => {}
+            }
+        }
+
+        let mut generator = ClassedHTMLGenerator::new_with_class_style(
+            syntax,
+            &SYNTAXES,
+            ClassStyle::SpacedPrefixed {
+                prefix: HIGHLIGHT_CLASS_PREFIX,
+            },
+        );
+        for line in LinesWithEndings::from(&code) {
+            generator
+                .parse_html_for_line_which_includes_newline(line)
+                .map_err(|_| MarkdownError::Highlight(lang.clone()))?;
+        }
+        output.push(Event::Html(CowStr::from(format!(
+            "<pre class=\"highlight\"><code class=\"language-{lang}\">{}</code></pre>\n",
+            generator.finalize()
+        ))));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headings_get_unique_anchors_and_toc() {
+        let rendered = render("# 迷路の作り方\n\n## Setup\n\n## Setup\n\n### `code` here").unwrap();
+
+        let ids: Vec<&str> = rendered.toc.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(
+            vec![
+                "user-content-迷路の作り方",
+                "user-content-setup",
+                "user-content-setup-2",
+                "user-content-code-here"
+            ],
+            ids
+        );
+        assert_eq!(3, rendered.toc[3].level);
+        assert!(rendered.html.contains("<h2 id=\"user-content-setup-2\">"));
+        assert!(rendered.html.contains("href=\"#user-content-setup-2\""));
+    }
+
+    #[test]
+    fn code_blocks_are_highlighted() {
+        let rendered = render("```rust\nfn main() {}\n```\n\n```unknown\n<b>\n```").unwrap();
+
+        assert!(rendered.html.contains("<code class=\"language-rust\">"));
+        assert!(rendered.html.contains("class=\"hl-"));
+        // 知らない言語はエスケープしただけのコードになる
+        assert!(rendered.html.contains("&lt;b&gt;"));
+    }
+
+    #[test]
+    fn footnotes_are_rendered() {
+        let rendered = render("Maze[^1].\n\n[^1]: generated by Kruskal").unwrap();
+
+        assert!(rendered.html.contains("class=\"footnote-reference\""));
+        assert!(rendered.html.contains("class=\"footnote-definition\""));
+        assert!(rendered.html.contains("href=\"#user-content-1\""));
+        assert!(rendered.html.contains("id=\"user-content-1\""));
+    }
+
+    #[test]
+    fn undefined_footnote_is_an_error() {
+        assert_eq!(
+            Err(MarkdownError::UndefinedFootnote("missing".to_string())),
+            render("Maze[^1][^missing].\n\n[^1]: defined").map(|r| r.html)
+        );
+        // エスケープした括弧やコード中の文字列は脚注ではない
+        assert!(render("\\[^escaped] `[^code]`\n\n```\n[^block]\n```").is_ok());
+    }
+
+    #[test]
+    fn raw_html_is_sanitized() {
+        let rendered = render(
+            "<script>alert(1)</script>\n\n<a href=\"javascript:alert(1)\" onclick=\"x\">link</a>",
+        )
+        .unwrap();
+
+        assert!(!rendered.html.contains("<script"));
+        assert!(!rendered.html.contains("javascript:"));
+        assert!(!rendered.html.contains("onclick"));
+    }
+
+    #[test]
+    fn author_ids_are_removed() {
+        let rendered = render(
+            "<h2 id=\"login\">raw</h2>\n\n<div id=\"app\" class=\"note\">x</div>\n\n## Heading",
+        )
+        .unwrap();
+
+        assert!(rendered.html.contains("<h2>raw</h2>"));
+        assert!(rendered.html.contains("<div class=\"note\">"));
+        assert!(rendered.html.contains("<h2 id=\"user-content-heading\">"));
+    }
+}
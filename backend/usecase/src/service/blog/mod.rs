@@ -1 +1,3 @@
 pub mod blog_service;
+pub mod image_processing;
+pub mod markdown;
@@ -0,0 +1,140 @@
+use crate::errors::app_error::AppError;
+use crate::model::maze::{
+    GeneratedMaze, MazeBody, MazeFormat, MazeRequest, SaveMazeRequest, SavedMaze,
+};
+
+use super::super::service::Service;
+use async_trait::async_trait;
+use chrono::Utc;
+use maze_core::maze::{png, svg};
+use maze_core::{Maze, MazeType, RenderStyle};
+use tracing::error;
+use uuid::Uuid;
+
+// 1辺あたりのマス数の上限
+pub const MAX_MAZE_SIZE: usize = 400;
+pub const MAX_TITLE_LENGTH: usize = 100;
+
+#[async_trait]
+pub trait MazeService {
+    async fn generate_maze(&self, req: MazeRequest) -> Result<GeneratedMaze, AppError>;
+    async fn save_maze(&self, owner_id: Uuid, req: SaveMazeRequest) -> Result<SavedMaze, AppError>;
+    async fn get_saved_mazes(&self, owner_id: Uuid) -> Result<Vec<SavedMaze>, AppError>;
+    async fn get_saved_maze(&self, owner_id: Uuid, maze_id: Uuid) -> Result<SavedMaze, AppError>;
+    async fn delete_saved_maze(&self, owner_id: Uuid, maze_id: Uuid) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl MazeService for Service {
+    async fn generate_maze(&self, req: MazeRequest) -> Result<GeneratedMaze, AppError> {
+        validate_size(req.rows, req.cols)?;
+        let maze_type = parse_maze_type(req.maze_type.as_deref())?;
+        let seed = req.seed.unwrap_or_else(random_seed);
+
+        // 生成と描画はCPUを使い続けるので、非同期のワーカーを塞がないよう別スレッドで行う
+        let body = tokio::task::spawn_blocking(move || {
+            let maze = maze_core::generate(req.rows, req.cols, maze_type, seed)?;
+            render(maze, req.format)
+        })
+        .await
+        .map_err(|e| {
+            error!("Failed to generate maze: {e}");
+            AppError::internal(Some("Failed to generate maze"))
+        })??;
+
+        Ok(GeneratedMaze {
+            seed,
+            maze_type,
+            body,
+        })
+    }
+
+    async fn save_maze(&self, owner_id: Uuid, req: SaveMazeRequest) -> Result<SavedMaze, AppError> {
+        validate_size(req.rows, req.cols)?;
+        let maze_type = parse_maze_type(req.maze_type.as_deref())?;
+        if !maze_core::validate(req.rows, req.cols, maze_type) {
+            return Err(maze_core::MazeError::InvalidSize {
+                row: req.rows,
+                col: req.cols,
+            }
+            .into());
+        }
+        let title = req
+            .title
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty());
+        if title
+            .as_ref()
+            .is_some_and(|title| title.chars().count() > MAX_TITLE_LENGTH)
+        {
+            return Err(AppError::invalid(Some(&format!(
+                "title must be at most {MAX_TITLE_LENGTH} characters"
+            ))));
+        }
+
+        let maze = SavedMaze {
+            id: Uuid::now_v7(),
+            owner_id,
+            title,
+            maze_type,
+            seed: req.seed.unwrap_or_else(random_seed),
+            rows: req.rows,
+            cols: req.cols,
+            created_at: Utc::now().naive_utc(),
+        };
+        let saved = self.repository.create_maze(maze).await.inspect_err(|e| {
+            error!("Failed to save maze of user: {owner_id}, error: {e}");
+        })?;
+        Ok(saved)
+    }
+
+    async fn get_saved_mazes(&self, owner_id: Uuid) -> Result<Vec<SavedMaze>, AppError> {
+        Ok(self.repository.get_mazes(owner_id).await?)
+    }
+
+    async fn get_saved_maze(&self, owner_id: Uuid, maze_id: Uuid) -> Result<SavedMaze, AppError> {
+        Ok(self.repository.get_maze(owner_id, maze_id).await?)
+    }
+
+    async fn delete_saved_maze(&self, owner_id: Uuid, maze_id: Uuid) -> Result<(), AppError> {
+        self.repository.delete_maze(owner_id, maze_id).await?;
+        Ok(())
+    }
+}
+
+fn render(maze: Maze, format: MazeFormat) -> Result<MazeBody, AppError> {
+    let style = RenderStyle::default();
+    Ok(match format {
+        MazeFormat::Json => MazeBody::Json(maze),
+        MazeFormat::Svg => MazeBody::Svg(svg::render(&maze, &style)),
+        // 大きな迷路はマスを小さくして、画像のピクセル数を抑える
+        MazeFormat::Png => MazeBody::Png(
+            png::render(&maze, &png::fit_style(&maze, &style)).map_err(|e| {
+                error!("Failed to render maze as png: {e}");
+                AppError::from(e)
+            })?,
+        ),
+    })
+}
+
+fn validate_size(rows: usize, cols: usize) -> Result<(), AppError> {
+    if rows > MAX_MAZE_SIZE || cols > MAX_MAZE_SIZE {
+        return Err(AppError::invalid(Some(&format!(
+            "rows and cols must be less than or equal to {MAX_MAZE_SIZE}"
+        ))));
+    }
+    Ok(())
+}
+
+fn parse_maze_type(name: Option<&str>) -> Result<MazeType, AppError> {
+    match name {
+        Some(name) => Ok(name.parse::<MazeType>()?),
+        None => Ok(MazeType::Random),
+    }
+}
+
+// UUIDv7の乱数部分から、seed未指定時の値を作る
+fn random_seed() -> u64 {
+    let bytes = Uuid::now_v7().into_bytes();
+    u64::from_be_bytes(bytes[8..16].try_into().expect("slice has 8 bytes"))
+}
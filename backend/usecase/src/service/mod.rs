@@ -1,3 +1,4 @@
 pub mod blog;
+pub mod maze;
 pub mod service;
 pub mod user;
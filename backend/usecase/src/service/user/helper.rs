@@ -1,21 +1,174 @@
-use argon2::password_hash::SaltString;
-use argon2::{Argon2, PasswordHasher};
+// パスワードのハッシュはArgon2idのPHC文字列で保存する
+//
+// ペッパーはArgon2の秘密鍵として渡し、そのidをPHC文字列のkeyidに書き込む。
+// keyidのない古いハッシュは、パスワードの末尾にペッパーを連結して作っていたもの
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, KeyId, Params, ParamsBuilder, Version};
+use shared::config::{Pepper, Peppers};
 use tracing::error;
 
 use crate::errors::app_error::AppError;
 
-pub fn hash_with_salt_pepper(input: &str, salt: &str, pepper: &str) -> Result<String, AppError> {
-    let preppered = format!("{input}{pepper}");
-    let salt = SaltString::encode_b64(salt.as_bytes()).map_err(|e| {
-        error!("Failed to encode salt: {}", e);
-        AppError::internal(Some(&format!("Internal error on Encoding")))
-    })?;
-    let hash = Argon2::default()
-        .hash_password(preppered.as_bytes(), &salt)
-        .map_err(|e| {
-            error!("Failed to hash password with salt and pepper: {}", e);
-            AppError::internal(Some(&format!("Internal error on hashing password")))
-        })?
+// 新しく作るハッシュのパラメータ。変えると、古いパラメータのハッシュは次のログインで作り直される
+const M_COST: u32 = 19 * 1024;
+const T_COST: u32 = 2;
+const P_COST: u32 = 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verification {
+    Invalid,
+    // needs_rehashなら、パラメータかペッパーが古いので今の設定で作り直す
+    Valid { needs_rehash: bool },
+}
+
+pub fn hash_password(password: &str, pepper: &Pepper) -> Result<String, AppError> {
+    let params = ParamsBuilder::new()
+        .m_cost(M_COST)
+        .t_cost(T_COST)
+        .p_cost(P_COST)
+        .keyid(KeyId::new(pepper.id.as_bytes()).map_err(internal_error)?)
+        .build()
+        .map_err(internal_error)?;
+    let argon2 = Argon2::new_with_secret(
+        pepper.secret.as_bytes(),
+        Algorithm::Argon2id,
+        Version::V0x13,
+        params,
+    )
+    .map_err(internal_error)?;
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = argon2
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(internal_error)?
         .to_string();
     Ok(hash)
 }
+
+// 比較はPasswordVerifierに任せ、一定時間で行う
+pub fn verify_password(
+    password: &str,
+    hash: &str,
+    peppers: &Peppers,
+) -> Result<Verification, AppError> {
+    let hash = PasswordHash::new(hash).map_err(internal_error)?;
+    let params = Params::try_from(&hash).map_err(internal_error)?;
+
+    let valid = if params.keyid().is_empty() {
+        let mut valid = false;
+        for pepper in peppers.iter() {
+            let peppered = format!("{password}{}", pepper.secret);
+            if matches_hash(&Argon2::default(), &peppered, &hash)? {
+                valid = true;
+                break;
+            }
+        }
+        valid
+    } else {
+        let Some(pepper) = peppers.find(params.keyid()) else {
+            error!("Password hash refers to an unknown pepper id");
+            return Err(AppError::internal(Some(
+                "Internal error on verifying password",
+            )));
+        };
+        let argon2 = Argon2::new_with_secret(
+            pepper.secret.as_bytes(),
+            Algorithm::default(),
+            Version::default(),
+            Params::default(),
+        )
+        .map_err(internal_error)?;
+        matches_hash(&argon2, password, &hash)?
+    };
+
+    if !valid {
+        return Ok(Verification::Invalid);
+    }
+    let needs_rehash = hash.algorithm != Algorithm::Argon2id.ident()
+        || hash.version != Some(Version::V0x13.into())
+        || (params.m_cost(), params.t_cost(), params.p_cost()) != (M_COST, T_COST, P_COST)
+        || params.keyid() != peppers.current.id.as_bytes();
+    Ok(Verification::Valid { needs_rehash })
+}
+
+fn matches_hash(argon2: &Argon2, password: &str, hash: &PasswordHash) -> Result<bool, AppError> {
+    match argon2.verify_password(password.as_bytes(), hash) {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(internal_error(e)),
+    }
+}
+
+fn internal_error(e: impl std::fmt::Display) -> AppError {
+    error!("Failed to hash password: {}", e);
+    AppError::internal(Some("Internal error on hashing password"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peppers() -> Peppers {
+        Peppers {
+            current: Pepper::new("2", "new pepper"),
+            old: vec![Pepper::new("1", "old pepper")],
+        }
+    }
+
+    // keyidを書き込む前の方式で作ったハッシュ
+    fn legacy_hash(password: &str, pepper: &str) -> String {
+        let salt = SaltString::encode_b64(b"0123456789abcdef").unwrap();
+        Argon2::default()
+            .hash_password(format!("{password}{pepper}").as_bytes(), &salt)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn hashes_are_salted_and_keyed() {
+        let peppers = peppers();
+        let hash = hash_password("correct horse", &peppers.current).unwrap();
+
+        assert!(hash.starts_with("$argon2id$v=19$m=19456,t=2,p=1,keyid="));
+        assert_ne!(
+            hash,
+            hash_password("correct horse", &peppers.current).unwrap()
+        );
+        assert_eq!(
+            Verification::Valid {
+                needs_rehash: false
+            },
+            verify_password("correct horse", &hash, &peppers).unwrap()
+        );
+        assert_eq!(
+            Verification::Invalid,
+            verify_password("wrong horse", &hash, &peppers).unwrap()
+        );
+    }
+
+    #[test]
+    fn old_peppers_and_legacy_hashes_ask_for_rehash() {
+        let peppers = peppers();
+        let old = hash_password("correct horse", &peppers.old[0]).unwrap();
+        let legacy = legacy_hash("correct horse", "old pepper");
+
+        for hash in [old, legacy] {
+            assert_eq!(
+                Verification::Valid { needs_rehash: true },
+                verify_password("correct horse", &hash, &peppers).unwrap()
+            );
+            assert_eq!(
+                Verification::Invalid,
+                verify_password("wrong horse", &hash, &peppers).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_pepper_id_is_an_error() {
+        let hash = hash_password("correct horse", &Pepper::new("9", "lost")).unwrap();
+
+        assert!(verify_password("correct horse", &hash, &peppers()).is_err());
+    }
+}
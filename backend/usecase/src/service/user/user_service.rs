@@ -1,13 +1,31 @@
 use async_trait::async_trait;
-use std::env;
+use chrono::{Duration, Utc};
 use tracing::{error, warn};
 use uuid::Uuid;
 
-use crate::model::user::{self, Token, User};
+use crate::model::user::{
+    self, API_TOKEN_PREFIX, ApiToken, ApiTokenRequest, ClientInfo, IssuedTokens, LoginScope,
+    NewApiToken, Permission, Role, Session, SessionToken, User,
+};
 
 use super::super::super::errors::app_error::AppError;
+use super::super::super::errors::repo_error::RepoError;
 use super::super::service::Service;
-use super::helper;
+use super::helper::{self, Verification};
+
+// 最後の失敗からこの秒数がたつまで、失敗の回数を覚えておく
+const LOGIN_FAILURE_WINDOW: u64 = 24 * 60 * 60;
+// 最初のロックの秒数。その後は失敗するたびに倍にする
+const LOGIN_LOCKOUT_BASE: u64 = 30;
+const LOGIN_LOCKOUT_MAX: u64 = 60 * 60;
+// users.nameの長さの上限
+const MAX_USERNAME_CHARS: usize = 50;
+const MIN_PASSWORD_CHARS: usize = 12;
+// 長すぎる入力でハッシュの計算に時間をかけさせない
+const MAX_PASSWORD_CHARS: usize = 1024;
+// api_tokens.nameの長さの上限
+const MAX_API_TOKEN_NAME_CHARS: usize = 100;
+const MAX_API_TOKEN_DAYS: u32 = 365;
 
 #[async_trait]
 pub trait UserService {
@@ -15,10 +33,32 @@ pub trait UserService {
         &self,
         username: &String,
         password: &String,
-    ) -> Result<(Token, String), AppError>;
-    async fn logout(&self, access_token: Token) -> Result<(), AppError>;
+        client: ClientInfo,
+    ) -> Result<IssuedTokens, AppError>;
+    // このセッションのアクセストークンとリフレッシュトークンを全て無効にする
+    async fn logout(&self, user_id: Uuid, session_id: Uuid) -> Result<(), AppError>;
+    // リフレッシュトークンを使い捨てにして、アクセストークンと一緒に発行し直す
+    async fn refresh(&self, refresh_token: &str) -> Result<IssuedTokens, AppError>;
     async fn get_user(&self, user_id: Uuid) -> Result<User, AppError>;
-    async fn fetch_user_id_by_token(&self, access_token: String) -> Result<Uuid, AppError>;
+    async fn fetch_session_by_token(&self, access_token: String) -> Result<SessionToken, AppError>;
+    async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, AppError>;
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<(), AppError>;
+    // 全ての端末からログアウトする。無効にしたセッションの数を返す
+    async fn revoke_all_sessions(&self, user_id: Uuid) -> Result<u64, AppError>;
+    // 利用者がいなければ作り、いればパスワードを置き換えて全てのセッションを無効にする。作ったらtrue
+    async fn set_password(&self, username: &str, password: &str) -> Result<bool, AppError>;
+    // 利用者がいなければNotFound
+    async fn set_role(&self, username: &str, role: Role) -> Result<(), AppError>;
+    // 役割が許す範囲のスコープで、個人用のAPIトークンを発行する
+    async fn create_api_token(
+        &self,
+        user: &User,
+        req: ApiTokenRequest,
+    ) -> Result<NewApiToken, AppError>;
+    async fn list_api_tokens(&self, user_id: Uuid) -> Result<Vec<ApiToken>, AppError>;
+    async fn revoke_api_token(&self, user_id: Uuid, token_id: Uuid) -> Result<(), AppError>;
+    // 期限内のトークンなら、持ち主とトークンを返す
+    async fn authenticate_api_token(&self, secret: &str) -> Result<(User, ApiToken), AppError>;
 }
 
 #[async_trait]
@@ -27,66 +67,417 @@ impl UserService for Service {
         &self,
         username: &String,
         password: &String,
-    ) -> Result<(Token, String), AppError> {
-        let res = self.repository.get_user_by_username(username).await;
-        let user = match res {
-            Ok(user) => user,
-            Err(e) => {
-                warn!("Failed to get user: {}, error: {}", username, e);
-                return Err(AppError::invalid(Some(
-                    "The pair of username and password is incorrect",
-                )));
+        client: ClientInfo,
+    ) -> Result<IssuedTokens, AppError> {
+        let scopes = login_scopes(username, &client);
+        for scope in &scopes {
+            if let Some(remaining) = self.repository.login_lockout(scope).await? {
+                warn!("Login is locked for {:?}, {}s remaining", scope, remaining);
+                return Err(AppError::too_many_requests(
+                    Some("Too many failed login attempts"),
+                    remaining,
+                ));
             }
+        }
+
+        let Some(user) = self.verify_password(username, password).await? else {
+            self.record_login_failure(&scopes).await?;
+            return Err(AppError::invalid(Some(
+                "The pair of username and password is incorrect",
+            )));
+        };
+        // IPの方は、同じIPから他の利用者名を試され続けることがあるので消さない
+        self.repository
+            .clear_login_failures(&account_scope(username, &client))
+            .await?;
+
+        let session = Session {
+            id: Uuid::now_v7(),
+            user_id: user.id,
+            user_agent: client.user_agent,
+            ip: client.ip,
+            created_at: Utc::now().naive_utc(),
+        };
+        self.repository
+            .create_session(&session, self.config.refresh_ttl)
+            .await?;
+        self.issue_tokens(SessionToken::new(user.id, session.id))
+            .await
+    }
+
+    async fn logout(&self, user_id: Uuid, session_id: Uuid) -> Result<(), AppError> {
+        self.repository.revoke_session(user_id, session_id).await?;
+        Ok(())
+    }
+
+    async fn refresh(&self, refresh_token: &str) -> Result<IssuedTokens, AppError> {
+        let Some(entry) = self.repository.rotate_refresh_token(refresh_token).await? else {
+            return Err(AppError::unauthorized(Some(
+                "refresh token is invalid or expired",
+            )));
         };
+        if entry.rotated {
+            // ローテーション済みのトークンが再び使われたら、盗まれたものとして同じログインのトークンを全て無効にする
+            warn!(
+                "Reuse of refresh token detected for user: {}, revoking token family: {}",
+                entry.user_id, entry.family_id
+            );
+            self.repository.revoke_token_family(entry.family_id).await?;
+            return Err(AppError::unauthorized(Some(
+                "refresh token has already been used",
+            )));
+        }
+        self.issue_tokens(SessionToken {
+            user_id: entry.user_id,
+            session_id: entry.family_id,
+            csrf_token: entry.csrf_token,
+        })
+        .await
+    }
+
+    async fn get_user(&self, user_id: Uuid) -> Result<User, AppError> {
+        let user = self.repository.get_user(user_id).await?;
+        Ok(user)
+    }
+
+    async fn fetch_session_by_token(&self, access_token: String) -> Result<SessionToken, AppError> {
+        let session = self.repository.fetch_session_by_token(access_token).await;
+
+        if let Some(session) = session {
+            return Ok(session);
+        }
+
+        Err(AppError::not_found(Some("user_id is not found")))
+    }
+
+    async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, AppError> {
+        Ok(self.repository.list_sessions(user_id).await?)
+    }
+
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<(), AppError> {
+        self.repository.revoke_session(user_id, session_id).await?;
+        Ok(())
+    }
+
+    async fn revoke_all_sessions(&self, user_id: Uuid) -> Result<u64, AppError> {
+        Ok(self.repository.revoke_all_sessions(user_id).await?)
+    }
+
+    async fn set_password(&self, username: &str, password: &str) -> Result<bool, AppError> {
+        validate_credentials(username, password)?;
+
+        let user = User {
+            id: Uuid::now_v7(),
+            name: username.to_string(),
+            password: helper::hash_password(password, &self.config.password_peppers.current)?,
+            // 管理用のコマンドで作る利用者なので管理者にする。既にいる利用者の役割は変えない
+            role: Role::Admin,
+        };
+        let saved_id = self.repository.upsert_user(&user).await?;
+        if saved_id == user.id {
+            return Ok(true);
+        }
+        // パスワードが漏れて置き換えた場合に備え、ログイン中の端末も追い出す
+        self.repository.revoke_all_sessions(saved_id).await?;
+        Ok(false)
+    }
+
+    async fn set_role(&self, username: &str, role: Role) -> Result<(), AppError> {
+        self.repository.update_role(username, role).await?;
+        Ok(())
+    }
+
+    async fn create_api_token(
+        &self,
+        user: &User,
+        req: ApiTokenRequest,
+    ) -> Result<NewApiToken, AppError> {
+        let name = validate_api_token_name(&req.name)?;
+        let scopes = parse_scopes(&req.scopes, user.role)?;
+        let expires_in_days = req
+            .expires_in_days
+            .map(|days| {
+                if !(1..=MAX_API_TOKEN_DAYS).contains(&days) {
+                    return Err(AppError::invalid(Some(&format!(
+                        "expires_in_days must be 1 to {MAX_API_TOKEN_DAYS}"
+                    ))));
+                }
+                Ok(days)
+            })
+            .transpose()?;
+
+        let secret = ApiToken::generate_secret();
+        let now = Utc::now().naive_utc();
+        let token = ApiToken {
+            id: Uuid::now_v7(),
+            user_id: user.id,
+            name,
+            prefix: ApiToken::display_prefix(&secret),
+            scopes,
+            created_at: now,
+            expires_at: expires_in_days.map(|days| now + Duration::days(days.into())),
+            last_used_at: None,
+        };
+        self.repository
+            .create_api_token(&token, &ApiToken::hash_secret(&secret))
+            .await?;
+        Ok(NewApiToken { token, secret })
+    }
+
+    async fn list_api_tokens(&self, user_id: Uuid) -> Result<Vec<ApiToken>, AppError> {
+        Ok(self.repository.list_api_tokens(user_id).await?)
+    }
+
+    async fn revoke_api_token(&self, user_id: Uuid, token_id: Uuid) -> Result<(), AppError> {
+        self.repository.delete_api_token(user_id, token_id).await?;
+        Ok(())
+    }
 
-        let pepper = env::var("PASSWORD_PEPPER").expect("A value of pepper is not set");
+    async fn authenticate_api_token(&self, secret: &str) -> Result<(User, ApiToken), AppError> {
+        let invalid = || AppError::unauthorized(Some("api token is invalid or expired"));
+        if !secret.starts_with(API_TOKEN_PREFIX) {
+            return Err(invalid());
+        }
+        let token = self
+            .repository
+            .use_api_token(&ApiToken::hash_secret(secret), Utc::now().naive_utc())
+            .await?
+            .ok_or_else(invalid)?;
+        let user = self.repository.get_user(token.user_id).await?;
+        Ok((user, token))
+    }
+}
 
-        let hash = match helper::hash_with_salt_pepper(password, &user.salt, &pepper) {
-            Ok(hash) => hash,
+impl Service {
+    // 利用者名とパスワードが合っていれば利用者を返す
+    async fn verify_password(
+        &self,
+        username: &String,
+        password: &str,
+    ) -> Result<Option<User>, AppError> {
+        let user = match self.repository.get_user_by_username(username).await {
+            Ok(user) => user,
+            Err(RepoError::NotFound(_)) => {
+                warn!("Login attempted for unknown user: {}", username);
+                // 利用者がいなくてもハッシュは計算し、応答までの時間から利用者名の有無が分からないようにする
+                helper::hash_password(password, &self.config.password_peppers.current)?;
+                return Ok(None);
+            }
+            // DBの障害を認証の失敗として数えないよう、そのまま返す
             Err(e) => {
-                error!("Failed to hash password: {}, error: {}", username, e);
-                return Err(AppError::internal(Some(
-                    "Internal error on hashing password",
-                )));
+                error!("Failed to get user: {}, error: {}", username, e);
+                return Err(e.into());
             }
         };
 
-        if hash != user.password {
-            warn!("Incorrect password for user: {}", username);
-            return Err(AppError::invalid(Some(
-                "The pair of username and password is incorrect",
-            )));
+        let peppers = &self.config.password_peppers;
+        match helper::verify_password(password, &user.password, peppers)? {
+            Verification::Valid { needs_rehash } => {
+                if needs_rehash {
+                    self.rehash_password(&user, password).await;
+                }
+                Ok(Some(user))
+            }
+            Verification::Invalid => {
+                warn!("Incorrect password for user: {}", username);
+                Ok(None)
+            }
         }
+    }
+
+    // パラメータやペッパーを変えた後のログインで、今の設定のハッシュに置き換える。失敗してもログインは続ける
+    async fn rehash_password(&self, user: &User, password: &str) {
+        let result = match helper::hash_password(password, &self.config.password_peppers.current) {
+            Ok(hash) => self
+                .repository
+                .update_password(user.id, &hash)
+                .await
+                .map_err(AppError::from),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            error!(
+                "Failed to rehash password for user: {}, error: {}",
+                user.name, e
+            );
+        }
+    }
 
+    // 失敗を数え、続けて失敗した回数が多ければしばらくログインを止める
+    async fn record_login_failure(&self, scopes: &[LoginScope]) -> Result<(), AppError> {
+        for scope in scopes {
+            let failures = self
+                .repository
+                .record_login_failure(scope, LOGIN_FAILURE_WINDOW)
+                .await?;
+            if let Some(lockout) = lockout_seconds(failures, allowed_failures(scope)) {
+                warn!(
+                    "Locking login for {:?} for {}s after {} failures",
+                    scope, lockout, failures
+                );
+                self.repository.lock_login(scope, lockout).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn issue_tokens(&self, session: SessionToken) -> Result<IssuedTokens, AppError> {
         let token = self
             .repository
-            .create_token(user.id, self.config.token_ttl)
+            .create_token(&session, self.config.token_ttl)
             .await?;
         let refresh_token = self
             .repository
-            .create_token(user.id, self.config.refresh_ttl)
+            .create_refresh_token(&session, self.config.refresh_ttl)
             .await?;
+        Ok(IssuedTokens {
+            token,
+            refresh_token,
+            csrf_token: session.csrf_token,
+        })
+    }
+}
 
-        Ok((token, refresh_token.access_token))
+fn validate_credentials(username: &str, password: &str) -> Result<(), AppError> {
+    let username_chars = username.chars().count();
+    if username_chars == 0 || username_chars > MAX_USERNAME_CHARS || username.trim() != username {
+        return Err(AppError::invalid(Some(&format!(
+            "username must be 1 to {MAX_USERNAME_CHARS} characters without surrounding spaces"
+        ))));
     }
+    let password_chars = password.chars().count();
+    if !(MIN_PASSWORD_CHARS..=MAX_PASSWORD_CHARS).contains(&password_chars) {
+        return Err(AppError::invalid(Some(&format!(
+            "password must be {MIN_PASSWORD_CHARS} to {MAX_PASSWORD_CHARS} characters"
+        ))));
+    }
+    Ok(())
+}
 
-    async fn logout(&self, token: Token) -> Result<(), AppError> {
-        self.repository.delete_token(token).await?;
-        Ok(())
+fn validate_api_token_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_API_TOKEN_NAME_CHARS {
+        return Err(AppError::invalid(Some(&format!(
+            "name must be 1 to {MAX_API_TOKEN_NAME_CHARS} characters"
+        ))));
     }
+    Ok(name.to_string())
+}
 
-    async fn get_user(&self, user_id: Uuid) -> Result<User, AppError> {
-        let user = self.repository.get_user(user_id).await?;
-        Ok(user)
+// 役割にない権限をトークンに付けることはできない
+fn parse_scopes(scopes: &[String], role: Role) -> Result<Vec<Permission>, AppError> {
+    let mut permissions = Vec::new();
+    for scope in scopes {
+        let permission = scope
+            .parse::<Permission>()
+            .map_err(|e| AppError::invalid(Some(&e)))?;
+        if !role.permits(permission) {
+            return Err(AppError::forbidden(Some(&format!(
+                "Your role does not allow the scope: {permission}"
+            ))));
+        }
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
     }
+    if permissions.is_empty() {
+        return Err(AppError::invalid(Some("scopes must not be empty")));
+    }
+    Ok(permissions)
+}
 
-    async fn fetch_user_id_by_token(&self, access_token: String) -> Result<Uuid, AppError> {
-        let user_id = self.repository.fetch_user_id_by_token(access_token).await;
+fn account_scope(username: &str, client: &ClientInfo) -> LoginScope {
+    LoginScope::Account {
+        username: username.to_string(),
+        ip: client.ip.clone(),
+    }
+}
 
-        if let Some(uid) = user_id {
-            return Ok(uid);
-        }
+fn login_scopes(username: &str, client: &ClientInfo) -> Vec<LoginScope> {
+    let mut scopes = vec![account_scope(username, client)];
+    if let Some(ip) = &client.ip {
+        scopes.push(LoginScope::Ip(ip.clone()));
+    }
+    scopes
+}
 
-        Err(AppError::not_found(Some("user_id is not found")))
+// ロックせずに許す失敗の回数。1つのIPを何人かで共有していることもあるので、IPの方を緩くする
+fn allowed_failures(scope: &LoginScope) -> u64 {
+    match scope {
+        LoginScope::Ip(_) => 20,
+        LoginScope::Account { .. } => 5,
+    }
+}
+
+// 許す回数に達したらLOGIN_LOCKOUT_BASE秒ロックし、その後は1回失敗するごとに倍にする
+fn lockout_seconds(failures: u64, allowed: u64) -> Option<u64> {
+    let exponent = u32::try_from(failures.checked_sub(allowed)?).unwrap_or(u32::MAX);
+    let lockout = 2u64
+        .checked_pow(exponent)
+        .and_then(|factor| factor.checked_mul(LOGIN_LOCKOUT_BASE))
+        .map_or(LOGIN_LOCKOUT_MAX, |secs| secs.min(LOGIN_LOCKOUT_MAX));
+    Some(lockout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lockout_doubles_up_to_the_limit() {
+        assert_eq!(None, lockout_seconds(4, 5));
+        assert_eq!(Some(30), lockout_seconds(5, 5));
+        assert_eq!(Some(60), lockout_seconds(6, 5));
+        assert_eq!(Some(1920), lockout_seconds(11, 5));
+        assert_eq!(Some(LOGIN_LOCKOUT_MAX), lockout_seconds(12, 5));
+        assert_eq!(Some(LOGIN_LOCKOUT_MAX), lockout_seconds(u64::MAX, 5));
+    }
+
+    #[test]
+    fn scopes_are_limited_to_the_role() {
+        let scopes = |scopes: &[&str]| scopes.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![Permission::WriteBlogs],
+            parse_scopes(&scopes(&["blogs:write", "blogs:write"]), Role::Editor).unwrap()
+        );
+        assert!(parse_scopes(&scopes(&["blogs:manage"]), Role::Editor).is_err());
+        assert!(parse_scopes(&scopes(&["blogs:write"]), Role::Viewer).is_err());
+        assert!(parse_scopes(&scopes(&["unknown"]), Role::Admin).is_err());
+        assert!(parse_scopes(&[], Role::Admin).is_err());
+    }
+
+    #[test]
+    fn credentials_are_validated() {
+        assert!(validate_credentials("admin", "correct horse battery").is_ok());
+        assert!(validate_credentials("", "correct horse battery").is_err());
+        assert!(validate_credentials(" admin", "correct horse battery").is_err());
+        assert!(validate_credentials(&"a".repeat(51), "correct horse battery").is_err());
+        assert!(validate_credentials("admin", "short").is_err());
+    }
+
+    #[test]
+    fn ip_is_counted_only_when_known() {
+        let client = ClientInfo {
+            user_agent: None,
+            ip: Some("192.0.2.1".to_string()),
+        };
+
+        assert_eq!(
+            vec![
+                LoginScope::Account {
+                    username: "admin".to_string(),
+                    ip: Some("192.0.2.1".to_string()),
+                },
+                LoginScope::Ip("192.0.2.1".to_string()),
+            ],
+            login_scopes("admin", &client)
+        );
+        assert_eq!(1, login_scopes("admin", &ClientInfo::default()).len());
+        // 他のIPからの失敗では、同じ利用者名でも別に数える
+        assert_ne!(
+            account_scope("admin", &client),
+            account_scope("admin", &ClientInfo::default())
+        );
     }
 }
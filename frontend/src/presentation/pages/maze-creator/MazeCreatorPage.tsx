@@ -20,8 +20,16 @@ import {
 import Grid from "@mui/material/Grid";
 import PlayArrowIcon from "@mui/icons-material/PlayArrow";
 import RestartAltIcon from "@mui/icons-material/RestartAlt";
+import DownloadIcon from "@mui/icons-material/Download";
 
-import { draw_maze, MazeType } from "../../../wasm";
+import {
+  draw_maze,
+  draw_solution,
+  generate_maze,
+  MazeType,
+  SolverType,
+  SvgStyle,
+} from "../../../wasm";
 
 type GridParams = {
   cellSize: number;
@@ -30,7 +38,54 @@ type GridParams = {
 };
 
 const DEFAULT_PARAMS: GridParams = { cellSize: 20, cols: 15, rows: 10 };
-type Mode = "random" | "single";
+type Mode =
+  | "random"
+  | "single"
+  | "backtracker"
+  | "prim"
+  | "wilson"
+  | "aldous-broder"
+  | "eller"
+  | "binary-tree"
+  | "sidewinder"
+  | "growing-tree"
+  | "division";
+
+const MODES: { value: Mode; label: string; mazeType: MazeType }[] = [
+  { value: "random", label: "Random", mazeType: MazeType.Random },
+  { value: "single", label: "Single-Stroke", mazeType: MazeType.SingleStroke },
+  {
+    value: "backtracker",
+    label: "Backtracker",
+    mazeType: MazeType.RecursiveBacktracker,
+  },
+  { value: "prim", label: "Prim", mazeType: MazeType.Prim },
+  { value: "wilson", label: "Wilson", mazeType: MazeType.Wilson },
+  {
+    value: "aldous-broder",
+    label: "Aldous-Broder",
+    mazeType: MazeType.AldousBroder,
+  },
+  { value: "eller", label: "Eller", mazeType: MazeType.Eller },
+  { value: "binary-tree", label: "Binary Tree", mazeType: MazeType.BinaryTree },
+  { value: "sidewinder", label: "Sidewinder", mazeType: MazeType.Sidewinder },
+  {
+    value: "growing-tree",
+    label: "Growing Tree",
+    mazeType: MazeType.GrowingTree,
+  },
+  {
+    value: "division",
+    label: "Recursive Division",
+    mazeType: MazeType.RecursiveDivision,
+  },
+];
+
+const randomSeed = (): bigint => {
+  const buf = new BigUint64Array(1);
+  crypto.getRandomValues(buf);
+  return buf[0];
+};
 
 function MazeCreatorPage() {
   const canvasRef = useRef<HTMLCanvasElement | null>(null);
@@ -39,6 +94,9 @@ function MazeCreatorPage() {
   const [autoPreview, setAutoPreview] = useState<boolean>(true);
   const [mode, setMode] = useState<Mode>("random");
   const [themeMode, setThemeMode] = useState<"light" | "dark">("light");
+  const [seed, setSeed] = useState<bigint>(randomSeed);
+  const [seedPinned, setSeedPinned] = useState<boolean>(false);
+  const [showSolution, setShowSolution] = useState<boolean>(false);
 
   const { widthPx, heightPx } = useMemo(
     () => ({
@@ -81,6 +139,7 @@ function MazeCreatorPage() {
   const ensureCanvasAndDraw = (
     { cellSize, cols, rows }: GridParams,
     m: Mode,
+    s: bigint,
   ) => {
     const canvas = canvasRef.current;
     if (!canvas) return;
@@ -89,30 +148,65 @@ function MazeCreatorPage() {
     const height = cellSize * rows;
     canvas.width = width;
     canvas.height = height;
-    if (m === "single") {
-      draw_maze(0, 0, rows, cols, cellSize, MazeType.SingleStroke);
-    } else {
-      draw_maze(0, 0, rows, cols, cellSize, MazeType.Random);
+    const mazeType =
+      MODES.find((mode) => mode.value === m)?.mazeType ?? MazeType.Random;
+    draw_maze(0, 0, rows, cols, cellSize, mazeType, s);
+    if (showSolution) {
+      draw_solution(
+        rows,
+        cols,
+        cellSize,
+        mazeType,
+        s,
+        0,
+        0,
+        rows - 1,
+        cols - 1,
+        SolverType.AStar,
+      );
     }
   };
 
   useEffect(() => {
     if (!autoPreview || !validation.valid) return;
-    const t = setTimeout(() => ensureCanvasAndDraw(params, mode), 300);
+    const t = setTimeout(() => ensureCanvasAndDraw(params, mode, seed), 300);
     return () => clearTimeout(t);
-  }, [autoPreview, validation.valid, params, mode]);
+  }, [autoPreview, validation.valid, params, mode, seed, showSolution]);
+
+  // シード値を固定していなければ、生成のたびに新しいシード値を使う
+  const nextSeed = () => (seedPinned ? seed : randomSeed());
 
   const onSubmit = (e: React.FormEvent) => {
     e.preventDefault();
     if (!validation.valid) return;
-    ensureCanvasAndDraw(params, mode);
+    const next = nextSeed();
+    setSeed(next);
+    ensureCanvasAndDraw(params, mode, next);
+  };
+
+  const onDownloadSvg = () => {
+    if (!validation.valid) return;
+    const { cellSize, cols, rows } = params;
+    const mazeType =
+      MODES.find((m) => m.value === mode)?.mazeType ?? MazeType.Random;
+    const svg = generate_maze(rows, cols, mazeType, seed).to_svg(
+      new SvgStyle().with_cell_size(cellSize),
+    );
+    const url = URL.createObjectURL(new Blob([svg], { type: "image/svg+xml" }));
+    const a = document.createElement("a");
+    a.href = url;
+    a.download = `maze-${rows}x${cols}-${seed}.svg`;
+    a.click();
+    URL.revokeObjectURL(url);
   };
 
   const onReset = () => {
     setParams(DEFAULT_PARAMS);
     setMode("random");
+    const next = nextSeed();
+    setSeed(next);
     if (autoPreview) {
-      ensureCanvasAndDraw(DEFAULT_PARAMS, "random");
+      ensureCanvasAndDraw(DEFAULT_PARAMS, "random", next);
     }
   };
 
@@ -136,9 +230,16 @@ function MazeCreatorPage() {
             <Typography variant="h5" gutterBottom>
               迷路グリッドの設定
             </Typography>
-            <Tabs value={mode} onChange={(_, v) => setMode(v)} sx={{ mb: 2 }}>
-              <Tab value="random" label="Random" />
-              <Tab value="single" label="Single-Stroke" />
+            <Tabs
+              value={mode}
+              onChange={(_, v) => setMode(v)}
+              variant="scrollable"
+              scrollButtons="auto"
+              sx={{ mb: 2 }}
+            >
+              {MODES.map((m) => (
+                <Tab key={m.value} value={m.value} label={m.label} />
+              ))}
             </Tabs>
             <Box component="form" onSubmit={onSubmit}>
               <Grid container spacing={2}>
@@ -188,6 +289,33 @@ function MazeCreatorPage() {
                   />
                 </Grid>
 
+                <Grid size={{ xs: 12, md: 8 }}>
+                  <TextField
+                    fullWidth
+                    label="シード値"
+                    value={seed.toString()}
+                    onChange={(e) => {
+                      if (/^\d+$/.test(e.target.value)) {
+                        setSeed(BigInt.asUintN(64, BigInt(e.target.value)));
+                        setSeedPinned(true);
+                      }
+                    }}
+                    helperText="同じシード値とサイズから同じ迷路が生成されます"
+                  />
+                </Grid>
+
+                <Grid size={{ xs: 12, md: 4 }}>
+                  <FormControlLabel
+                    control={
+                      <Switch
+                        checked={seedPinned}
+                        onChange={(e) => setSeedPinned(e.target.checked)}
+                      />
+                    }
+                    label="シード値を固定"
+                  />
+                </Grid>
+
                 <Grid size={{ xs: 12, md: 6 }}>
                   <Stack spacing={1} sx={{ px: 1 }}>
                     <Typography variant="body2" color="text.secondary">
@@ -261,6 +389,15 @@ function MazeCreatorPage() {
                         }
                         label="自動プレビュー"
                       />
+                      <FormControlLabel
+                        control={
+                          <Switch
+                            checked={showSolution}
+                            onChange={(e) => setShowSolution(e.target.checked)}
+                          />
+                        }
+                        label="解答を表示"
+                      />
                       <FormControlLabel
                         control={
                           <Switch
@@ -282,6 +419,15 @@ function MazeCreatorPage() {
                       >
                         生成して表示
                       </Button>
+                      <Button
+                        type="button"
+                        variant="outlined"
+                        startIcon={<DownloadIcon />}
+                        onClick={onDownloadSvg}
+                        disabled={!validation.valid}
+                      >
+                        SVG
+                      </Button>
                       <Button
                         type="button"
                         variant="outlined"
@@ -1,5 +0,0 @@
-pub mod grid;
-pub mod kruskal;
-pub mod shape;
-pub mod single_stroke;
-pub mod unionfind;
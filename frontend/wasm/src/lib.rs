@@ -1,10 +1,13 @@
-mod algo;
 mod dom;
 mod maze;
 use wasm_bindgen::prelude::*;
 
-use crate::algo::shape::Point;
-use crate::maze::{random_maze, single_stroke_maze};
+use maze_core::algo::grid;
+use maze_core::algo::shape::Point;
+use maze_core::maze::single_stroke_maze;
+
+use crate::maze::draw_shape;
+use crate::maze::model::Maze;
 
 #[wasm_bindgen(start)]
 pub fn start() {
@@ -14,9 +17,62 @@ pub fn start() {
 }
 
 #[wasm_bindgen]
+#[derive(Clone, Copy)]
 pub enum MazeType {
     Random,
     SingleStroke,
+    RecursiveBacktracker,
+    Prim,
+    Wilson,
+    AldousBroder,
+    Eller,
+    BinaryTree,
+    Sidewinder,
+    GrowingTree,
+    RecursiveDivision,
+}
+
+impl From<MazeType> for maze_core::MazeType {
+    fn from(maze: MazeType) -> Self {
+        match maze {
+            MazeType::Random => maze_core::MazeType::Random,
+            MazeType::SingleStroke => maze_core::MazeType::SingleStroke,
+            MazeType::RecursiveBacktracker => maze_core::MazeType::RecursiveBacktracker,
+            MazeType::Prim => maze_core::MazeType::Prim,
+            MazeType::Wilson => maze_core::MazeType::Wilson,
+            MazeType::AldousBroder => maze_core::MazeType::AldousBroder,
+            MazeType::Eller => maze_core::MazeType::Eller,
+            MazeType::BinaryTree => maze_core::MazeType::BinaryTree,
+            MazeType::Sidewinder => maze_core::MazeType::Sidewinder,
+            MazeType::GrowingTree => maze_core::MazeType::GrowingTree,
+            MazeType::RecursiveDivision => maze_core::MazeType::RecursiveDivision,
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub enum SolverType {
+    Bfs,
+    AStar,
+}
+
+impl From<SolverType> for maze_core::SolverType {
+    fn from(solver: SolverType) -> Self {
+        match solver {
+            SolverType::Bfs => maze_core::SolverType::Bfs,
+            SolverType::AStar => maze_core::SolverType::AStar,
+        }
+    }
+}
+
+const SOLUTION_COLOR: &str = "#e53935";
+
+#[wasm_bindgen]
+pub fn generate_maze(row: usize, col: usize, maze: MazeType, seed: u64) -> Result<Maze, JsError> {
+    maze_core::generate(row, col, maze.into(), seed)
+        .map(Maze::from)
+        .map_err(|e| JsError::new(&e.to_string()))
 }
 
 #[wasm_bindgen]
@@ -27,34 +83,97 @@ pub fn draw_maze(
     col: usize,
     space: f64,
     maze: MazeType,
+    seed: u64,
 ) {
-    let validated_input = match maze {
-        MazeType::Random => random_maze::validate(row, col, space),
-        MazeType::SingleStroke => single_stroke_maze::validate(row, col, space),
-    };
-
-    if !validated_input {
+    if !space.is_finite() || space <= 0.0 {
         return;
     }
+    let Ok(maze) = maze_core::generate(row, col, maze.into(), seed) else {
+        return;
+    };
 
     let ctx = dom::fetch_2d_context("canvas");
 
-    let from = Point::new(left_top_x, left_top_y);
     let width = space * col as f64;
     let height = space * row as f64;
 
-    ctx.clear_rect(from.x, from.y, width, height);
+    ctx.clear_rect(left_top_x, left_top_y, width, height);
 
     ctx.begin_path();
+    draw_shape::draw_maze(&ctx, &maze, space);
+    ctx.stroke();
+}
 
-    match maze {
-        MazeType::Random => {
-            ctx.rect(0.0, 0.0, width, height);
-            random_maze::draw_maze(&ctx, col, row, space)
-        }
-        MazeType::SingleStroke => {
-            single_stroke_maze::draw_maze(&ctx, col, row, space);
-        }
+// startからgoalまでの経路を、row * colの一次元インデックスの列として返す
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn solve_maze(
+    row: usize,
+    col: usize,
+    maze: MazeType,
+    seed: u64,
+    start_row: usize,
+    start_col: usize,
+    goal_row: usize,
+    goal_col: usize,
+    solver: SolverType,
+) -> Result<Vec<usize>, JsError> {
+    let maze = generate_maze(row, col, maze, seed)?;
+    let path = maze_core::find_path(
+        maze.inner(),
+        Point::new(start_row, start_col),
+        Point::new(goal_row, goal_col),
+        solver.into(),
+    )
+    .ok_or_else(|| JsError::new(&maze_core::MazeError::NoPath.to_string()))?;
+
+    Ok(path
+        .iter()
+        .map(|point| grid::index_2d_to_1d(point.x, point.y, col))
+        .collect())
+}
+
+// draw_mazeで描画した迷路に、startからgoalまでの経路を重ねて描画する
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn draw_solution(
+    row: usize,
+    col: usize,
+    space: f64,
+    maze: MazeType,
+    seed: u64,
+    start_row: usize,
+    start_col: usize,
+    goal_row: usize,
+    goal_col: usize,
+    solver: SolverType,
+) {
+    if !space.is_finite() || space <= 0.0 {
+        return;
+    }
+    let Ok(maze) = maze_core::generate(row, col, maze.into(), seed) else {
+        return;
     };
-    ctx.stroke();
+    let Some(path) = maze_core::find_path(
+        &maze,
+        Point::new(start_row, start_col),
+        Point::new(goal_row, goal_col),
+        solver.into(),
+    ) else {
+        log::warn!("no path found between start and goal");
+        return;
+    };
+
+    let ctx = dom::fetch_2d_context("canvas");
+    draw_shape::draw_path(&ctx, &path, space, SOLUTION_COLOR);
+}
+
+// 一筆書き迷路を左上のマスから辿る順番を、row * colの一次元インデックスの列として返す
+#[wasm_bindgen]
+pub fn single_stroke_order(row: usize, col: usize, seed: u64) -> Result<Vec<usize>, JsError> {
+    let maze = generate_maze(row, col, MazeType::SingleStroke, seed)?;
+    Ok(single_stroke_maze::stroke_order(maze.inner())
+        .iter()
+        .map(|point| grid::index_2d_to_1d(point.x, point.y, col))
+        .collect())
 }
@@ -1,6 +1,7 @@
 use web_sys::CanvasRenderingContext2d;
 
-use crate::algo::shape::Point;
+use maze_core::Maze;
+use maze_core::algo::shape::{Line, Point};
 
 use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
@@ -17,42 +18,38 @@ pub fn set_line_between_grid(
     to: Point<usize>,
     space: f64,
 ) {
-    let from = Point::new(from.x as f64 * space, from.y as f64 * space);
-    let to = Point::new(to.x as f64 * space, to.y as f64 * space);
-    ctx.move_to(from.y, from.x);
-    ctx.line_to(to.y, to.x);
+    let line = Line::from_grid(&from, &to, space, 0.0);
+    ctx.move_to(line.from.x, line.from.y);
+    ctx.line_to(line.to.x, line.to.y);
 }
 
-pub fn set_grid_boundary(
-    ctx: &CanvasRenderingContext2d,
-    from: Point<usize>,
-    to: Point<usize>,
-    space: f64,
-) {
-    if from.x == to.x {
-        set_line_between_grid(
-            ctx,
-            Point::new(from.x, to.y),
-            Point::new(to.x + 1, to.y),
-            space,
-        );
+pub fn draw_maze(ctx: &CanvasRenderingContext2d, maze: &Maze, space: f64) {
+    for (from, to) in maze.wall_segments() {
+        set_line_between_grid(ctx, from, to, space);
     }
-    if from.y == to.y {
+}
+
+// マスの中心を結ぶ経路をcolorで描画する
+pub fn draw_path(ctx: &CanvasRenderingContext2d, path: &[Point<usize>], space: f64, color: &str) {
+    ctx.save();
+    ctx.set_stroke_style_str(color);
+    ctx.set_line_width(space / 4.0);
+    ctx.set_line_cap("round");
+    ctx.set_line_join("round");
+    // 格子点をマスの中心に合わせる
+    if ctx.translate(space / 2.0, space / 2.0).is_err() {
+        log::warn!("failed to translate canvas context");
+    }
+
+    ctx.begin_path();
+    for pair in path.windows(2) {
         set_line_between_grid(
             ctx,
-            Point::new(to.x, from.y),
-            Point::new(to.x, to.y + 1),
+            Point::new(pair[0].x, pair[0].y),
+            Point::new(pair[1].x, pair[1].y),
             space,
         );
     }
-}
-
-pub fn draw_lines(
-    ctx: &CanvasRenderingContext2d,
-    edges: Vec<(Point<usize>, Point<usize>)>,
-    space: f64,
-) {
-    for (from, to) in edges {
-        set_line_between_grid(ctx, from, to, space);
-    }
+    ctx.stroke();
+    ctx.restore();
 }
@@ -1,3 +1,3 @@
 pub mod draw_shape;
-pub mod random_maze;
-pub mod single_stroke_maze;
+pub mod model;
+pub mod svg;
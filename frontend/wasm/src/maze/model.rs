@@ -0,0 +1,78 @@
+use maze_core::maze::svg;
+use wasm_bindgen::prelude::*;
+
+use crate::maze::svg::SvgStyle;
+
+// セルの壁を表すビット
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Wall {
+    Top = 1,
+    Right = 2,
+    Bottom = 4,
+    Left = 8,
+}
+
+impl From<Wall> for maze_core::Wall {
+    fn from(wall: Wall) -> Self {
+        match wall {
+            Wall::Top => maze_core::Wall::Top,
+            Wall::Right => maze_core::Wall::Right,
+            Wall::Bottom => maze_core::Wall::Bottom,
+            Wall::Left => maze_core::Wall::Left,
+        }
+    }
+}
+
+// maze_coreの迷路をJSから扱えるようにしたもの
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Maze {
+    inner: maze_core::Maze,
+}
+
+impl From<maze_core::Maze> for Maze {
+    fn from(inner: maze_core::Maze) -> Self {
+        Maze { inner }
+    }
+}
+
+impl Maze {
+    pub fn inner(&self) -> &maze_core::Maze {
+        &self.inner
+    }
+}
+
+#[wasm_bindgen]
+impl Maze {
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.inner.rows()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cols(&self) -> usize {
+        self.inner.cols()
+    }
+
+    // 行優先で並べた全セルの壁のビットマスク
+    pub fn walls(&self) -> Vec<u8> {
+        self.inner.walls().to_vec()
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> u8 {
+        self.inner.cell(row, col)
+    }
+
+    pub fn has_wall(&self, row: usize, col: usize, wall: Wall) -> bool {
+        self.inner.has_wall(row, col, wall.into())
+    }
+
+    pub fn to_svg(&self, style: &SvgStyle) -> Result<String, JsError> {
+        if !style.inner().validate() {
+            return Err(JsError::new("invalid svg style"));
+        }
+        Ok(svg::render(&self.inner, style.inner()))
+    }
+}
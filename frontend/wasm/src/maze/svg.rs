@@ -0,0 +1,54 @@
+use maze_core::RenderStyle;
+use wasm_bindgen::prelude::*;
+
+// SVGとして書き出す際の見た目の設定
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct SvgStyle {
+    inner: RenderStyle,
+}
+
+impl SvgStyle {
+    pub fn inner(&self) -> &RenderStyle {
+        &self.inner
+    }
+}
+
+#[wasm_bindgen]
+impl SvgStyle {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        SvgStyle::default()
+    }
+
+    pub fn with_cell_size(self, cell_size: f64) -> Self {
+        SvgStyle {
+            inner: self.inner.with_cell_size(cell_size),
+        }
+    }
+
+    pub fn with_stroke_width(self, stroke_width: f64) -> Self {
+        SvgStyle {
+            inner: self.inner.with_stroke_width(stroke_width),
+        }
+    }
+
+    pub fn with_stroke_color(self, color: &str) -> Self {
+        SvgStyle {
+            inner: self.inner.with_stroke_color(color),
+        }
+    }
+
+    // Noneを渡すと背景を透過にする
+    pub fn with_background_color(self, color: Option<String>) -> Self {
+        SvgStyle {
+            inner: self.inner.with_background_color(color),
+        }
+    }
+
+    pub fn with_margin(self, margin: f64) -> Self {
+        SvgStyle {
+            inner: self.inner.with_margin(margin),
+        }
+    }
+}
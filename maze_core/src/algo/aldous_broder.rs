@@ -0,0 +1,36 @@
+use rand::prelude::*;
+
+use crate::algo::generator::{MazeGenerator, adjacent_cells, to_passage};
+use crate::algo::rng::MazeRng;
+use crate::algo::shape::Point;
+
+// 全てのマスを訪れるまでランダムウォークし、初めて訪れたマスへの辺を通路にする
+pub struct AldousBroder;
+
+impl MazeGenerator for AldousBroder {
+    fn passages(
+        &self,
+        width: usize,
+        height: usize,
+        rng: &mut MazeRng,
+    ) -> Vec<(Point<usize>, Point<usize>)> {
+        let size = width * height;
+        let mut passages = Vec::with_capacity(size.saturating_sub(1));
+        let mut visited = vec![false; size];
+        let mut cell = rng.random_range(0..size);
+        visited[cell] = true;
+        let mut remaining = size - 1;
+
+        while remaining > 0 {
+            let adjacent = adjacent_cells(cell, width, height);
+            let next = adjacent[rng.random_range(0..adjacent.len())];
+            if !visited[next] {
+                visited[next] = true;
+                passages.push(to_passage(cell, next, width));
+                remaining -= 1;
+            }
+            cell = next;
+        }
+        passages
+    }
+}
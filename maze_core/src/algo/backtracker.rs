@@ -0,0 +1,42 @@
+use rand::prelude::*;
+
+use crate::algo::generator::{MazeGenerator, adjacent_cells, to_passage};
+use crate::algo::rng::MazeRng;
+use crate::algo::shape::Point;
+
+// 深さ優先探索で未訪問のマスへ進み、行き止まりで引き返す。長い通路ができやすい
+pub struct RecursiveBacktracker;
+
+impl MazeGenerator for RecursiveBacktracker {
+    fn passages(
+        &self,
+        width: usize,
+        height: usize,
+        rng: &mut MazeRng,
+    ) -> Vec<(Point<usize>, Point<usize>)> {
+        let size = width * height;
+        let mut passages = Vec::with_capacity(size.saturating_sub(1));
+        let mut visited = vec![false; size];
+        let start = rng.random_range(0..size);
+        visited[start] = true;
+        let mut stack = vec![start];
+
+        while let Some(&cell) = stack.last() {
+            let unvisited: Vec<usize> = adjacent_cells(cell, width, height)
+                .into_iter()
+                .filter(|&next| !visited[next])
+                .collect();
+            match unvisited.choose(rng) {
+                Some(&next) => {
+                    visited[next] = true;
+                    passages.push(to_passage(cell, next, width));
+                    stack.push(next);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+        passages
+    }
+}
@@ -0,0 +1,49 @@
+use rand::prelude::*;
+
+use crate::algo::generator::{MazeGenerator, to_passage};
+use crate::algo::rng::MazeRng;
+use crate::algo::shape::Point;
+
+// 各マスから上か左のどちらかへ通路を伸ばす。上端と左端が一直線の通路になる
+pub struct BinaryTree;
+
+impl MazeGenerator for BinaryTree {
+    fn passages(
+        &self,
+        width: usize,
+        height: usize,
+        rng: &mut MazeRng,
+    ) -> Vec<(Point<usize>, Point<usize>)> {
+        let mut passages = Vec::with_capacity((width * height).saturating_sub(1));
+        for cell in 1..width * height {
+            let (row, col) = (cell / width, cell % width);
+            let mut candidates = Vec::with_capacity(2);
+            if row > 0 {
+                candidates.push(cell - width);
+            }
+            if col > 0 {
+                candidates.push(cell - 1);
+            }
+            if let Some(&next) = candidates.choose(rng) {
+                passages.push(to_passage(cell, next, width));
+            }
+        }
+        passages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::rng;
+
+    #[test]
+    fn top_row_is_single_corridor() {
+        let width = 12;
+        let passages = BinaryTree.passages(width, 8, &mut rng::seeded(0));
+
+        for col in 1..width {
+            assert!(passages.contains(&(Point::new(0, col), Point::new(0, col - 1))));
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use rand::prelude::*;
+
+use crate::algo::generator::{MazeGenerator, to_passage};
+use crate::algo::grid::index_2d_to_1d;
+use crate::algo::rng::MazeRng;
+use crate::algo::shape::Point;
+
+// 一行ずつ、各マスがどの集合に属するかだけを保持して迷路を作る
+pub struct Eller;
+
+impl MazeGenerator for Eller {
+    fn passages(
+        &self,
+        width: usize,
+        height: usize,
+        rng: &mut MazeRng,
+    ) -> Vec<(Point<usize>, Point<usize>)> {
+        let mut passages = Vec::with_capacity((width * height).saturating_sub(1));
+        let mut sets: Vec<usize> = (0..width).collect();
+        let mut next_set = width;
+
+        for row in 0..height {
+            let last_row = row + 1 == height;
+
+            // 異なる集合に属する左右のマスを無作為に繋ぐ。最終行では全て繋ぐ
+            for col in 0..width.saturating_sub(1) {
+                if sets[col] != sets[col + 1] && (last_row || rng.random_bool(0.5)) {
+                    passages.push(to_passage(
+                        index_2d_to_1d(row, col, width),
+                        index_2d_to_1d(row, col + 1, width),
+                        width,
+                    ));
+                    let (merged, into) = (sets[col + 1], sets[col]);
+                    sets.iter_mut()
+                        .filter(|set| **set == merged)
+                        .for_each(|set| *set = into);
+                }
+            }
+            if last_row {
+                break;
+            }
+
+            // 各集合から少なくとも一つのマスを下の行へ繋ぐ
+            let mut columns_by_set: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+            for (col, set) in sets.iter().enumerate() {
+                columns_by_set.entry(*set).or_default().push(col);
+            }
+            let mut below = vec![None; width];
+            for (set, mut columns) in columns_by_set {
+                columns.shuffle(rng);
+                let count = rng.random_range(1..=columns.len());
+                for &col in &columns[..count] {
+                    passages.push(to_passage(
+                        index_2d_to_1d(row, col, width),
+                        index_2d_to_1d(row + 1, col, width),
+                        width,
+                    ));
+                    below[col] = Some(set);
+                }
+            }
+            sets = below
+                .into_iter()
+                .map(|set| {
+                    set.unwrap_or_else(|| {
+                        next_set += 1;
+                        next_set - 1
+                    })
+                })
+                .collect();
+        }
+        passages
+    }
+}
@@ -0,0 +1,114 @@
+use crate::algo::grid;
+use crate::algo::rng::MazeRng;
+use crate::algo::shape::Point;
+
+// 縦heightマス・横widthマスのグリッドから、通路となる隣接マスの組を選んで迷路を作るアルゴリズム
+pub trait MazeGenerator {
+    fn passages(
+        &self,
+        width: usize,
+        height: usize,
+        rng: &mut MazeRng,
+    ) -> Vec<(Point<usize>, Point<usize>)>;
+}
+
+// 一次元インデックスで表されたマスに隣接するマスを返す
+pub fn adjacent_cells(cell: usize, width: usize, height: usize) -> Vec<usize> {
+    let (row, col) = grid::index_1d_to_2d(cell, width);
+    let mut cells = Vec::with_capacity(4);
+    if row > 0 {
+        cells.push(cell - width);
+    }
+    if col + 1 < width {
+        cells.push(cell + 1);
+    }
+    if row + 1 < height {
+        cells.push(cell + width);
+    }
+    if col > 0 {
+        cells.push(cell - 1);
+    }
+    cells
+}
+
+// 一次元インデックスで表された2マスを、Pointの組に変換する
+pub fn to_passage(from: usize, to: usize, width: usize) -> (Point<usize>, Point<usize>) {
+    (
+        Point::from_1d_index(from, width),
+        Point::from_1d_index(to, width),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use crate::algo::aldous_broder::AldousBroder;
+    use crate::algo::backtracker::RecursiveBacktracker;
+    use crate::algo::binary_tree::BinaryTree;
+    use crate::algo::eller::Eller;
+    use crate::algo::growing_tree::GrowingTree;
+    use crate::algo::prim::Prim;
+    use crate::algo::recursive_division::RecursiveDivision;
+    use crate::algo::rng;
+    use crate::algo::sidewinder::Sidewinder;
+    use crate::algo::unionfind::UnionFind;
+    use crate::algo::wilson::Wilson;
+
+    fn generators() -> Vec<Box<dyn MazeGenerator>> {
+        vec![
+            Box::new(RecursiveBacktracker),
+            Box::new(Prim),
+            Box::new(Wilson),
+            Box::new(AldousBroder),
+            Box::new(Eller),
+            Box::new(BinaryTree),
+            Box::new(Sidewinder),
+            Box::new(GrowingTree::default()),
+            Box::new(GrowingTree {
+                newest_probability: 0.0,
+            }),
+            Box::new(RecursiveDivision),
+        ]
+    }
+
+    #[rstest]
+    #[case(1, 1)]
+    #[case(1, 7)]
+    #[case(9, 1)]
+    #[case(10, 20)]
+    #[case(33, 17)]
+    fn every_generator_creates_spanning_tree(#[case] width: usize, #[case] height: usize) {
+        for generator in generators() {
+            let passages = generator.passages(width, height, &mut rng::seeded(11));
+
+            assert_eq!(width * height - 1, passages.len());
+
+            let mut unionfind = UnionFind::new(width * height);
+            for (from, to) in passages {
+                assert_eq!(1, from.x.abs_diff(to.x) + from.y.abs_diff(to.y));
+                assert!(from.x < height && from.y < width);
+                assert!(to.x < height && to.y < width);
+                assert!(!unionfind.same(from.flatten(width), to.flatten(width)));
+                unionfind.merge(from.flatten(width), to.flatten(width));
+            }
+        }
+    }
+
+    #[test]
+    fn every_generator_is_reproducible_by_seed() {
+        for generator in generators() {
+            let first = generator.passages(15, 12, &mut rng::seeded(3));
+            let second = generator.passages(15, 12, &mut rng::seeded(3));
+
+            assert_eq!(first, second);
+        }
+    }
+
+    #[test]
+    fn adjacent_cells_in_corner() {
+        assert_eq!(vec![1, 3], adjacent_cells(0, 3, 2));
+        assert_eq!(vec![2, 4], adjacent_cells(5, 3, 2));
+    }
+}
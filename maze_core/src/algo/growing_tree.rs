@@ -0,0 +1,60 @@
+use rand::prelude::*;
+
+use crate::algo::generator::{MazeGenerator, adjacent_cells, to_passage};
+use crate::algo::rng::MazeRng;
+use crate::algo::shape::Point;
+
+// 作成中のマスの一覧から次に広げるマスを選んで迷路を作る
+// newest_probabilityが1.0なら深さ優先探索、0.0ならPrim法と同じ性質になる
+pub struct GrowingTree {
+    pub newest_probability: f64,
+}
+
+impl Default for GrowingTree {
+    fn default() -> Self {
+        GrowingTree {
+            newest_probability: 0.5,
+        }
+    }
+}
+
+impl MazeGenerator for GrowingTree {
+    fn passages(
+        &self,
+        width: usize,
+        height: usize,
+        rng: &mut MazeRng,
+    ) -> Vec<(Point<usize>, Point<usize>)> {
+        let size = width * height;
+        let newest_probability = self.newest_probability.clamp(0.0, 1.0);
+        let mut passages = Vec::with_capacity(size.saturating_sub(1));
+        let mut visited = vec![false; size];
+        let start = rng.random_range(0..size);
+        visited[start] = true;
+        let mut active = vec![start];
+
+        while !active.is_empty() {
+            let idx = if rng.random_bool(newest_probability) {
+                active.len() - 1
+            } else {
+                rng.random_range(0..active.len())
+            };
+            let cell = active[idx];
+            let unvisited: Vec<usize> = adjacent_cells(cell, width, height)
+                .into_iter()
+                .filter(|&next| !visited[next])
+                .collect();
+            match unvisited.choose(rng) {
+                Some(&next) => {
+                    visited[next] = true;
+                    passages.push(to_passage(cell, next, width));
+                    active.push(next);
+                }
+                None => {
+                    active.remove(idx);
+                }
+            }
+        }
+        passages
+    }
+}
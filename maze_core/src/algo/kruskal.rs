@@ -1,7 +1,7 @@
-use rand::SeedableRng;
 use rand::prelude::*;
 
 use crate::algo::grid::grid_edges;
+use crate::algo::rng::MazeRng;
 use crate::algo::shape::Point;
 use crate::algo::unionfind::UnionFind;
 
@@ -16,11 +16,12 @@ pub fn extract_maze_edges_by_kruskal(
     height: usize,
     step: usize,
     result: KruskalResultEdge,
+    rng: &mut MazeRng,
 ) -> Vec<(Point<usize>, Point<usize>)> {
     if width <= step && height <= step {
         return Vec::new();
     }
-    let edges = arrange_random_edges(width, height, step);
+    let edges = arrange_random_edges(width, height, step, rng);
     let result_edges = kruskal(width * height, edges, result);
 
     result_edges
@@ -59,12 +60,14 @@ fn kruskal(
 }
 
 // ランダムな順番のgridグラフの辺を返す
-fn arrange_random_edges(width: usize, height: usize, step: usize) -> Vec<(usize, usize)> {
+fn arrange_random_edges(
+    width: usize,
+    height: usize,
+    step: usize,
+    rng: &mut MazeRng,
+) -> Vec<(usize, usize)> {
     let mut edges = grid_edges(width, height, step);
-    let mut random_bytes = [0u8; 8];
-    getrandom::getrandom(&mut random_bytes).unwrap();
-    let mut rng = rand::rngs::SmallRng::seed_from_u64(u64::from_ne_bytes(random_bytes));
-    edges.shuffle(&mut rng);
+    edges.shuffle(rng);
     edges
 }
 
@@ -74,6 +77,7 @@ mod tests {
     use std::collections::HashSet;
 
     use super::*;
+    use crate::algo::rng;
 
     #[test]
     fn create_minimum_spanning_tree() {
@@ -186,13 +190,65 @@ mod tests {
         assert_eq!(0, nodes.len());
     }
 
+    #[rstest]
+    #[case(10, 20, 1, 42)]
+    #[case(50, 100, 7, 7)]
+    fn same_seed_creates_same_maze(
+        #[case] width: usize,
+        #[case] height: usize,
+        #[case] step: usize,
+        #[case] seed: u64,
+    ) {
+        let first = extract_maze_edges_by_kruskal(
+            width,
+            height,
+            step,
+            KruskalResultEdge::Unused,
+            &mut rng::seeded(seed),
+        );
+        let second = extract_maze_edges_by_kruskal(
+            width,
+            height,
+            step,
+            KruskalResultEdge::Unused,
+            &mut rng::seeded(seed),
+        );
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn different_seed_creates_different_maze() {
+        let first = extract_maze_edges_by_kruskal(
+            30,
+            30,
+            1,
+            KruskalResultEdge::Unused,
+            &mut rng::seeded(1),
+        );
+        let second = extract_maze_edges_by_kruskal(
+            30,
+            30,
+            1,
+            KruskalResultEdge::Unused,
+            &mut rng::seeded(2),
+        );
+
+        assert_ne!(first, second);
+    }
+
     fn create_spanning_tree_from_unused_edges(
         width: usize,
         height: usize,
         step: usize,
     ) -> HashSet<usize> {
-        let unused_edges =
-            extract_maze_edges_by_kruskal(width, height, step, KruskalResultEdge::Unused);
+        let unused_edges = extract_maze_edges_by_kruskal(
+            width,
+            height,
+            step,
+            KruskalResultEdge::Unused,
+            &mut rng::seeded(0),
+        );
 
         // fetch node in spanning tree
         let mut set: HashSet<(Point<usize>, Point<usize>)> = HashSet::new();
@@ -223,8 +279,13 @@ mod tests {
         height: usize,
         step: usize,
     ) -> HashSet<Point<usize>> {
-        let used_edges =
-            extract_maze_edges_by_kruskal(width, height, step, KruskalResultEdge::Used);
+        let used_edges = extract_maze_edges_by_kruskal(
+            width,
+            height,
+            step,
+            KruskalResultEdge::Used,
+            &mut rng::seeded(0),
+        );
 
         // fetch node in spanning tree
         let mut nodes: HashSet<Point<usize>> = HashSet::new();
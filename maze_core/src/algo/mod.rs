@@ -0,0 +1,17 @@
+pub mod aldous_broder;
+pub mod backtracker;
+pub mod binary_tree;
+pub mod eller;
+pub mod generator;
+pub mod grid;
+pub mod growing_tree;
+pub mod kruskal;
+pub mod prim;
+pub mod recursive_division;
+pub mod rng;
+pub mod shape;
+pub mod sidewinder;
+pub mod single_stroke;
+pub mod solver;
+pub mod unionfind;
+pub mod wilson;
@@ -0,0 +1,42 @@
+use rand::prelude::*;
+
+use crate::algo::generator::{MazeGenerator, adjacent_cells, to_passage};
+use crate::algo::rng::MazeRng;
+use crate::algo::shape::Point;
+
+// 迷路に含まれるマスから伸びる辺を無作為に選んで広げる。短い行き止まりが多くなる
+pub struct Prim;
+
+impl MazeGenerator for Prim {
+    fn passages(
+        &self,
+        width: usize,
+        height: usize,
+        rng: &mut MazeRng,
+    ) -> Vec<(Point<usize>, Point<usize>)> {
+        let size = width * height;
+        let mut passages = Vec::with_capacity(size.saturating_sub(1));
+        let mut visited = vec![false; size];
+        let start = rng.random_range(0..size);
+        visited[start] = true;
+        let mut frontier: Vec<(usize, usize)> = adjacent_cells(start, width, height)
+            .into_iter()
+            .map(|next| (start, next))
+            .collect();
+
+        while !frontier.is_empty() {
+            let (cell, next) = frontier.swap_remove(rng.random_range(0..frontier.len()));
+            if visited[next] {
+                continue;
+            }
+            visited[next] = true;
+            passages.push(to_passage(cell, next, width));
+            for adjacent in adjacent_cells(next, width, height) {
+                if !visited[adjacent] {
+                    frontier.push((next, adjacent));
+                }
+            }
+        }
+        passages
+    }
+}
@@ -0,0 +1,103 @@
+use rand::prelude::*;
+
+use crate::algo::generator::{MazeGenerator, to_passage};
+use crate::algo::rng::MazeRng;
+use crate::algo::shape::Point;
+
+// 壁のない領域を、一箇所だけ穴の空いた壁で再帰的に分割していく。長い直線の壁ができる
+pub struct RecursiveDivision;
+
+// 分割する領域。左上のマスと縦横のマス数
+struct Region {
+    row: usize,
+    col: usize,
+    height: usize,
+    width: usize,
+}
+
+impl MazeGenerator for RecursiveDivision {
+    fn passages(
+        &self,
+        width: usize,
+        height: usize,
+        rng: &mut MazeRng,
+    ) -> Vec<(Point<usize>, Point<usize>)> {
+        let size = width * height;
+        // 各マスの右と下が通行可能かどうか
+        let mut open_right = vec![true; size];
+        let mut open_down = vec![true; size];
+
+        let mut regions = vec![Region {
+            row: 0,
+            col: 0,
+            height,
+            width,
+        }];
+        while let Some(region) = regions.pop() {
+            if region.height < 2 && region.width < 2 {
+                continue;
+            }
+            let horizontal = match region.height.cmp(&region.width) {
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Equal => rng.random_bool(0.5),
+            };
+
+            if horizontal {
+                // wall_rowの下に壁を作る
+                let wall_row = region.row + rng.random_range(0..region.height - 1);
+                let hole = region.col + rng.random_range(0..region.width);
+                for col in region.col..region.col + region.width {
+                    if col != hole {
+                        open_down[wall_row * width + col] = false;
+                    }
+                }
+                regions.push(Region {
+                    row: region.row,
+                    col: region.col,
+                    height: wall_row - region.row + 1,
+                    width: region.width,
+                });
+                regions.push(Region {
+                    row: wall_row + 1,
+                    col: region.col,
+                    height: region.row + region.height - wall_row - 1,
+                    width: region.width,
+                });
+            } else {
+                // wall_colの右に壁を作る
+                let wall_col = region.col + rng.random_range(0..region.width - 1);
+                let hole = region.row + rng.random_range(0..region.height);
+                for row in region.row..region.row + region.height {
+                    if row != hole {
+                        open_right[row * width + wall_col] = false;
+                    }
+                }
+                regions.push(Region {
+                    row: region.row,
+                    col: region.col,
+                    height: region.height,
+                    width: wall_col - region.col + 1,
+                });
+                regions.push(Region {
+                    row: region.row,
+                    col: wall_col + 1,
+                    height: region.height,
+                    width: region.col + region.width - wall_col - 1,
+                });
+            }
+        }
+
+        let mut passages = Vec::with_capacity(size.saturating_sub(1));
+        for cell in 0..size {
+            let (row, col) = (cell / width, cell % width);
+            if col + 1 < width && open_right[cell] {
+                passages.push(to_passage(cell, cell + 1, width));
+            }
+            if row + 1 < height && open_down[cell] {
+                passages.push(to_passage(cell, cell + width, width));
+            }
+        }
+        passages
+    }
+}
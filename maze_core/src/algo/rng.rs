@@ -0,0 +1,9 @@
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+// 迷路生成で共有する乱数生成器。同じseedからは同じ迷路が生成される
+pub type MazeRng = SmallRng;
+
+pub fn seeded(seed: u64) -> MazeRng {
+    SmallRng::seed_from_u64(seed)
+}
@@ -1,8 +1,6 @@
 use std::ops::{Add, Mul, Sub};
 
-use web_sys::CanvasRenderingContext2d;
-
-#[derive(Hash, Eq, PartialEq)]
+#[derive(Debug, Hash, Eq, PartialEq)]
 pub struct Point<T> {
     pub x: T,
     pub y: T,
@@ -57,8 +55,22 @@ impl<T> Line<T> {
 }
 
 impl Line<f64> {
-    pub fn draw(&self, ctx: &CanvasRenderingContext2d) {
-        ctx.move_to(self.from.x, self.from.y);
-        ctx.line_to(self.to.x, self.to.y);
+    // 格子点fromからtoまでの線分を、間隔space・余白marginの座標系に変換する
+    pub fn from_grid(from: &Point<usize>, to: &Point<usize>, space: f64, margin: f64) -> Self {
+        Line::new(
+            Point::new(
+                from.y as f64 * space + margin,
+                from.x as f64 * space + margin,
+            ),
+            Point::new(to.y as f64 * space + margin, to.x as f64 * space + margin),
+        )
+    }
+
+    // SVGのpath要素のd属性に使うコマンド
+    pub fn to_path_command(&self) -> String {
+        format!(
+            "M{} {}L{} {}",
+            self.from.x, self.from.y, self.to.x, self.to.y
+        )
     }
 }
@@ -0,0 +1,44 @@
+use rand::prelude::*;
+
+use crate::algo::generator::{MazeGenerator, to_passage};
+use crate::algo::grid::index_2d_to_1d;
+use crate::algo::rng::MazeRng;
+use crate::algo::shape::Point;
+
+// 一行ずつ右へ通路を伸ばし、区切ったまとまりから一箇所だけ上の行へ繋ぐ
+pub struct Sidewinder;
+
+impl MazeGenerator for Sidewinder {
+    fn passages(
+        &self,
+        width: usize,
+        height: usize,
+        rng: &mut MazeRng,
+    ) -> Vec<(Point<usize>, Point<usize>)> {
+        let mut passages = Vec::with_capacity((width * height).saturating_sub(1));
+        for row in 0..height {
+            let mut run_start = 0;
+            for col in 0..width {
+                let close_run = col + 1 == width || (row > 0 && rng.random_bool(0.5));
+                if close_run {
+                    if row > 0 {
+                        let up = rng.random_range(run_start..=col);
+                        passages.push(to_passage(
+                            index_2d_to_1d(row, up, width),
+                            index_2d_to_1d(row - 1, up, width),
+                            width,
+                        ));
+                    }
+                    run_start = col + 1;
+                } else {
+                    passages.push(to_passage(
+                        index_2d_to_1d(row, col, width),
+                        index_2d_to_1d(row, col + 1, width),
+                        width,
+                    ));
+                }
+            }
+        }
+        passages
+    }
+}
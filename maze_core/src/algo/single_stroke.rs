@@ -1,4 +1,5 @@
-use crate::algo::{kruskal, shape::Point};
+use crate::algo::{kruskal, rng::MazeRng, shape::Point};
+use rand::Rng;
 use std::{collections::VecDeque, mem::swap};
 
 #[derive(Clone, Copy)]
@@ -12,6 +13,7 @@ const BIG_NUM: usize = 100000;
 pub fn single_stroke_maze(
     mut width: usize,
     mut height: usize,
+    rng: &mut MazeRng,
 ) -> Vec<(Point<usize>, Point<usize>)> {
     width -= 1;
     height -= 1;
@@ -25,17 +27,18 @@ pub fn single_stroke_maze(
         height - (height + 1) % 2,
         step,
         kruskal::KruskalResultEdge::Used,
+        rng,
     );
-    let modulo = get_random_bool();
+    let modulo = get_random_bool(rng);
     let offset = match modulo {
         false => Offset::Zero,
         true => Offset::One,
     };
 
     if width % 2 == 0 {
-        shift_horizontal(&mut used_grid_line, width, offset);
+        shift_horizontal(&mut used_grid_line, width, offset, rng);
     } else if height % 2 == 0 {
-        shift_vertical(&mut used_grid_line, height, offset);
+        shift_vertical(&mut used_grid_line, height, offset, rng);
     }
 
     let mut used_grid_edges = divide_edges(&mut used_grid_line, step);
@@ -118,11 +121,16 @@ fn divide_edges(
 }
 
 // 二次元座標中に存在する線分を、水平方向に+1移動させる
-fn shift_horizontal(edges: &mut Vec<(Point<usize>, Point<usize>)>, width: usize, offset: Offset) {
+fn shift_horizontal(
+    edges: &mut Vec<(Point<usize>, Point<usize>)>,
+    width: usize,
+    offset: Offset,
+    rng: &mut MazeRng,
+) {
     match offset {
         Offset::Zero => {
             edges.iter_mut().for_each(|(x, y)| {
-                if x.y == y.y && x.y == width - 2 && get_random_bool() {
+                if x.y == y.y && x.y == width - 2 && get_random_bool(rng) {
                     x.shift_horizontal();
                     y.shift_horizontal();
                 }
@@ -130,7 +138,7 @@ fn shift_horizontal(edges: &mut Vec<(Point<usize>, Point<usize>)>, width: usize,
         }
         Offset::One => {
             edges.iter_mut().for_each(|(x, y)| {
-                if !(x.y == y.y && x.y == 0 && get_random_bool()) {
+                if !(x.y == y.y && x.y == 0 && get_random_bool(rng)) {
                     x.shift_horizontal();
                     y.shift_horizontal();
                 }
@@ -161,11 +169,16 @@ fn add_end_horizontal(
 }
 
 // 二次元座標中に存在する線分を、垂直s方向に+1移動させる
-fn shift_vertical(edges: &mut Vec<(Point<usize>, Point<usize>)>, height: usize, offset: Offset) {
+fn shift_vertical(
+    edges: &mut Vec<(Point<usize>, Point<usize>)>,
+    height: usize,
+    offset: Offset,
+    rng: &mut MazeRng,
+) {
     match offset {
         Offset::Zero => {
             edges.iter_mut().for_each(|(x, y)| {
-                if x.x == y.x && x.x == height - 2 && get_random_bool() {
+                if x.x == y.x && x.x == height - 2 && get_random_bool(rng) {
                     x.shift_vertical();
                     y.shift_vertical();
                 }
@@ -173,7 +186,7 @@ fn shift_vertical(edges: &mut Vec<(Point<usize>, Point<usize>)>, height: usize,
         }
         Offset::One => {
             edges.iter_mut().for_each(|(x, y)| {
-                if !(x.x == y.x && x.x == 0 && get_random_bool()) {
+                if !(x.x == y.x && x.x == 0 && get_random_bool(rng)) {
                     x.shift_vertical();
                     y.shift_vertical();
                 }
@@ -203,15 +216,8 @@ fn add_end_vertical(
     }
 }
 
-fn get_random_bool() -> bool {
-    let mut random_bytes = [0u8; 1];
-    match getrandom::getrandom(&mut random_bytes) {
-        Ok(_) => u8::from_ne_bytes(random_bytes) % 2 == 1,
-        Err(_) => {
-            log::warn!("get_randome failed, so return false");
-            false
-        }
-    }
+fn get_random_bool(rng: &mut MazeRng) -> bool {
+    rng.random_bool(0.5)
 }
 
 // #[cfg(test)]
@@ -0,0 +1,213 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::algo::shape::Point;
+
+// 縦heightマス・横widthマスのグリッド上で、通行可能な辺のみを持つグラフ
+pub struct Graph {
+    width: usize,
+    height: usize,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    pub fn from_edges(width: usize, height: usize, edges: &[(Point<usize>, Point<usize>)]) -> Self {
+        let mut adjacency = vec![Vec::new(); width * height];
+        for (from, to) in edges {
+            let (u, v) = (from.flatten(width), to.flatten(width));
+            adjacency[u].push(v);
+            adjacency[v].push(u);
+        }
+        Graph {
+            width,
+            height,
+            adjacency,
+        }
+    }
+
+    fn contains(&self, point: &Point<usize>) -> bool {
+        point.x < self.height && point.y < self.width
+    }
+}
+
+// 幅優先探索によってstartからgoalまでの最短経路を返す
+pub fn bfs(graph: &Graph, start: &Point<usize>, goal: &Point<usize>) -> Option<Vec<Point<usize>>> {
+    if !graph.contains(start) || !graph.contains(goal) {
+        return None;
+    }
+    let (start, goal) = (start.flatten(graph.width), goal.flatten(graph.width));
+
+    let mut prev = vec![None; graph.adjacency.len()];
+    let mut visited = vec![false; graph.adjacency.len()];
+    let mut queue = VecDeque::new();
+    visited[start] = true;
+    queue.push_back(start);
+    while let Some(v) = queue.pop_front() {
+        if v == goal {
+            return Some(restore_path(graph, &prev, goal));
+        }
+        for &nv in &graph.adjacency[v] {
+            if visited[nv] {
+                continue;
+            }
+            visited[nv] = true;
+            prev[nv] = Some(v);
+            queue.push_back(nv);
+        }
+    }
+    None
+}
+
+// マンハッタン距離をヒューリスティックとしたA*によってstartからgoalまでの最短経路を返す
+pub fn a_star(
+    graph: &Graph,
+    start: &Point<usize>,
+    goal: &Point<usize>,
+) -> Option<Vec<Point<usize>>> {
+    if !graph.contains(start) || !graph.contains(goal) {
+        return None;
+    }
+    let heuristic = |v: usize| {
+        let point = Point::from_1d_index(v, graph.width);
+        point.x.abs_diff(goal.x) + point.y.abs_diff(goal.y)
+    };
+    let (start, goal) = (start.flatten(graph.width), goal.flatten(graph.width));
+
+    let mut prev = vec![None; graph.adjacency.len()];
+    let mut cost = vec![usize::MAX; graph.adjacency.len()];
+    let mut heap = BinaryHeap::new();
+    cost[start] = 0;
+    heap.push(Reverse((heuristic(start), start)));
+    while let Some(Reverse((score, v))) = heap.pop() {
+        if v == goal {
+            return Some(restore_path(graph, &prev, goal));
+        }
+        if score > cost[v] + heuristic(v) {
+            continue;
+        }
+        for &nv in &graph.adjacency[v] {
+            let next_cost = cost[v] + 1;
+            if next_cost < cost[nv] {
+                cost[nv] = next_cost;
+                prev[nv] = Some(v);
+                heap.push(Reverse((next_cost + heuristic(nv), nv)));
+            }
+        }
+    }
+    None
+}
+
+fn restore_path(graph: &Graph, prev: &[Option<usize>], goal: usize) -> Vec<Point<usize>> {
+    let mut path = vec![Point::from_1d_index(goal, graph.width)];
+    let mut v = goal;
+    while let Some(p) = prev[v] {
+        path.push(Point::from_1d_index(p, graph.width));
+        v = p;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use crate::algo::kruskal::{self, KruskalResultEdge};
+    use crate::algo::rng;
+
+    fn spanning_tree(width: usize, height: usize, seed: u64) -> Graph {
+        let edges = kruskal::extract_maze_edges_by_kruskal(
+            width,
+            height,
+            1,
+            KruskalResultEdge::Used,
+            &mut rng::seeded(seed),
+        );
+        Graph::from_edges(width, height, &edges)
+    }
+
+    fn is_connected_path(graph: &Graph, path: &[Point<usize>]) -> bool {
+        path.windows(2).all(|pair| {
+            graph.adjacency[pair[0].flatten(graph.width)].contains(&pair[1].flatten(graph.width))
+        })
+    }
+
+    #[rstest]
+    #[case(10, 20, 1)]
+    #[case(30, 30, 2)]
+    #[case(1, 15, 3)]
+    fn bfs_finds_path_between_corners(
+        #[case] width: usize,
+        #[case] height: usize,
+        #[case] seed: u64,
+    ) {
+        let graph = spanning_tree(width, height, seed);
+        let start = Point::new(0, 0);
+        let goal = Point::new(height - 1, width - 1);
+
+        let path = bfs(&graph, &start, &goal).unwrap();
+
+        assert_eq!(Some(&start), path.first());
+        assert_eq!(Some(&goal), path.last());
+        assert!(is_connected_path(&graph, &path));
+    }
+
+    #[rstest]
+    #[case(10, 20, 1)]
+    #[case(30, 30, 2)]
+    #[case(25, 7, 3)]
+    fn a_star_finds_same_path_as_bfs_in_spanning_tree(
+        #[case] width: usize,
+        #[case] height: usize,
+        #[case] seed: u64,
+    ) {
+        let graph = spanning_tree(width, height, seed);
+        let start = Point::new(height / 2, 0);
+        let goal = Point::new(0, width - 1);
+
+        assert_eq!(bfs(&graph, &start, &goal), a_star(&graph, &start, &goal));
+    }
+
+    #[test]
+    fn a_star_finds_shortest_path_in_graph_with_cycle() {
+        // 2x2のグリッドで全ての辺を持つグラフ
+        let edges = vec![
+            (Point::new(0, 0), Point::new(0, 1)),
+            (Point::new(0, 0), Point::new(1, 0)),
+            (Point::new(0, 1), Point::new(1, 1)),
+            (Point::new(1, 0), Point::new(1, 1)),
+        ];
+        let graph = Graph::from_edges(2, 2, &edges);
+
+        let path = a_star(&graph, &Point::new(0, 0), &Point::new(1, 1)).unwrap();
+
+        assert_eq!(3, path.len());
+    }
+
+    #[test]
+    fn start_equals_goal_returns_single_point() {
+        let graph = spanning_tree(5, 5, 0);
+        let point = Point::new(2, 3);
+
+        assert_eq!(Some(vec![Point::new(2, 3)]), bfs(&graph, &point, &point));
+        assert_eq!(Some(vec![Point::new(2, 3)]), a_star(&graph, &point, &point));
+    }
+
+    #[test]
+    fn out_of_range_returns_none() {
+        let graph = spanning_tree(5, 5, 0);
+
+        assert_eq!(None, bfs(&graph, &Point::new(0, 0), &Point::new(5, 0)));
+        assert_eq!(None, a_star(&graph, &Point::new(0, 5), &Point::new(0, 0)));
+    }
+
+    #[test]
+    fn disconnected_goal_returns_none() {
+        let edges = vec![(Point::new(0, 0), Point::new(0, 1))];
+        let graph = Graph::from_edges(3, 1, &edges);
+
+        assert_eq!(None, bfs(&graph, &Point::new(0, 0), &Point::new(0, 2)));
+        assert_eq!(None, a_star(&graph, &Point::new(0, 0), &Point::new(0, 2)));
+    }
+}
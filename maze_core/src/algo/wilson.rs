@@ -0,0 +1,47 @@
+use rand::prelude::*;
+
+use crate::algo::generator::{MazeGenerator, adjacent_cells, to_passage};
+use crate::algo::rng::MazeRng;
+use crate::algo::shape::Point;
+
+// ループを消去したランダムウォークで迷路を広げる。全域木から一様に選ばれた迷路になる
+pub struct Wilson;
+
+impl MazeGenerator for Wilson {
+    fn passages(
+        &self,
+        width: usize,
+        height: usize,
+        rng: &mut MazeRng,
+    ) -> Vec<(Point<usize>, Point<usize>)> {
+        let size = width * height;
+        let mut passages = Vec::with_capacity(size.saturating_sub(1));
+        let mut in_maze = vec![false; size];
+        in_maze[rng.random_range(0..size)] = true;
+
+        let mut cells: Vec<usize> = (0..size).collect();
+        cells.shuffle(rng);
+        // ランダムウォーク中に各マスから最後に進んだ先のマス
+        let mut next = vec![0; size];
+
+        for start in cells {
+            if in_maze[start] {
+                continue;
+            }
+            let mut cell = start;
+            while !in_maze[cell] {
+                let adjacent = adjacent_cells(cell, width, height);
+                next[cell] = adjacent[rng.random_range(0..adjacent.len())];
+                cell = next[cell];
+            }
+
+            let mut cell = start;
+            while !in_maze[cell] {
+                in_maze[cell] = true;
+                passages.push(to_passage(cell, next[cell], width));
+                cell = next[cell];
+            }
+        }
+        passages
+    }
+}
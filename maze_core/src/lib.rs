@@ -0,0 +1,201 @@
+pub mod algo;
+pub mod maze;
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::algo::aldous_broder::AldousBroder;
+use crate::algo::backtracker::RecursiveBacktracker;
+use crate::algo::binary_tree::BinaryTree;
+use crate::algo::eller::Eller;
+use crate::algo::growing_tree::GrowingTree;
+use crate::algo::prim::Prim;
+use crate::algo::recursive_division::RecursiveDivision;
+use crate::algo::rng::{self, MazeRng};
+use crate::algo::shape::Point;
+use crate::algo::sidewinder::Sidewinder;
+use crate::algo::solver;
+use crate::algo::wilson::Wilson;
+use crate::maze::{perfect_maze, random_maze, single_stroke_maze};
+
+pub use crate::maze::model::{Maze, Wall};
+pub use crate::maze::style::RenderStyle;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MazeType {
+    Random,
+    SingleStroke,
+    RecursiveBacktracker,
+    Prim,
+    Wilson,
+    AldousBroder,
+    Eller,
+    BinaryTree,
+    Sidewinder,
+    GrowingTree,
+    RecursiveDivision,
+}
+
+impl MazeType {
+    pub const ALL: [MazeType; 11] = [
+        MazeType::Random,
+        MazeType::SingleStroke,
+        MazeType::RecursiveBacktracker,
+        MazeType::Prim,
+        MazeType::Wilson,
+        MazeType::AldousBroder,
+        MazeType::Eller,
+        MazeType::BinaryTree,
+        MazeType::Sidewinder,
+        MazeType::GrowingTree,
+        MazeType::RecursiveDivision,
+    ];
+
+    // URLのクエリなどで使うケバブケースの名前
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MazeType::Random => "random",
+            MazeType::SingleStroke => "single-stroke",
+            MazeType::RecursiveBacktracker => "recursive-backtracker",
+            MazeType::Prim => "prim",
+            MazeType::Wilson => "wilson",
+            MazeType::AldousBroder => "aldous-broder",
+            MazeType::Eller => "eller",
+            MazeType::BinaryTree => "binary-tree",
+            MazeType::Sidewinder => "sidewinder",
+            MazeType::GrowingTree => "growing-tree",
+            MazeType::RecursiveDivision => "recursive-division",
+        }
+    }
+}
+
+impl fmt::Display for MazeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MazeType {
+    type Err = MazeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MazeType::ALL
+            .into_iter()
+            .find(|maze| maze.as_str() == s)
+            .ok_or_else(|| MazeError::UnknownType(s.to_string()))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolverType {
+    Bfs,
+    AStar,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MazeError {
+    InvalidSize { row: usize, col: usize },
+    UnknownType(String),
+    InvalidStyle,
+    NoPath,
+    Encode(String),
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MazeError::InvalidSize { row, col } => {
+                write!(f, "maze of row: {}, col: {} cannot be created", row, col)
+            }
+            MazeError::UnknownType(name) => write!(f, "unknown maze type: {}", name),
+            MazeError::InvalidStyle => f.write_str("invalid render style"),
+            MazeError::NoPath => f.write_str("no path found between start and goal"),
+            MazeError::Encode(message) => write!(f, "failed to encode maze: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for MazeError {}
+
+// 同じseedからは常に同じ迷路を生成する
+pub fn generate(row: usize, col: usize, maze: MazeType, seed: u64) -> Result<Maze, MazeError> {
+    if !validate(row, col, maze) {
+        return Err(MazeError::InvalidSize { row, col });
+    }
+    Ok(build_maze(row, col, maze, &mut rng::seeded(seed)))
+}
+
+pub fn validate(row: usize, col: usize, maze: MazeType) -> bool {
+    match maze {
+        MazeType::Random => random_maze::validate(row, col),
+        MazeType::SingleStroke => single_stroke_maze::validate(row, col),
+        _ => perfect_maze::validate(row, col),
+    }
+}
+
+fn build_maze(row: usize, col: usize, maze: MazeType, rng: &mut MazeRng) -> Maze {
+    match maze {
+        MazeType::Random => random_maze::generate(row, col, rng),
+        MazeType::SingleStroke => single_stroke_maze::generate(row, col, rng),
+        MazeType::RecursiveBacktracker => {
+            perfect_maze::generate(row, col, &RecursiveBacktracker, rng)
+        }
+        MazeType::Prim => perfect_maze::generate(row, col, &Prim, rng),
+        MazeType::Wilson => perfect_maze::generate(row, col, &Wilson, rng),
+        MazeType::AldousBroder => perfect_maze::generate(row, col, &AldousBroder, rng),
+        MazeType::Eller => perfect_maze::generate(row, col, &Eller, rng),
+        MazeType::BinaryTree => perfect_maze::generate(row, col, &BinaryTree, rng),
+        MazeType::Sidewinder => perfect_maze::generate(row, col, &Sidewinder, rng),
+        MazeType::GrowingTree => perfect_maze::generate(row, col, &GrowingTree::default(), rng),
+        MazeType::RecursiveDivision => perfect_maze::generate(row, col, &RecursiveDivision, rng),
+    }
+}
+
+pub fn find_path(
+    maze: &Maze,
+    start: Point<usize>,
+    goal: Point<usize>,
+    solver: SolverType,
+) -> Option<Vec<Point<usize>>> {
+    let graph = solver::Graph::from_edges(maze.cols(), maze.rows(), &maze.passages());
+    match solver {
+        SolverType::Bfs => solver::bfs(&graph, &start, &goal),
+        SolverType::AStar => solver::a_star(&graph, &start, &goal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maze_type_round_trips_through_name() {
+        for maze in MazeType::ALL {
+            assert_eq!(Ok(maze), maze.as_str().parse());
+        }
+        assert_eq!(
+            Err(MazeError::UnknownType("maze".to_string())),
+            "maze".parse::<MazeType>()
+        );
+    }
+
+    #[test]
+    fn generate_rejects_invalid_size() {
+        assert_eq!(
+            Err(MazeError::InvalidSize { row: 0, col: 3 }),
+            generate(0, 3, MazeType::Prim, 1)
+        );
+    }
+
+    #[test]
+    fn generate_is_reproducible() {
+        for maze in MazeType::ALL {
+            let (row, col) = if maze == MazeType::SingleStroke {
+                (6, 8)
+            } else {
+                (7, 9)
+            };
+            assert_eq!(generate(row, col, maze, 42), generate(row, col, maze, 42));
+        }
+    }
+}
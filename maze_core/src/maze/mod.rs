@@ -0,0 +1,8 @@
+pub mod model;
+pub mod perfect_maze;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod random_maze;
+pub mod single_stroke_maze;
+pub mod style;
+pub mod svg;
@@ -0,0 +1,238 @@
+use crate::algo::{grid, shape::Point};
+
+// セルの壁を表すビット
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Wall {
+    Top = 1,
+    Right = 2,
+    Bottom = 4,
+    Left = 8,
+}
+
+const ALL_WALLS: u8 = Wall::Top as u8 | Wall::Right as u8 | Wall::Bottom as u8 | Wall::Left as u8;
+
+// 縦rowsマス・横colsマスの迷路。各セルの壁をビットマスクで保持する
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Maze {
+    rows: usize,
+    cols: usize,
+    walls: Vec<u8>,
+}
+
+impl Maze {
+    // 全てのセルが壁で囲まれた迷路
+    pub fn closed(rows: usize, cols: usize) -> Self {
+        Maze {
+            rows,
+            cols,
+            walls: vec![ALL_WALLS; rows * cols],
+        }
+    }
+
+    // 壁が一つもない迷路
+    pub fn open(rows: usize, cols: usize) -> Self {
+        Maze {
+            rows,
+            cols,
+            walls: vec![0; rows * cols],
+        }
+    }
+
+    // 隣接するセル間の壁を取り除く
+    pub fn remove_wall_between(&mut self, from: &Point<usize>, to: &Point<usize>) {
+        let (first, second) = if from.flatten(self.cols) < to.flatten(self.cols) {
+            (from, to)
+        } else {
+            (to, from)
+        };
+        if first.x == second.x && first.y + 1 == second.y {
+            self.clear(first.x, first.y, Wall::Right);
+            self.clear(second.x, second.y, Wall::Left);
+        } else if first.y == second.y && first.x + 1 == second.x {
+            self.clear(first.x, first.y, Wall::Bottom);
+            self.clear(second.x, second.y, Wall::Top);
+        }
+    }
+
+    // 格子点fromからtoまでの線分を壁として追加する
+    pub fn add_wall_segment(&mut self, from: &Point<usize>, to: &Point<usize>) {
+        if from.x == to.x {
+            let row = from.x;
+            for col in from.y.min(to.y)..from.y.max(to.y) {
+                if row < self.rows {
+                    self.set(row, col, Wall::Top);
+                }
+                if row > 0 {
+                    self.set(row - 1, col, Wall::Bottom);
+                }
+            }
+        } else if from.y == to.y {
+            let col = from.y;
+            for row in from.x.min(to.x)..from.x.max(to.x) {
+                if col < self.cols {
+                    self.set(row, col, Wall::Left);
+                }
+                if col > 0 {
+                    self.set(row, col - 1, Wall::Right);
+                }
+            }
+        }
+    }
+
+    // 壁で隔てられていない隣接セルを返す
+    pub fn neighbours(&self, cell: &Point<usize>) -> Vec<Point<usize>> {
+        let (row, col) = (cell.x, cell.y);
+        let mut neighbours = Vec::with_capacity(4);
+        if row > 0 && !self.has_wall(row, col, Wall::Top) {
+            neighbours.push(Point::new(row - 1, col));
+        }
+        if col + 1 < self.cols && !self.has_wall(row, col, Wall::Right) {
+            neighbours.push(Point::new(row, col + 1));
+        }
+        if row + 1 < self.rows && !self.has_wall(row, col, Wall::Bottom) {
+            neighbours.push(Point::new(row + 1, col));
+        }
+        if col > 0 && !self.has_wall(row, col, Wall::Left) {
+            neighbours.push(Point::new(row, col - 1));
+        }
+        neighbours
+    }
+
+    // 壁を格子点間の線分として返す。各セルの上と左の壁に加え、外周の下と右の壁を含む
+    pub fn wall_segments(&self) -> Vec<(Point<usize>, Point<usize>)> {
+        let mut segments = Vec::new();
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                if self.has_wall(row, col, Wall::Top) {
+                    segments.push((Point::new(row, col), Point::new(row, col + 1)));
+                }
+                if self.has_wall(row, col, Wall::Left) {
+                    segments.push((Point::new(row, col), Point::new(row + 1, col)));
+                }
+                if row + 1 == self.rows && self.has_wall(row, col, Wall::Bottom) {
+                    segments.push((Point::new(row + 1, col), Point::new(row + 1, col + 1)));
+                }
+                if col + 1 == self.cols && self.has_wall(row, col, Wall::Right) {
+                    segments.push((Point::new(row, col + 1), Point::new(row + 1, col + 1)));
+                }
+            }
+        }
+        segments
+    }
+
+    // 通行可能な隣接セルの組を全て返す
+    pub fn passages(&self) -> Vec<(Point<usize>, Point<usize>)> {
+        let mut passages = Vec::new();
+        for idx in 0..self.walls.len() {
+            let (row, col) = grid::index_1d_to_2d(idx, self.cols);
+            for next in self.neighbours(&Point::new(row, col)) {
+                if next.flatten(self.cols) > idx {
+                    passages.push((Point::new(row, col), next));
+                }
+            }
+        }
+        passages
+    }
+
+    fn set(&mut self, row: usize, col: usize, wall: Wall) {
+        let idx = grid::index_2d_to_1d(row, col, self.cols);
+        self.walls[idx] |= wall as u8;
+    }
+
+    fn clear(&mut self, row: usize, col: usize, wall: Wall) {
+        let idx = grid::index_2d_to_1d(row, col, self.cols);
+        self.walls[idx] &= !(wall as u8);
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    // 行優先で並べた全セルの壁のビットマスク
+    pub fn walls(&self) -> &[u8] {
+        &self.walls
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> u8 {
+        self.walls[grid::index_2d_to_1d(row, col, self.cols)]
+    }
+
+    pub fn has_wall(&self, row: usize, col: usize, wall: Wall) -> bool {
+        self.cell(row, col) & wall as u8 != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_WALLS: u8 =
+        Wall::Top as u8 | Wall::Right as u8 | Wall::Bottom as u8 | Wall::Left as u8;
+
+    #[test]
+    fn remove_wall_between_horizontal_neighbours() {
+        let mut maze = Maze::closed(2, 2);
+        maze.remove_wall_between(&Point::new(0, 1), &Point::new(0, 0));
+
+        assert!(!maze.has_wall(0, 0, Wall::Right));
+        assert!(!maze.has_wall(0, 1, Wall::Left));
+        assert!(maze.has_wall(0, 0, Wall::Bottom));
+    }
+
+    #[test]
+    fn remove_wall_between_vertical_neighbours() {
+        let mut maze = Maze::closed(2, 2);
+        maze.remove_wall_between(&Point::new(0, 1), &Point::new(1, 1));
+
+        assert!(!maze.has_wall(0, 1, Wall::Bottom));
+        assert!(!maze.has_wall(1, 1, Wall::Top));
+        assert!(maze.has_wall(0, 1, Wall::Left));
+    }
+
+    #[test]
+    fn add_wall_segment_sets_both_sides() {
+        let mut maze = Maze::open(2, 2);
+        maze.add_wall_segment(&Point::new(1, 0), &Point::new(1, 1));
+        maze.add_wall_segment(&Point::new(0, 1), &Point::new(1, 1));
+
+        assert_eq!(Wall::Bottom as u8 | Wall::Right as u8, maze.cell(0, 0));
+        assert_eq!(Wall::Top as u8, maze.cell(1, 0));
+        assert_eq!(Wall::Left as u8, maze.cell(0, 1));
+        assert_eq!(0, maze.cell(1, 1));
+    }
+
+    #[test]
+    fn passages_are_listed_once() {
+        let mut maze = Maze::open(2, 2);
+        maze.add_wall_segment(&Point::new(1, 0), &Point::new(1, 1));
+
+        let passages = maze.passages();
+
+        assert_eq!(3, passages.len());
+        assert!(!passages.contains(&(Point::new(0, 0), Point::new(1, 0))));
+    }
+
+    #[test]
+    fn wall_segments_of_closed_maze() {
+        let maze = Maze::closed(2, 3);
+
+        // 横線: 3本 x 3段, 縦線: 4本 x 2段
+        assert_eq!(3 * 3 + 4 * 2, maze.wall_segments().len());
+    }
+
+    #[test]
+    fn add_wall_segment_on_boundary() {
+        let mut maze = Maze::open(1, 1);
+        maze.add_wall_segment(&Point::new(0, 0), &Point::new(0, 1));
+        maze.add_wall_segment(&Point::new(1, 1), &Point::new(1, 0));
+        maze.add_wall_segment(&Point::new(0, 0), &Point::new(1, 0));
+        maze.add_wall_segment(&Point::new(0, 1), &Point::new(1, 1));
+
+        assert_eq!(ALL_WALLS, maze.cell(0, 0));
+    }
+}
@@ -0,0 +1,32 @@
+use crate::{
+    algo::{generator::MazeGenerator, rng::MazeRng},
+    maze::model::Maze,
+};
+
+pub fn validate(row: usize, col: usize) -> bool {
+    !(row == 0 || col == 0)
+}
+
+// generatorが選んだ通路以外を壁として、縦rowマス・横colマスの迷路を作成する
+pub fn generate(row: usize, col: usize, generator: &dyn MazeGenerator, rng: &mut MazeRng) -> Maze {
+    log::info!("create perfect maze in row: {}, col: {}", row, col);
+    let mut maze = Maze::closed(row, col);
+    for (from, to) in generator.passages(col, row, rng) {
+        maze.remove_wall_between(&from, &to);
+    }
+    maze
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::backtracker::RecursiveBacktracker;
+    use crate::algo::rng;
+
+    #[test]
+    fn perfect_maze_has_one_passage_less_than_cells() {
+        let maze = generate(12, 9, &RecursiveBacktracker, &mut rng::seeded(4));
+
+        assert_eq!(12 * 9 - 1, maze.passages().len());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::MazeError;
+use crate::algo::shape::Line;
+use crate::maze::model::Maze;
+use crate::maze::style::RenderStyle;
+
+// 1枚あたりの最大ピクセル数(RGBAで16MiB)。巨大な画像の生成でメモリを使い果たさないようにする
+pub const MAX_PIXELS: usize = 2048 * 2048;
+
+// 迷路の壁を塗りつぶしたRGBA画像をPNGにエンコードして返す
+pub fn render(maze: &Maze, style: &RenderStyle) -> Result<Vec<u8>, MazeError> {
+    let width = (maze.cols() as f64 * style.cell_size + style.margin * 2.0).ceil() as usize;
+    let height = (maze.rows() as f64 * style.cell_size + style.margin * 2.0).ceil() as usize;
+    if width == 0 || height == 0 || width.saturating_mul(height) > MAX_PIXELS {
+        return Err(MazeError::InvalidStyle);
+    }
+
+    let stroke = parse_color(&style.stroke_color).ok_or(MazeError::InvalidStyle)?;
+    let background = match &style.background_color {
+        Some(color) => parse_color(color).ok_or(MazeError::InvalidStyle)?,
+        None => [0, 0, 0, 0],
+    };
+
+    let mut pixels = background.repeat(width * height);
+    let half = style.stroke_width / 2.0;
+    for (from, to) in maze.wall_segments() {
+        // SVGのstroke-linecap="square"と同じく、線分の両端も線幅の半分だけ伸ばす
+        let line = Line::from_grid(&from, &to, style.cell_size, style.margin);
+        let left = line.from.x.min(line.to.x) - half;
+        let right = line.from.x.max(line.to.x) + half;
+        let top = line.from.y.min(line.to.y) - half;
+        let bottom = line.from.y.max(line.to.y) + half;
+        fill_rect(
+            &mut pixels,
+            width,
+            height,
+            (left, top, right, bottom),
+            stroke,
+        );
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| MazeError::Encode(e.to_string()))?;
+    writer
+        .write_image_data(&pixels)
+        .map_err(|e| MazeError::Encode(e.to_string()))?;
+    writer
+        .finish()
+        .map_err(|e| MazeError::Encode(e.to_string()))?;
+    Ok(png)
+}
+
+// MAX_PIXELSに収まるまでマスを小さくした見た目を返す。線幅はマスの半分を超えないようにする
+//
+// 1ピクセルのマスでも収まらない場合はそのまま返し、renderでエラーにする
+pub fn fit_style(maze: &Maze, style: &RenderStyle) -> RenderStyle {
+    let (rows, cols) = (maze.rows() as f64, maze.cols() as f64);
+    let margin = style.margin * 2.0;
+    let pixels = |cell: f64| (cols * cell + margin).ceil() * (rows * cell + margin).ceil();
+    if rows == 0.0 || cols == 0.0 || pixels(style.cell_size) <= MAX_PIXELS as f64 {
+        return style.clone();
+    }
+
+    // (cols * c + margin) * (rows * c + margin) = MAX_PIXELS を解き、整数に切り捨てる
+    let a = rows * cols;
+    let b = margin * (rows + cols);
+    let c = margin * margin - MAX_PIXELS as f64;
+    let cell_size = ((-b + (b * b - 4.0 * a * c).sqrt()) / (2.0 * a)).floor();
+    if cell_size < 1.0 {
+        return style.clone();
+    }
+    style
+        .clone()
+        .with_cell_size(cell_size)
+        .with_stroke_width(style.stroke_width.min(cell_size / 2.0))
+}
+
+fn fill_rect(
+    pixels: &mut [u8],
+    width: usize,
+    height: usize,
+    (left, top, right, bottom): (f64, f64, f64, f64),
+    color: [u8; 4],
+) {
+    let x_range = left.round().max(0.0) as usize..(right.round().max(0.0) as usize).min(width);
+    let y_range = top.round().max(0.0) as usize..(bottom.round().max(0.0) as usize).min(height);
+    for y in y_range {
+        for x in x_range.clone() {
+            let idx = (y * width + x) * 4;
+            pixels[idx..idx + 4].copy_from_slice(&color);
+        }
+    }
+}
+
+// "#rgb"または"#rrggbb"形式の色をRGBAに変換する
+fn parse_color(color: &str) -> Option<[u8; 4]> {
+    let hex = color.strip_prefix('#')?;
+    if !hex.is_ascii() {
+        return None;
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    match hex.len() {
+        3 => {
+            let mut rgba = [255; 4];
+            for (i, c) in hex.chars().enumerate() {
+                rgba[i] = channel(&c.to_string())? * 17;
+            }
+            Some(rgba)
+        }
+        6 => Some([
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+            255,
+        ]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+    #[test]
+    fn render_closed_maze_as_png() {
+        let maze = Maze::closed(2, 3);
+        let style = RenderStyle::new().with_cell_size(10.0).with_margin(5.0);
+
+        let png = render(&maze, &style).unwrap();
+
+        assert_eq!(PNG_SIGNATURE, png[..8]);
+        // IHDRチャンクの幅と高さ
+        assert_eq!(40u32.to_be_bytes(), png[16..20]);
+        assert_eq!(30u32.to_be_bytes(), png[20..24]);
+    }
+
+    #[test]
+    fn parse_short_and_long_colors() {
+        assert_eq!(Some([255, 0, 170, 255]), parse_color("#f0a"));
+        assert_eq!(Some([0x12, 0x34, 0x56, 255]), parse_color("#123456"));
+        assert_eq!(None, parse_color("red"));
+        assert_eq!(None, parse_color("#12345"));
+    }
+
+    #[test]
+    fn reject_non_hex_colors() {
+        let maze = Maze::closed(1, 1);
+        let style = RenderStyle::new().with_stroke_color("black");
+
+        assert_eq!(Err(MazeError::InvalidStyle), render(&maze, &style));
+    }
+
+    #[test]
+    fn shrink_cells_to_fit_pixel_budget() {
+        let maze = Maze::closed(400, 400);
+        let style = fit_style(&maze, &RenderStyle::new());
+
+        assert_eq!(5.0, style.cell_size);
+        assert_eq!(2.0, style.stroke_width);
+        assert!(render(&maze, &style).is_ok());
+
+        // 収まるものはそのまま
+        let small = Maze::closed(10, 10);
+        assert_eq!(20.0, fit_style(&small, &RenderStyle::new()).cell_size);
+    }
+
+    #[test]
+    fn reject_too_large_images() {
+        let maze = Maze::closed(1000, 1000);
+
+        assert_eq!(
+            Err(MazeError::InvalidStyle),
+            render(&maze, &RenderStyle::new())
+        );
+    }
+}
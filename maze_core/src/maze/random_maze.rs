@@ -0,0 +1,86 @@
+use crate::{
+    algo::{kruskal, rng::MazeRng, shape::Point},
+    maze::model::Maze,
+};
+
+pub fn validate(row: usize, col: usize) -> bool {
+    !(row == 0 || col == 0)
+}
+
+// 最小全域木で使用しなかった辺を壁として、縦rowマス・横colマスの迷路を作成する
+pub fn generate(row: usize, col: usize, rng: &mut MazeRng) -> Maze {
+    log::info!("create maze in row: {}, col: {}", row, col);
+    let unused_edges = kruskal::extract_maze_edges_by_kruskal(
+        col,
+        row,
+        1,
+        kruskal::KruskalResultEdge::Unused,
+        rng,
+    );
+
+    let mut maze = Maze::open(row, col);
+    maze.add_wall_segment(&Point::new(0, 0), &Point::new(0, col));
+    maze.add_wall_segment(&Point::new(row, 0), &Point::new(row, col));
+    maze.add_wall_segment(&Point::new(0, 0), &Point::new(row, 0));
+    maze.add_wall_segment(&Point::new(0, col), &Point::new(row, col));
+    for (from, to) in unused_edges {
+        let (start, end) = grid_boundary(&from, &to);
+        maze.add_wall_segment(&start, &end);
+    }
+    maze
+}
+
+// 隣接する2マスの境界となる線分の端点を返す
+fn grid_boundary(from: &Point<usize>, to: &Point<usize>) -> (Point<usize>, Point<usize>) {
+    if from.x == to.x {
+        (Point::new(from.x, to.y), Point::new(to.x + 1, to.y))
+    } else {
+        (Point::new(to.x, from.y), Point::new(to.x, to.y + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::rng;
+    use crate::maze::model::Wall;
+    use rstest::*;
+
+    #[rstest]
+    #[case(1, 1)]
+    #[case(1, 10)]
+    #[case(10, 15)]
+    #[case(40, 30)]
+    fn random_maze_has_one_passage_less_than_cells(#[case] row: usize, #[case] col: usize) {
+        let maze = generate(row, col, &mut rng::seeded(0));
+
+        let mut passages = 0;
+        for r in 0..row {
+            for c in 0..col {
+                if c + 1 < col && !maze.has_wall(r, c, Wall::Right) {
+                    passages += 1;
+                }
+                if r + 1 < row && !maze.has_wall(r, c, Wall::Bottom) {
+                    passages += 1;
+                }
+            }
+        }
+
+        assert_eq!(row * col - 1, passages);
+    }
+
+    #[test]
+    fn random_maze_is_surrounded_by_walls() {
+        let (row, col) = (8, 12);
+        let maze = generate(row, col, &mut rng::seeded(5));
+
+        for c in 0..col {
+            assert!(maze.has_wall(0, c, Wall::Top));
+            assert!(maze.has_wall(row - 1, c, Wall::Bottom));
+        }
+        for r in 0..row {
+            assert!(maze.has_wall(r, 0, Wall::Left));
+            assert!(maze.has_wall(r, col - 1, Wall::Right));
+        }
+    }
+}
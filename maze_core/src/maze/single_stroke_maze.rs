@@ -0,0 +1,146 @@
+use crate::{
+    algo::{rng::MazeRng, shape::Point, single_stroke},
+    maze::model::Maze,
+};
+
+pub fn validate(row: usize, col: usize) -> bool {
+    if row == 0 || col == 0 {
+        return false;
+    }
+    if row % 2 == 1 && col % 2 == 1 {
+        return false;
+    }
+    if row <= 2 && col <= 2 {
+        return false;
+    }
+
+    true
+}
+
+// 一筆書きで全てのセルを通れる、縦rowマス・横colマスの迷路を作成する
+pub fn generate(row: usize, col: usize, rng: &mut MazeRng) -> Maze {
+    log::debug!("create single stroke maze in row: {}, col: {}", row, col);
+    let edges = single_stroke::single_stroke_maze(col, row, rng);
+
+    let mut maze = Maze::open(row, col);
+    for (from, to) in edges {
+        maze.add_wall_segment(&from, &to);
+    }
+    maze
+}
+
+// 左上のマスから一筆書きの経路を辿り、通過する順にマスを返す
+pub fn stroke_order(maze: &Maze) -> Vec<Point<usize>> {
+    let mut order = vec![Point::new(0, 0)];
+    let mut prev: Option<Point<usize>> = None;
+    loop {
+        let current = &order[order.len() - 1];
+        let next = maze
+            .neighbours(current)
+            .into_iter()
+            .find(|next| Some(next) != prev.as_ref());
+        match next {
+            Some(next) if next != order[0] => {
+                prev = Some(Point::new(current.x, current.y));
+                order.push(next);
+            }
+            _ => break,
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::rng;
+    use crate::maze::model::Wall;
+    use rstest::*;
+    use std::collections::HashSet;
+
+    #[rstest]
+    #[case(10, 15)]
+    #[case(7, 4)]
+    #[case(20, 20)]
+    fn single_stroke_maze_walls_are_consistent(#[case] row: usize, #[case] col: usize) {
+        let maze = generate(row, col, &mut rng::seeded(1));
+
+        for r in 0..row {
+            for c in 0..col {
+                if c + 1 < col {
+                    assert_eq!(
+                        maze.has_wall(r, c, Wall::Right),
+                        maze.has_wall(r, c + 1, Wall::Left)
+                    );
+                }
+                if r + 1 < row {
+                    assert_eq!(
+                        maze.has_wall(r, c, Wall::Bottom),
+                        maze.has_wall(r + 1, c, Wall::Top)
+                    );
+                }
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(10, 15)]
+    #[case(7, 4)]
+    #[case(3, 2)]
+    fn single_stroke_maze_forms_one_cycle(#[case] row: usize, #[case] col: usize) {
+        let maze = generate(row, col, &mut rng::seeded(2));
+
+        let mut passages = 0;
+        for r in 0..row {
+            for c in 0..col {
+                let openings = [Wall::Top, Wall::Right, Wall::Bottom, Wall::Left]
+                    .into_iter()
+                    .filter(|wall| !maze.has_wall(r, c, *wall))
+                    .count();
+                assert_eq!(2, openings);
+                if c + 1 < col && !maze.has_wall(r, c, Wall::Right) {
+                    passages += 1;
+                }
+                if r + 1 < row && !maze.has_wall(r, c, Wall::Bottom) {
+                    passages += 1;
+                }
+            }
+        }
+
+        assert_eq!(row * col, passages);
+    }
+
+    #[rstest]
+    #[case(10, 15)]
+    #[case(7, 4)]
+    #[case(3, 2)]
+    fn stroke_order_visits_every_cell_once(#[case] row: usize, #[case] col: usize) {
+        let maze = generate(row, col, &mut rng::seeded(3));
+
+        let order = stroke_order(&maze);
+
+        let visited: HashSet<&Point<usize>> = order.iter().collect();
+        assert_eq!(row * col, order.len());
+        assert_eq!(row * col, visited.len());
+        assert!(
+            order
+                .windows(2)
+                .all(|pair| maze.neighbours(&pair[0]).contains(&pair[1]))
+        );
+    }
+
+    #[rstest]
+    #[case(10, 15, 3)]
+    #[case(21, 8, 11)]
+    #[case(30, 30, 100)]
+    fn same_seed_creates_same_single_stroke_maze(
+        #[case] width: usize,
+        #[case] height: usize,
+        #[case] seed: u64,
+    ) {
+        let first = single_stroke::single_stroke_maze(width, height, &mut rng::seeded(seed));
+        let second = single_stroke::single_stroke_maze(width, height, &mut rng::seeded(seed));
+
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,74 @@
+// SVGやPNGとして書き出す際の見た目の設定
+#[derive(Clone, Debug)]
+pub struct RenderStyle {
+    pub(crate) cell_size: f64,
+    pub(crate) stroke_width: f64,
+    pub(crate) stroke_color: String,
+    pub(crate) background_color: Option<String>,
+    pub(crate) margin: f64,
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        RenderStyle {
+            cell_size: 20.0,
+            stroke_width: 2.0,
+            stroke_color: "#000000".to_string(),
+            background_color: Some("#ffffff".to_string()),
+            margin: 10.0,
+        }
+    }
+}
+
+impl RenderStyle {
+    pub fn new() -> Self {
+        RenderStyle::default()
+    }
+
+    pub fn with_cell_size(mut self, cell_size: f64) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    pub fn with_stroke_width(mut self, stroke_width: f64) -> Self {
+        self.stroke_width = stroke_width;
+        self
+    }
+
+    pub fn with_stroke_color(mut self, color: &str) -> Self {
+        self.stroke_color = color.to_string();
+        self
+    }
+
+    // Noneを渡すと背景を透過にする
+    pub fn with_background_color(mut self, color: Option<String>) -> Self {
+        self.background_color = color;
+        self
+    }
+
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn validate(&self) -> bool {
+        [self.cell_size, self.stroke_width]
+            .iter()
+            .all(|v| v.is_finite() && *v > 0.0)
+            && self.margin.is_finite()
+            && self.margin >= 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_style_is_rejected() {
+        assert!(RenderStyle::new().validate());
+        assert!(!RenderStyle::new().with_stroke_width(0.0).validate());
+        assert!(!RenderStyle::new().with_cell_size(f64::NAN).validate());
+        assert!(!RenderStyle::new().with_margin(-1.0).validate());
+    }
+}
@@ -0,0 +1,83 @@
+use crate::algo::shape::Line;
+use crate::maze::model::Maze;
+use crate::maze::style::RenderStyle;
+
+// 迷路の壁を一つのpath要素にまとめた、単体で表示できるSVG文字列を返す
+pub fn render(maze: &Maze, style: &RenderStyle) -> String {
+    let width = maze.cols() as f64 * style.cell_size + style.margin * 2.0;
+    let height = maze.rows() as f64 * style.cell_size + style.margin * 2.0;
+
+    let path: String = maze
+        .wall_segments()
+        .iter()
+        .map(|(from, to)| {
+            Line::from_grid(from, to, style.cell_size, style.margin).to_path_command()
+        })
+        .collect();
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    );
+    if let Some(background) = &style.background_color {
+        svg.push_str(&format!(
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            escape_attribute(background)
+        ));
+    }
+    svg.push_str(&format!(
+        r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="square"/>"#,
+        path,
+        escape_attribute(&style.stroke_color),
+        style.stroke_width
+    ));
+    svg.push_str("</svg>");
+    svg
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_closed_maze_with_margin() {
+        let maze = Maze::closed(1, 2);
+        let style = RenderStyle::new().with_cell_size(10.0).with_margin(5.0);
+
+        let svg = render(&maze, &style);
+
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="20" viewBox="0 0 30 20">"#
+        ));
+        assert!(svg.contains("M5 5L15 5"));
+        assert!(svg.contains("M25 5L25 15"));
+        assert_eq!(maze.wall_segments().len(), svg.matches('M').count());
+        assert!(svg.ends_with("</svg>"));
+    }
+
+    #[test]
+    fn render_without_background() {
+        let maze = Maze::closed(2, 2);
+        let style = RenderStyle::new().with_background_color(None);
+
+        assert!(!render(&maze, &style).contains("<rect"));
+    }
+
+    #[test]
+    fn render_escapes_colors() {
+        let maze = Maze::closed(1, 1);
+        let style = RenderStyle::new().with_stroke_color(r#"red"/><script>"#);
+
+        let svg = render(&maze, &style);
+
+        assert!(!svg.contains("<script>"));
+        assert!(svg.contains("red&quot;/&gt;&lt;script&gt;"));
+    }
+}